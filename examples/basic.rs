@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use rs_bean::bean::{BeanContainer, Scope};
+use rs_bean::error::BeanError;
 
 // Define some simple structs
 struct Database {
@@ -54,7 +55,7 @@ impl OrderService {
     }
 }
 
-fn main() -> Result<(), String> {
+fn main() -> Result<(), BeanError> {
     // Create container
     let container = BeanContainer::new();
 
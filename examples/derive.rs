@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use rs_bean::bean::{BeanContainer, Scope};
+use rs_bean::error::BeanError;
+use rs_bean::factory::Injectable;
+
+struct Database {
+    connection_string: String,
+}
+
+impl Database {
+    fn query(&self, sql: &str) {
+        println!("Executing query on {}: {}", self.connection_string, sql);
+    }
+}
+
+// Fields are resolved from the container, no factory to write
+#[derive(Injectable)]
+struct UserService {
+    db: Arc<Database>,
+}
+
+impl UserService {
+    fn get_user(&self, id: u32) {
+        self.db
+            .query(&format!("SELECT * FROM users WHERE id = {}", id));
+    }
+}
+
+#[derive(Injectable)]
+struct OrderService {
+    db: Arc<Database>,
+    user_service: Arc<UserService>,
+    // Resolved if registered
+    audit: Option<Arc<AuditLog>>,
+}
+
+impl OrderService {
+    fn create_order(&self, user_id: u32, product: &str) {
+        self.user_service.get_user(user_id);
+        self.db.query(&format!(
+            "INSERT INTO orders (user_id, product) VALUES ({}, '{}')",
+            user_id, product
+        ));
+        if let Some(audit) = &self.audit {
+            audit.record(&format!("order of {} by user {}", product, user_id));
+        }
+    }
+}
+
+struct AuditLog;
+
+impl AuditLog {
+    fn record(&self, event: &str) {
+        println!("Audit: {}", event);
+    }
+}
+
+fn main() -> Result<(), BeanError> {
+    let container = BeanContainer::new();
+
+    container.register::<Database, _>(Scope::Singleton, |_deps| {
+        Ok(Database {
+            connection_string: "postgresql://localhost:5432/mydb".to_string(),
+        })
+    })?;
+    container.register_injectable::<UserService>(Scope::Singleton)?;
+    container.register_injectable::<OrderService>(Scope::Singleton)?;
+
+    let order_service = container.get::<OrderService>();
+    order_service.create_order(1, "Laptop");
+
+    Ok(())
+}
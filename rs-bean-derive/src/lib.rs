@@ -0,0 +1,258 @@
+//! Derive macros for `rs-bean`, re-exported by its `derive` feature
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{
+    Data, DeriveInput, Error, Field, Fields, GenericArgument, LitStr, PathArguments, Type,
+    parse_macro_input,
+};
+
+/// Implement `rs_bean::factory::Injectable`, building the struct from its dependencies
+///
+/// Fields are resolved from their type:
+/// - `Arc<T>`: the bean `T`
+/// - `Option<Arc<T>>`: the bean `T` if registered
+/// - `Vec<Arc<T>>`: every bean of type `T`
+///
+/// `#[inject(name = "...")]` resolves an `Arc<T>` field by name, `#[inject(default)]` sets a
+/// field to its `Default` value. `Arc<T>` fields are declared as the bean's dependencies.
+#[proc_macro_derive(Injectable, attributes(inject))]
+pub fn derive_injectable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(
+            input.ident.span(),
+            "Injectable can only be derived for structs",
+        ));
+    };
+    let dependencies = data
+        .fields
+        .iter()
+        .map(field_dependency)
+        .collect::<Result<Vec<_>, Error>>()?
+        .into_iter()
+        .flatten();
+    let body = match &data.fields {
+        Fields::Named(fields) => {
+            let fields = fields
+                .named
+                .iter()
+                .map(|field| {
+                    let ident = &field.ident;
+                    let value = resolve_field(field)?;
+                    Ok(quote! { #ident: #value })
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            quote! { Self { #(#fields),* } }
+        }
+        Fields::Unnamed(fields) => {
+            let fields = fields
+                .unnamed
+                .iter()
+                .map(resolve_field)
+                .collect::<Result<Vec<_>, Error>>()?;
+            quote! { Self(#(#fields),*) }
+        }
+        Fields::Unit => quote! { Self },
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::rs_bean::factory::Injectable for #ident #ty_generics #where_clause {
+            fn inject(
+                deps: &mut ::rs_bean::bean::Dependencies,
+            ) -> ::std::result::Result<Self, ::rs_bean::error::BeanError> {
+                ::std::result::Result::Ok(#body)
+            }
+
+            fn dependencies() -> ::std::vec::Vec<::rs_bean::bean::Dependency> {
+                ::std::vec![#(#dependencies),*]
+            }
+        }
+    })
+}
+
+/// Register the struct when the container scans for components, see `BeanContainer::scan`
+///
+/// The struct must implement `Injectable`, e.g. with `#[derive(Injectable)]`, which builds it.
+/// Arguments, all optional:
+/// - `scope = "..."`: `"singleton"` (default), `"prototype"`, or the name of a custom scope
+/// - `name = "..."`: register by name instead of by type
+/// - `profile = "..."`: register only when the profile is active
+#[proc_macro_attribute]
+pub fn component(args: TokenStream, item: TokenStream) -> TokenStream {
+    let mut scope: Option<LitStr> = None;
+    let mut name: Option<LitStr> = None;
+    let mut profile: Option<LitStr> = None;
+    let parser = syn::meta::parser(|meta| {
+        let target = if meta.path.is_ident("scope") {
+            &mut scope
+        } else if meta.path.is_ident("name") {
+            &mut name
+        } else if meta.path.is_ident("profile") {
+            &mut profile
+        } else {
+            return Err(meta.error("expected `scope`, `name` or `profile`"));
+        };
+        *target = Some(meta.value()?.parse()?);
+        Ok(())
+    });
+    parse_macro_input!(args with parser);
+    let item = parse_macro_input!(item as DeriveInput);
+    expand_component(item, scope, name, profile)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_component(
+    item: DeriveInput,
+    scope: Option<LitStr>,
+    name: Option<LitStr>,
+    profile: Option<LitStr>,
+) -> Result<TokenStream2, Error> {
+    if !matches!(item.data, Data::Struct(_)) {
+        return Err(Error::new(
+            item.ident.span(),
+            "#[component] can only be applied to structs",
+        ));
+    }
+    if !item.generics.params.is_empty() {
+        return Err(Error::new(
+            item.generics.span(),
+            "components can't be generic, register each instantiation instead",
+        ));
+    }
+    let scope = match scope {
+        None => quote! { ::rs_bean::bean::Scope::Singleton },
+        Some(scope) => match scope.value().as_str() {
+            "singleton" => quote! { ::rs_bean::bean::Scope::Singleton },
+            "prototype" => quote! { ::rs_bean::bean::Scope::Prototype },
+            "" => return Err(Error::new(scope.span(), "scope name can't be empty")),
+            _ => quote! { ::rs_bean::bean::Scope::Scoped(#scope) },
+        },
+    };
+    let name = name.map(|name| quote! { .named(#name) });
+    let profile = profile.map(|profile| quote! { .profile(#profile) });
+    let ident = &item.ident;
+    let type_name = ident.to_string();
+    Ok(quote! {
+        #item
+
+        const _: () = {
+            fn definition() -> ::rs_bean::bean::BeanDef {
+                ::rs_bean::bean::BeanDef::new::<#ident, _>(
+                    #scope,
+                    <#ident as ::rs_bean::factory::Injectable>::inject,
+                )
+                .with_dependencies(<#ident as ::rs_bean::factory::Injectable>::dependencies())
+                #name
+                #profile
+            }
+
+            ::rs_bean::scan::__private::inventory::submit! {
+                ::rs_bean::scan::Component::new(
+                    ::std::concat!(::std::module_path!(), "::", #type_name),
+                    definition,
+                )
+            }
+        };
+    })
+}
+
+enum Injection {
+    Resolve,
+    Named(LitStr),
+    Default,
+}
+
+fn injection(field: &Field) -> Result<Injection, Error> {
+    let mut injection = Injection::Resolve;
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("inject")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                injection = Injection::Named(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("default") {
+                injection = Injection::Default;
+                Ok(())
+            } else {
+                Err(meta.error("expected `name = \"...\"` or `default`"))
+            }
+        })?;
+    }
+    Ok(injection)
+}
+
+/// Expression resolving the value of a field from `deps`
+fn resolve_field(field: &Field) -> Result<TokenStream2, Error> {
+    let span = field.ty.span();
+    let unsupported = || {
+        Error::new(
+            span,
+            "expected `Arc<T>`, `Option<Arc<T>>` or `Vec<Arc<T>>`, or `#[inject(default)]`",
+        )
+    };
+    match injection(field)? {
+        Injection::Default => Ok(quote_spanned! {span=> ::std::default::Default::default() }),
+        Injection::Named(name) => {
+            let bean = wrapped(&field.ty, "Arc").ok_or_else(|| {
+                Error::new(span, "`#[inject(name = ...)]` expects an `Arc<T>` field")
+            })?;
+            Ok(
+                quote_spanned! {span=> deps.get_named::<#bean>(::std::option::Option::Some(#name))? },
+            )
+        }
+        Injection::Resolve => {
+            if let Some(bean) = wrapped(&field.ty, "Arc") {
+                Ok(quote_spanned! {span=> deps.get::<#bean>()? })
+            } else if let Some(bean) = wrapped(&field.ty, "Option").and_then(|t| wrapped(t, "Arc"))
+            {
+                Ok(quote_spanned! {span=> deps.get_opt::<#bean>()? })
+            } else if let Some(bean) = wrapped(&field.ty, "Vec").and_then(|t| wrapped(t, "Arc")) {
+                Ok(quote_spanned! {span=> deps.get_all::<#bean>()? })
+            } else {
+                Err(unsupported())
+            }
+        }
+    }
+}
+
+/// Dependency required by a field, `None` for optional ones
+fn field_dependency(field: &Field) -> Result<Option<TokenStream2>, Error> {
+    let span = field.ty.span();
+    Ok(match injection(field)? {
+        Injection::Default => None,
+        Injection::Named(name) => {
+            Some(quote_spanned! {span=> ::rs_bean::bean::Dependency::named(#name) })
+        }
+        Injection::Resolve => wrapped(&field.ty, "Arc")
+            .map(|bean| quote_spanned! {span=> ::rs_bean::bean::Dependency::on::<#bean>() }),
+    })
+}
+
+/// `T` if `ty` is `Wrapper<T>`, matching the last path segment only, e.g. `std::sync::Arc<T>`
+fn wrapped<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(ty) if args.args.len() == 1 => Some(ty),
+        _ => None,
+    }
+}
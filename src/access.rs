@@ -0,0 +1,207 @@
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+
+use crate::bean::{BeanId, Observers};
+
+/// Capability to resolve the internal beans of a module from outside of it
+///
+/// Beans marked internal (see [`BeanDef::internal`](crate::bean::BeanDef::internal)) are only
+/// resolvable from factories of beans of the same module. Code outside the module, e.g. its
+/// tests or an admin endpoint, presents a token to
+/// [`get_internal`](crate::bean::BeanContainer::get_internal). Tokens are only minted by
+/// [`module_token`](crate::bean::BeanContainer::module_token) while the module is installed, so
+/// only the code the module hands one to gets access. This guards against accidental coupling,
+/// not against malicious code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleToken {
+    module: String,
+}
+
+impl ModuleToken {
+    pub(crate) fn new(module: &str) -> Self {
+        ModuleToken {
+            module: module.to_string(),
+        }
+    }
+
+    pub fn module(&self) -> &str {
+        &self.module
+    }
+}
+
+/// Injection point passed to the guards of a bean, see
+/// [`Registration::guard`](crate::bean::Registration::guard)
+#[derive(Debug, Clone)]
+pub struct GuardContext {
+    /// Bean being resolved
+    pub bean: BeanId,
+    /// Bean whose factory requests it, `None` for a direct container lookup
+    pub requester: Option<BeanId>,
+    /// Tags of the requester
+    pub requester_tags: Vec<String>,
+    /// Dependency path leading to the resolution
+    pub path: String,
+    /// Scopes active on the current thread, innermost last
+    pub active_scopes: Vec<&'static str>,
+}
+
+impl GuardContext {
+    pub fn requester_has_tag(&self, tag: &str) -> bool {
+        self.requester_tags.iter().any(|t| t == tag)
+    }
+}
+
+/// Access pattern declared for a bean, checked in debug builds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AccessPattern {
+    /// Used by a single thread
+    SingleConsumer,
+    /// Used by one thread at a time
+    Exclusive,
+}
+
+/// Threads using a bean with a declared access pattern, and the ones already reported
+pub(crate) struct AccessCheck {
+    pattern: AccessPattern,
+    state: Mutex<AccessState>,
+}
+
+#[derive(Default)]
+struct AccessState {
+    // First thread resolving or using the bean
+    owner: Option<ThreadId>,
+    // Threads using the bean through a `Checked` wrapper right now
+    using: Vec<ThreadId>,
+    reported: Vec<ThreadId>,
+}
+
+impl AccessCheck {
+    pub(crate) fn new(pattern: AccessPattern) -> Self {
+        AccessCheck {
+            pattern,
+            state: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn pattern(&self) -> AccessPattern {
+        self.pattern
+    }
+
+    /// Violation of a resolution from the current thread, reported once per thread
+    pub(crate) fn resolved(&self) -> Option<String> {
+        if self.pattern != AccessPattern::SingleConsumer {
+            return None;
+        }
+        let current = thread::current();
+        let mut state = self.state.lock().unwrap();
+        let violation = *state.owner.get_or_insert(current.id()) != current.id();
+        state.report(violation, &current).then(|| {
+            format!(
+                "single consumer bean resolved from another thread ({})",
+                current.name().unwrap_or("unnamed")
+            )
+        })
+    }
+
+    /// Violation of a use starting on the current thread, reported once per thread
+    fn enter(&self) -> Option<String> {
+        let current = thread::current();
+        let mut state = self.state.lock().unwrap();
+        let violation = match self.pattern {
+            AccessPattern::SingleConsumer => {
+                *state.owner.get_or_insert(current.id()) != current.id()
+            }
+            AccessPattern::Exclusive => state.using.iter().any(|t| *t != current.id()),
+        };
+        state.using.push(current.id());
+        state
+            .report(violation, &current)
+            .then(|| match self.pattern {
+                AccessPattern::SingleConsumer => format!(
+                    "single consumer bean used from another thread ({})",
+                    current.name().unwrap_or("unnamed")
+                ),
+                AccessPattern::Exclusive => format!(
+                    "bean used concurrently from another thread ({})",
+                    current.name().unwrap_or("unnamed")
+                ),
+            })
+    }
+
+    fn exit(&self) {
+        let current = thread::current().id();
+        let mut state = self.state.lock().unwrap();
+        if let Some(index) = state.using.iter().position(|t| *t == current) {
+            state.using.swap_remove(index);
+        }
+    }
+}
+
+impl AccessState {
+    /// Whether the violation of `thread` is to report, the first one only
+    fn report(&mut self, violation: bool, thread: &thread::Thread) -> bool {
+        if !violation || self.reported.contains(&thread.id()) {
+            return false;
+        }
+        self.reported.push(thread.id());
+        true
+    }
+}
+
+/// Bean checking its declared access pattern on each use, in debug builds
+///
+/// Factories of beans declared [`single_consumer`](crate::bean::Registration::single_consumer)
+/// or [`assert_sync_access`](crate::bean::Registration::assert_sync_access) wrap their instance
+/// with [`Dependencies::checked`](crate::bean::Dependencies::checked). Unlike the check on
+/// resolution, this catches an instance handed over to another thread. Violations are reported
+/// to observers with `on_access_violation`, once per thread. In release builds, or without a
+/// declared pattern, [`access`](Self::access) is a plain borrow.
+pub struct Checked<T> {
+    value: T,
+    check: Option<(BeanId, Arc<AccessCheck>, Observers)>,
+}
+
+impl<T> Checked<T> {
+    pub(crate) fn new(value: T, check: Option<(BeanId, Arc<AccessCheck>, Observers)>) -> Self {
+        Checked { value, check }
+    }
+
+    /// Use the bean until the returned guard is dropped
+    pub fn access(&self) -> CheckedAccess<'_, T> {
+        if let Some((bean, check, observers)) = &self.check
+            && let Some(violation) = check.enter()
+        {
+            let observers = observers.read().unwrap().clone();
+            for observer in &observers {
+                observer.on_access_violation(*bean, &violation);
+            }
+        }
+        CheckedAccess { checked: self }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+/// Use of a [`Checked`] bean, ends when dropped
+pub struct CheckedAccess<'a, T> {
+    checked: &'a Checked<T>,
+}
+
+impl<T> Deref for CheckedAccess<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.checked.value
+    }
+}
+
+impl<T> Drop for CheckedAccess<'_, T> {
+    fn drop(&mut self) {
+        if let Some((_, check, _)) = &self.checked.check {
+            check.exit();
+        }
+    }
+}
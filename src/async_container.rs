@@ -0,0 +1,365 @@
+use std::any::{Any, type_name};
+use std::collections::HashMap;
+use std::future::{Future, poll_fn};
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, OnceLock, RwLock};
+use std::task::{Poll, Waker};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bean::{
+    BeanContainer, BeanDef, BeanFactory, Dependencies, Identifier, Registration, Scope,
+};
+use crate::error::BeanError;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+type AsyncFactory = dyn Fn(AsyncDependencies) -> BoxFuture<Result<Arc<dyn Any + Send + Sync>, BeanError>>
+    + Send
+    + Sync;
+
+/// Wait before retrying a bean whose creation permits are taken, as they free up without notice
+const PERMIT_POLL: Duration = Duration::from_millis(5);
+
+/// Container front-end whose singletons are built by async factories, e.g. DB pools or
+/// HTTP clients
+///
+/// Runtime-agnostic: resolutions are plain futures. Concurrent `.await`s of a bean being created
+/// wait for the first one instead of running its factory again. Failures are not cached, and a
+/// creation cancelled by dropping its future is taken over by the next waiter.
+///
+/// Async beans are registered in the wrapped synchronous [`BeanContainer`], which caches their
+/// instances: they are disposed at shutdown and show up in its manifest, validation, graph and
+/// observers, with their guards and module access checked. The synchronous container only
+/// resolves them once created through this front-end.
+///
+/// Beans not registered here are resolved from the synchronous container. Their factories run
+/// on the polling thread, but resolutions don't block it waiting for rate-limited factories or
+/// creation permits: they wait asynchronously instead of failing with
+/// [`BeanError::RateLimited`].
+#[derive(Clone)]
+pub struct AsyncBeanContainer {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    container: BeanContainer,
+    // Async factories by identifier of the definition holding their instance, which a named
+    // bean's by-type alias resolves to
+    beans: RwLock<HashMap<Identifier, Arc<AsyncBean>>>,
+}
+
+struct AsyncBean {
+    factory: Arc<AsyncFactory>,
+    type_name: &'static str,
+    state: Mutex<SlotState>,
+}
+
+#[derive(Default)]
+struct SlotState {
+    // A future is running the factory
+    creating: bool,
+    waiters: Vec<Waker>,
+    // Instance created, handed over to the container by the factory of its definition
+    created: Option<Arc<dyn Any + Send + Sync>>,
+}
+
+/// Factory of the definition of an async bean, handing over the instance created by the
+/// async factory
+struct HandOver(Arc<AsyncBean>);
+
+impl BeanFactory for HandOver {
+    fn create(&self, _deps: &mut Dependencies) -> Result<Arc<dyn Any + Send + Sync>, BeanError> {
+        self.0.state.lock().unwrap().created.take().ok_or_else(|| {
+            BeanError::from(format!(
+                "{} is created by an async factory, get it from its AsyncBeanContainer first",
+                self.0.type_name
+            ))
+        })
+    }
+}
+
+/// Dependency provider of async factories
+pub struct AsyncDependencies {
+    container: AsyncBeanContainer,
+    // Beans being created on this path, to detect circular dependencies
+    path: Vec<Identifier>,
+}
+
+impl AsyncDependencies {
+    /// Get an async bean, or a bean of the synchronous container, by type
+    pub async fn get<T: Any + Send + Sync + 'static>(&self) -> Result<Arc<T>, BeanError> {
+        self.container.resolve::<T>(None, &self.path).await
+    }
+
+    /// Get an async bean, or a bean of the synchronous container, by name
+    pub async fn get_named<T: Any + Send + Sync + 'static>(
+        &self,
+        name: &str,
+    ) -> Result<Arc<T>, BeanError> {
+        self.container.resolve::<T>(Some(name), &self.path).await
+    }
+
+    /// The synchronous container
+    pub fn container(&self) -> &BeanContainer {
+        self.container.container()
+    }
+}
+
+impl AsyncBeanContainer {
+    pub fn new() -> Self {
+        Self::with_container(BeanContainer::new())
+    }
+
+    /// Front-end over a synchronous container, which holds the async beans and resolves the
+    /// others
+    pub fn with_container(container: BeanContainer) -> Self {
+        AsyncBeanContainer {
+            inner: Arc::new(Inner {
+                container,
+                beans: RwLock::new(HashMap::new()),
+            }),
+        }
+    }
+
+    pub fn container(&self) -> &BeanContainer {
+        &self.inner.container
+    }
+
+    /// Register a singleton built by an async factory
+    #[track_caller]
+    pub fn register<T, F, Fut>(&self, factory: F) -> Result<Registration<'_>, BeanError>
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(AsyncDependencies) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, BeanError>> + Send + 'static,
+    {
+        self.insert(None, factory)
+    }
+
+    /// Register a named singleton built by an async factory
+    #[track_caller]
+    pub fn register_named<T, F, Fut>(
+        &self,
+        name: &str,
+        factory: F,
+    ) -> Result<Registration<'_>, BeanError>
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(AsyncDependencies) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, BeanError>> + Send + 'static,
+    {
+        self.insert(Some(name), factory)
+    }
+
+    /// Get a bean by type, creating it if needed
+    pub async fn get<T: Any + Send + Sync + 'static>(&self) -> Result<Arc<T>, BeanError> {
+        self.resolve::<T>(None, &[]).await
+    }
+
+    /// Get a bean by name, creating it if needed
+    pub async fn get_named<T: Any + Send + Sync + 'static>(
+        &self,
+        name: &str,
+    ) -> Result<Arc<T>, BeanError> {
+        self.resolve::<T>(Some(name), &[]).await
+    }
+
+    #[track_caller]
+    fn insert<T, F, Fut>(
+        &self,
+        name: Option<&str>,
+        factory: F,
+    ) -> Result<Registration<'_>, BeanError>
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(AsyncDependencies) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, BeanError>> + Send + 'static,
+    {
+        let bean = Arc::new(AsyncBean {
+            factory: Arc::new(move |deps| {
+                let created = factory(deps);
+                Box::pin(async move { Ok(Arc::new(created.await?) as Arc<dyn Any + Send + Sync>) })
+            }),
+            type_name: type_name::<T>(),
+            state: Mutex::default(),
+        });
+        let def = BeanDef::from_factory::<T>(Scope::Singleton, Arc::new(HandOver(bean.clone())));
+        let registration = self.inner.container.register_def(match name {
+            Some(name) => def.named(name),
+            None => def,
+        })?;
+        if let Some(id) = registration.ids().first() {
+            let id = self.inner.container.instance_id(id);
+            self.inner.beans.write().unwrap().insert(id, bean);
+        }
+        Ok(registration)
+    }
+
+    async fn resolve<T: Any + Send + Sync + 'static>(
+        &self,
+        name: Option<&str>,
+        path: &[Identifier],
+    ) -> Result<Arc<T>, BeanError> {
+        let container = &self.inner.container;
+        // One instance and one creation for the lookups by type and by name
+        let id = container
+            .find_id::<T>(name)
+            .map(|id| container.instance_id(&id));
+        if let (Some(parent), Some(id)) = (path.last(), &id) {
+            container.record_dependency(parent, id);
+        }
+        let bean = id
+            .as_ref()
+            .and_then(|id| self.inner.beans.read().unwrap().get(id).cloned());
+        let (Some(id), Some(bean)) = (id, bean) else {
+            loop {
+                match container.try_get_without_waiting::<T>(name, path.last()) {
+                    Err(BeanError::RateLimited { retry_after, .. }) => {
+                        sleep(retry_after.max(PERMIT_POLL)).await
+                    }
+                    resolved => return resolved,
+                }
+            }
+        };
+
+        if path.contains(&id) {
+            let path: Vec<String> = path.iter().chain([&id]).map(|i| i.to_string()).collect();
+            return Err(BeanError::CircularDependency(path.join(" -> ")));
+        }
+
+        if container.cached_instance(&id).is_none() {
+            let claim = bean.claim().await;
+            // Unless created by the previous claim
+            if container.cached_instance(&id).is_none() {
+                container.check_lookup(&id, path.last())?;
+                let deps = AsyncDependencies {
+                    container: self.clone(),
+                    path: path.iter().cloned().chain([id]).collect(),
+                };
+                let instance = (bean.factory)(deps).await?;
+                claim.hand_over(instance);
+                return container.get_requested::<T>(name, path.last());
+            }
+        }
+        container.get_requested::<T>(name, path.last())
+    }
+}
+
+impl Default for AsyncBeanContainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AsyncBean {
+    /// Wait for the right to create the bean
+    async fn claim(&self) -> Claim<'_> {
+        poll_fn(|cx| {
+            let mut state = self.state.lock().unwrap();
+            if !state.creating {
+                state.creating = true;
+                return Poll::Ready(Claim { bean: self });
+            }
+            if !state.waiters.iter().any(|w| w.will_wake(cx.waker())) {
+                state.waiters.push(cx.waker().clone());
+            }
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+/// Right to create a bean, handed over to the waiters when dropped, e.g. when the creation is
+/// cancelled
+struct Claim<'a> {
+    bean: &'a AsyncBean,
+}
+
+impl Claim<'_> {
+    /// Leave the created instance to the factory of its definition
+    fn hand_over(&self, instance: Arc<dyn Any + Send + Sync>) {
+        self.bean.state.lock().unwrap().created = Some(instance);
+    }
+}
+
+impl Drop for Claim<'_> {
+    fn drop(&mut self) {
+        let waiters = {
+            let mut state = self.bean.state.lock().unwrap();
+            state.creating = false;
+            state.created = None;
+            std::mem::take(&mut state.waiters)
+        };
+        waiters.into_iter().for_each(Waker::wake);
+    }
+}
+
+/// Wait for `duration`, timed by a thread shared by all waits so that it works on any executor
+async fn sleep(duration: Duration) {
+    let deadline = Instant::now() + duration;
+    poll_fn(|cx| {
+        if Instant::now() >= deadline {
+            return Poll::Ready(());
+        }
+        if !TIMER.wake_at(deadline, cx.waker()) {
+            // No timer, poll again right away
+            cx.waker().wake_by_ref();
+        }
+        Poll::Pending
+    })
+    .await
+}
+
+static TIMER: Timer = Timer {
+    waits: Mutex::new(Vec::new()),
+    cond: Condvar::new(),
+    started: OnceLock::new(),
+};
+
+/// Wakes the futures waiting for a deadline, from one thread started on first use
+struct Timer {
+    waits: Mutex<Vec<(Instant, Waker)>>,
+    cond: Condvar,
+    // Whether the timer thread could be started
+    started: OnceLock<bool>,
+}
+
+impl Timer {
+    /// Wake `waker` at `deadline`, `false` if the timer thread can't be started
+    fn wake_at(&'static self, deadline: Instant, waker: &Waker) -> bool {
+        let started = *self.started.get_or_init(|| {
+            thread::Builder::new()
+                .name("bean timer".to_string())
+                .spawn(|| self.run())
+                .is_ok()
+        });
+        if started {
+            self.waits.lock().unwrap().push((deadline, waker.clone()));
+            self.cond.notify_one();
+        }
+        started
+    }
+
+    fn run(&self) {
+        let mut waits = self.waits.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            let (due, pending) = std::mem::take(&mut *waits)
+                .into_iter()
+                .partition::<Vec<_>, _>(|(deadline, _)| *deadline <= now);
+            *waits = pending;
+            if !due.is_empty() {
+                // Wakers may run executor code, not under the lock
+                drop(waits);
+                due.into_iter().for_each(|(_, waker)| waker.wake());
+                waits = self.waits.lock().unwrap();
+                continue;
+            }
+            waits = match waits.iter().map(|(deadline, _)| *deadline).min() {
+                Some(next) => self.cond.wait_timeout(waits, next - now).unwrap().0,
+                None => self.cond.wait(waits).unwrap(),
+            };
+        }
+    }
+}
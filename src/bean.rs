@@ -1,22 +1,139 @@
 use std::any::{Any, TypeId, type_name};
+use std::cell::RefCell;
 use std::collections::HashMap;
+#[cfg(any(feature = "wiring", feature = "graph", feature = "testing"))]
+use std::collections::VecDeque;
 use std::fmt::Display;
-use std::sync::{Arc, RwLock};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::ops::Deref;
+#[cfg(feature = "introspection")]
+use std::panic::Location;
+#[cfg(feature = "wiring")]
+use std::sync::Weak;
+#[cfg(feature = "introspection")]
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+#[cfg(any(feature = "prefetch", feature = "resilience"))]
+use std::sync::{Condvar, Mutex};
+#[cfg(feature = "prefetch")]
+use std::thread;
+#[cfg(any(feature = "scopes", feature = "resilience"))]
+use std::time::Duration;
+use std::time::Instant;
+#[cfg(feature = "config")]
+use std::time::SystemTime;
+
+#[cfg(feature = "modules")]
+use crate::access::{AccessCheck, AccessPattern, Checked, GuardContext, ModuleToken};
+#[cfg(feature = "config")]
+use crate::clock::Clock;
+#[cfg(feature = "config")]
+use crate::environment::{AppInfo, Environment};
+use crate::error::BeanError;
+#[cfg(feature = "introspection")]
+use crate::history::{HistoryEvent, RecordPoint};
+#[cfg(feature = "introspection")]
+use crate::introspect::{
+    BeanInfo, MatchRule, ResolutionDecision, ResolutionPlanReport, ResolutionStep,
+};
+#[cfg(feature = "observers")]
+use crate::observer::BeanObserver;
+#[cfg(feature = "scopes")]
+use crate::scope::{self, LeakTracker, ScopeMarker, ScopeState};
+#[cfg(feature = "lifecycle")]
+use crate::service::ServiceSpec;
+#[cfg(feature = "introspection")]
+use crate::trace::{ResolutionTrace, TraceOutcome};
+#[cfg(feature = "reload")]
+use crate::watch::Watches;
+
+/// How bean names are matched, see [`BeanContainer::set_name_policy`]
+#[cfg(feature = "naming")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NamePolicy {
+    /// Names must match exactly
+    #[default]
+    Exact,
+    /// Names match regardless of case
+    CaseInsensitive,
+    /// Names match regardless of case, surrounding whitespace and separators,
+    /// e.g. `PRIMARY_DB`, `primary-db` and ` Primary.DB ` are the same name
+    Normalized,
+}
+
+#[cfg(feature = "naming")]
+impl NamePolicy {
+    /// Key the name is registered and looked up under
+    pub fn normalize(&self, name: &str) -> String {
+        match self {
+            NamePolicy::Exact => name.to_string(),
+            NamePolicy::CaseInsensitive => name.to_lowercase(),
+            NamePolicy::Normalized => name
+                .trim()
+                .to_lowercase()
+                .split(|c: char| c == '-' || c == '_' || c == '.' || c.is_whitespace())
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join("-"),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Scope {
     Singleton,
     Prototype,
+    /// One instance per entered scope with the given name, see [`BeanContainer::enter_scope`]
+    #[cfg(feature = "scopes")]
+    Scoped(&'static str),
+    /// One instance reused until it is older than the duration, then created again,
+    /// e.g. short-lived credentials
+    #[cfg(feature = "scopes")]
+    Cached(Duration),
+    /// Up to the given number of instances, created on demand and each handed out to one
+    /// holder at a time, back in the pool once dropped, e.g. workers or parsers
+    #[cfg(feature = "scopes")]
+    Pooled(usize),
+}
+
+#[cfg(feature = "scopes")]
+impl Scope {
+    /// One instance per request, see [`BeanContainer::enter_request_scope`]
+    pub const REQUEST: Scope = Scope::of::<scope::RequestScope>();
+
+    /// Scope keyed by a typed marker, see [`ScopeMarker`]
+    pub const fn of<M: ScopeMarker>() -> Self {
+        Scope::Scoped(M::NAME)
+    }
+}
+
+/// Lifecycle state of a registered bean
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BeanState {
+    /// Registered but never created
+    Registered,
+    /// Factory is currently running
+    Creating,
+    /// Last creation succeeded (singletons are cached)
+    Ready,
+    /// Last creation failed
+    Failed,
+    /// Instance has been released by the container
+    Disposed,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-enum Identifier {
+pub(crate) enum Identifier {
     // Named Bean
     Named(String),
     // Type-specific default Bean
     TypeSpec(TypeId, &'static str),
     // Unnamed temporary Bean (replaced by TypeSpec)
     Unnamed(TypeId, &'static str),
+    // Anonymous Bean, only reachable through its handle
+    Anonymous(u64, &'static str),
 }
 
 impl Identifier {
@@ -31,6 +148,27 @@ impl Identifier {
     pub fn unnamed<T: 'static>() -> Self {
         Identifier::Unnamed(TypeId::of::<T>(), type_name::<T>())
     }
+
+    /// Type of the bean, unknown for named beans
+    fn type_id(&self) -> Option<TypeId> {
+        match self {
+            Identifier::TypeSpec(type_id, _) | Identifier::Unnamed(type_id, _) => Some(*type_id),
+            Identifier::Named(_) | Identifier::Anonymous(..) => None,
+        }
+    }
+}
+
+impl Identifier {
+    /// Kind of the identifier and the name or type it holds, independent of the process
+    #[cfg(feature = "introspection")]
+    pub(crate) fn wiring_key(&self) -> (&'static str, &str) {
+        match self {
+            Identifier::Named(name) => ("named", name),
+            Identifier::TypeSpec(_, name) => ("type", name),
+            Identifier::Unnamed(_, name) => ("unnamed", name),
+            Identifier::Anonymous(_, name) => ("anonymous", name),
+        }
+    }
 }
 
 impl Display for Identifier {
@@ -39,37 +177,338 @@ impl Display for Identifier {
             Identifier::Named(name) => write!(f, "Bean({})", name),
             Identifier::TypeSpec(_, name) => write!(f, "Bean({})", name),
             Identifier::Unnamed(_, name) => write!(f, "Bean({})[unnamed]", name),
+            Identifier::Anonymous(n, name) => write!(f, "Bean({})[anonymous #{}]", name, n),
+        }
+    }
+}
+
+/// Identifier of a registered bean, displayed like `Bean(name)`
+///
+/// Interned in a process-wide table: copying, comparing and hashing it is cheap, and a bean
+/// gets the same id in the introspection, graph, observer and error APIs
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BeanId(u32);
+
+// Interned identifiers are never freed, their count is bounded by the registrations
+#[derive(Default)]
+struct Interner {
+    ids: Vec<&'static Identifier>,
+    indices: HashMap<&'static Identifier, u32>,
+}
+
+fn interner() -> &'static RwLock<Interner> {
+    static INTERNER: OnceLock<RwLock<Interner>> = OnceLock::new();
+    INTERNER.get_or_init(Default::default)
+}
+
+impl BeanId {
+    pub(crate) fn new(id: &Identifier) -> Self {
+        if let Some(index) = interner().read().unwrap().indices.get(id) {
+            return BeanId(*index);
+        }
+        let mut interner = interner().write().unwrap();
+        if let Some(index) = interner.indices.get(id) {
+            return BeanId(*index);
+        }
+        let id: &'static Identifier = Box::leak(Box::new(id.clone()));
+        let index = interner.ids.len() as u32;
+        interner.ids.push(id);
+        interner.indices.insert(id, index);
+        BeanId(index)
+    }
+
+    #[cfg(feature = "introspection")]
+    pub(crate) fn from_index(index: u32) -> Self {
+        BeanId(index)
+    }
+
+    pub(crate) fn identifier(&self) -> &'static Identifier {
+        interner().read().unwrap().ids[self.0 as usize]
+    }
+
+    /// Position in the interning table, stable for the lifetime of the process
+    pub fn index(&self) -> u32 {
+        self.0
+    }
+
+    /// Bean name, or type name for beans registered by type
+    pub fn name(&self) -> &'static str {
+        match self.identifier() {
+            Identifier::Named(name) => name,
+            Identifier::TypeSpec(_, name)
+            | Identifier::Unnamed(_, name)
+            | Identifier::Anonymous(_, name) => name,
+        }
+    }
+
+    /// Whether this is the by-type alias of a named bean
+    pub fn is_alias(&self) -> bool {
+        matches!(self.identifier(), Identifier::Unnamed(..))
+    }
+}
+
+impl Display for BeanId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.identifier().fmt(f)
+    }
+}
+
+impl std::fmt::Debug for BeanId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BeanId({}, {})", self.0, self.identifier())
+    }
+}
+
+/// Handle of a bean registered with [`BeanContainer::register_anonymous`], the only way to resolve it
+pub struct BeanHandle<T> {
+    id: BeanId,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> BeanHandle<T> {
+    pub fn id(&self) -> BeanId {
+        self.id
+    }
+}
+
+impl<T> Clone for BeanHandle<T> {
+    fn clone(&self) -> Self {
+        BeanHandle {
+            id: self.id,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for BeanHandle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("BeanHandle").field(&self.id).finish()
+    }
+}
+
+/// Weak reference of a bean to itself, see [`Dependencies::self_ref`]
+///
+/// Empty while the bean is being created, filled once its factory returned, so tasks
+/// spawned by the bean can reach it without keeping it alive.
+#[cfg(feature = "wiring")]
+pub struct SelfRef<T> {
+    weak: Arc<OnceLock<Weak<T>>>,
+}
+
+#[cfg(feature = "wiring")]
+impl<T> SelfRef<T> {
+    /// The bean, `None` while it is being created or once dropped
+    pub fn get(&self) -> Option<Arc<T>> {
+        self.weak.get().and_then(Weak::upgrade)
+    }
+
+    /// Weak reference of the bean, dangling while it is being created
+    pub fn weak(&self) -> Weak<T> {
+        self.weak.get().cloned().unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "wiring")]
+impl<T> Clone for SelfRef<T> {
+    fn clone(&self) -> Self {
+        SelfRef {
+            weak: self.weak.clone(),
         }
     }
 }
 
+// Fills a `SelfRef` with the created instance
+#[cfg(feature = "wiring")]
+type SelfRefFill = Box<dyn FnOnce(&Arc<dyn Any + Send + Sync>) + Send>;
+
+/// Completion signal of a bean prefetched in background
+#[cfg(feature = "prefetch")]
+#[derive(Default)]
+struct PrefetchSignal {
+    done: Mutex<bool>,
+    cond: Condvar,
+}
+
+/// Marks the prefetch as done when dropped, even if the factory panicked
+#[cfg(feature = "prefetch")]
+struct PrefetchDone(Arc<PrefetchSignal>);
+
+#[cfg(feature = "prefetch")]
+impl Drop for PrefetchDone {
+    fn drop(&mut self) {
+        *self.0.done.lock().unwrap() = true;
+        self.0.cond.notify_all();
+    }
+}
+
+thread_local! {
+    // Beans whose factory runs on this thread, by container id, outermost first
+    static RUNNING: RefCell<Vec<(u64, Identifier)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Pops the bean whose factory ran, even if it panicked
+struct Running;
+
+impl Running {
+    fn enter(container: u64, id: &Identifier) -> Self {
+        RUNNING.with(|running| running.borrow_mut().push((container, id.clone())));
+        Running
+    }
+
+    /// Beans whose factory runs on this thread for `container`, outermost first
+    fn path(container: u64) -> Vec<Identifier> {
+        RUNNING.with(|running| {
+            let running = running.borrow();
+            running
+                .iter()
+                .filter(|(c, _)| *c == container)
+                .map(|(_, id)| id.clone())
+                .collect()
+        })
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        RUNNING.with(|running| running.borrow_mut().pop());
+    }
+}
+
 /// Creation context
 struct CreationContext {
     // Creation stack
     creating: Vec<Identifier>,
+    // Beans being created around this resolution on the same thread, e.g. the factory using a
+    // provider, checked for cycles like the creation stack
+    outer: Vec<Identifier>,
+    // Bean the resolution is made for when the creation stack is empty, e.g. the bean a
+    // provider was injected into
+    requester: Option<Identifier>,
+    // Beans prefetched in background on behalf of this context
+    #[cfg(feature = "prefetch")]
+    prefetching: Vec<(Identifier, Arc<PrefetchSignal>)>,
+    // Scopes active when the resolution started, innermost last
+    #[cfg(feature = "scopes")]
+    scopes: Vec<Arc<ScopeState>>,
+    // Longest creation stack allowed, `None` for unlimited
+    max_depth: Option<usize>,
+    // Last failure notified to observers, to report a failure once along the path
+    #[cfg(feature = "observers")]
+    reported_failure: Option<BeanError>,
+    // Self references handed out to the factories being run, innermost last
+    #[cfg(feature = "wiring")]
+    self_refs: Vec<SelfRefFill>,
+    // Setters of the instances created, run once the creation stack unwound
+    #[cfg(feature = "wiring")]
+    setters: Vec<(Arc<dyn Any + Send + Sync>, Arc<SetterFn>)>,
+    // Bean to create without reusing or caching an instance, see `create`
+    fresh: Option<Identifier>,
+    // Instances created on this path, cached once the setters ran
+    #[cfg(feature = "wiring")]
+    unwired: Vec<Unwired>,
+    // Setters are running, instances they create are cached with the others
+    #[cfg(feature = "wiring")]
+    wiring: bool,
+    // Fail with `RateLimited` instead of waiting for a creation permit, for async callers
+    #[cfg(feature = "resilience")]
+    no_wait: bool,
+}
+
+/// Instance created along a path, not visible to other resolutions until its setters ran
+struct Unwired {
+    #[cfg(any(feature = "wiring", feature = "scopes"))]
+    id: Identifier,
+    instance: Arc<dyn Any + Send + Sync>,
+    // Dependency path it was created on
+    path: String,
+    // Scope it goes in, for scoped beans
+    #[cfg(feature = "scopes")]
+    scope: Option<Arc<ScopeState>>,
+    // Time of the container's clock when it was created, for TTLs
+    #[cfg(feature = "scopes")]
+    created_at: Option<Instant>,
 }
 
 impl CreationContext {
-    fn new() -> Self {
+    fn new(max_depth: Option<usize>) -> Self {
         CreationContext {
             creating: Vec::new(),
+            outer: Vec::new(),
+            requester: None,
+            #[cfg(feature = "prefetch")]
+            prefetching: Vec::new(),
+            #[cfg(feature = "scopes")]
+            scopes: Vec::new(),
+            max_depth,
+            #[cfg(feature = "observers")]
+            reported_failure: None,
+            #[cfg(feature = "wiring")]
+            self_refs: Vec::new(),
+            #[cfg(feature = "wiring")]
+            setters: Vec::new(),
+            fresh: None,
+            #[cfg(feature = "wiring")]
+            unwired: Vec::new(),
+            #[cfg(feature = "wiring")]
+            wiring: false,
+            #[cfg(feature = "resilience")]
+            no_wait: false,
+        }
+    }
+
+    /// Context for a background prefetch, sharing the current creation path
+    #[cfg(feature = "prefetch")]
+    fn fork(&self) -> Self {
+        let mut forked = CreationContext::new(self.max_depth);
+        forked.creating = self.creating.clone();
+        forked.outer = self.outer.clone();
+        forked.requester = self.requester.clone();
+        #[cfg(feature = "scopes")]
+        {
+            forked.scopes = self.scopes.clone();
+        }
+        #[cfg(feature = "resilience")]
+        {
+            forked.no_wait = self.no_wait;
+        }
+        forked
+    }
+
+    /// Instance created on this path and waiting for its setters
+    #[cfg(feature = "wiring")]
+    fn unwired_instance(&self, id: &Identifier) -> Option<Arc<dyn Any + Send + Sync>> {
+        let unwired = self.unwired.iter().find(|u| u.id == *id)?;
+        Some(unwired.instance.clone())
+    }
+
+    /// Innermost active scope with given name
+    #[cfg(feature = "scopes")]
+    fn active_scope(&self, name: &str) -> Option<&Arc<ScopeState>> {
+        self.scopes.iter().rev().find(|s| s.name() == name)
+    }
+
+    /// Block until the background prefetch of the bean finished, if any
+    #[cfg(feature = "prefetch")]
+    fn await_prefetch(&self, id: &Identifier) -> Option<()> {
+        let (_, signal) = self.prefetching.iter().find(|(pid, _)| pid == id)?;
+        let mut done = signal.done.lock().unwrap();
+        while !*done {
+            done = signal.cond.wait(done).unwrap();
         }
+        Some(())
     }
 
-    fn enter(&mut self, id: Identifier) -> Result<(), String> {
-        if self.creating.len() > 100 {
-            return Err("Dependency chain too deep (>100)".to_string());
+    fn enter(&mut self, id: Identifier) -> Result<(), BeanError> {
+        if let Some(max) = self.max_depth
+            && self.outer.len() + self.creating.len() > max
+        {
+            return Err(BeanError::TooDeep(max));
         }
 
         // Check for circular dependencies
-        if self.creating.iter().any(|i| i == &id) {
-            let path = self
-                .creating
-                .iter()
-                .map(|id| id.to_string())
-                .collect::<Vec<_>>()
-                .join(" -> ");
-            return Err(format!("Circular dependency detected: {} -> {}", path, id));
+        if self.outer.iter().chain(&self.creating).any(|i| i == &id) {
+            let path = self.get_path();
+            return Err(BeanError::CircularDependency(format!("{} -> {}", path, id)));
         }
 
         self.creating.push(id);
@@ -80,10 +519,24 @@ impl CreationContext {
         self.creating.pop();
     }
 
+    /// Fill the self references handed out since `from` with the created instance
+    #[cfg(feature = "wiring")]
+    fn fill_self_refs(
+        &mut self,
+        from: usize,
+        created: &Result<Arc<dyn Any + Send + Sync>, BeanError>,
+    ) {
+        let fills = self.self_refs.split_off(from);
+        if let Ok(instance) = created {
+            fills.into_iter().for_each(|fill| fill(instance));
+        }
+    }
+
     /// get current dependency path
     pub fn get_path(&self) -> String {
-        self.creating
+        self.outer
             .iter()
+            .chain(&self.creating)
             .map(|k| k.to_string())
             .collect::<Vec<_>>()
             .join(" -> ")
@@ -98,7 +551,7 @@ pub struct Dependencies<'a> {
 
 impl<'a> Dependencies<'a> {
     /// Get bean with default name
-    pub fn get<T: Any + Send + Sync + 'static>(&mut self) -> Result<Arc<T>, String> {
+    pub fn get<T: Any + Send + Sync + 'static>(&mut self) -> Result<Arc<T>, BeanError> {
         self.get_named::<T>(None)
     }
 
@@ -106,228 +559,3911 @@ impl<'a> Dependencies<'a> {
     pub fn get_named<T: Any + Send + Sync + 'static>(
         &mut self,
         name: Option<&str>,
-    ) -> Result<Arc<T>, String> {
+    ) -> Result<Arc<T>, BeanError> {
         self.container.get_with_context::<T>(name, self.context)
     }
 
+    /// Get an optional dependency: `Ok(None)` if no `T` bean is registered, while failures
+    /// to create it, including missing dependencies of its own, are errors
+    pub fn get_opt<T: Any + Send + Sync + 'static>(&mut self) -> Result<Option<Arc<T>>, BeanError> {
+        match self.get::<T>() {
+            Ok(instance) => Ok(Some(instance)),
+            Err(BeanError::NotFound(bean))
+                if bean == BeanId::new(&Identifier::type_spec::<T>()) =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get every bean of type `T`, see [`BeanContainer::get_all`]
+    pub fn get_all<T: Any + Send + Sync + 'static>(&mut self) -> Result<Vec<Arc<T>>, BeanError> {
+        let all = self
+            .container
+            .get_all_with_context::<T>(self.context, false)?;
+        Ok(all.into_iter().map(|(_, instance)| instance).collect())
+    }
+
+    /// Get the named beans of type `T` by name, see [`BeanContainer::get_named_map`]
+    pub fn get_named_map<T: Any + Send + Sync + 'static>(
+        &mut self,
+    ) -> Result<HashMap<String, Arc<T>>, BeanError> {
+        let named = self
+            .container
+            .get_all_with_context::<T>(self.context, true)?;
+        Ok(BeanContainer::by_name(named))
+    }
+
+    /// Get a clone of a value bean, see [`BeanContainer::register_value`]
+    pub fn get_value_bean<T: Clone + Any + Send + Sync + 'static>(
+        &mut self,
+    ) -> Result<T, BeanError> {
+        self.get::<T>().map(|value| T::clone(&value))
+    }
+
+    /// Get a bean, including internal beans of the token's module
+    #[cfg(feature = "modules")]
+    pub fn get_internal<T: Any + Send + Sync + 'static>(
+        &mut self,
+        token: &ModuleToken,
+        name: Option<&str>,
+    ) -> Result<Arc<T>, BeanError> {
+        self.container
+            .get_with_access::<T>(name, self.context, Some(token.module()))
+    }
+
+    /// Get the bean of a handle returned by [`BeanContainer::register_anonymous`]
+    pub fn get_handle<T: Any + Send + Sync + 'static>(
+        &mut self,
+        handle: &BeanHandle<T>,
+    ) -> Result<Arc<T>, BeanError> {
+        self.container.get_handle_with_context(handle, self.context)
+    }
+
+    /// Wrap the instance of the bean being created to check its declared access pattern on
+    /// each use, see [`Checked`]
+    #[cfg(feature = "modules")]
+    pub fn checked<T>(&self, value: T) -> Checked<T> {
+        let check = self.context.creating.last().and_then(|id| {
+            let access = self
+                .container
+                .beans
+                .read()
+                .unwrap()
+                .get(id)?
+                .access
+                .clone()?;
+            Some((BeanId::new(id), access, self.container.observers.clone()))
+        });
+        Checked::new(value, check.filter(|_| cfg!(debug_assertions)))
+    }
+
+    /// Bean the bean being created is injected into, `None` for a direct container lookup
+    #[cfg(feature = "scopes")]
+    pub(crate) fn requester(&self) -> Option<Identifier> {
+        let mut path = self.context.creating.iter().rev();
+        path.next();
+        path.next().or(self.context.requester.as_ref()).cloned()
+    }
+
+    /// Weak reference of the bean being created, filled once its factory returned
+    ///
+    /// Fails with `DowncastFailed` if `T` isn't the type of the bean being created
+    #[cfg(feature = "wiring")]
+    pub fn self_ref<T: Any + Send + Sync + 'static>(&mut self) -> Result<SelfRef<T>, BeanError> {
+        let creating = self.context.creating.last().and_then(|id| {
+            self.container
+                .beans
+                .read()
+                .unwrap()
+                .get(id)
+                .map(|d| d.type_id)
+        });
+        if creating != Some(TypeId::of::<T>()) {
+            return Err(BeanError::DowncastFailed(type_name::<T>()));
+        }
+        let self_ref = SelfRef {
+            weak: Arc::new(OnceLock::new()),
+        };
+        let weak = self_ref.weak.clone();
+        self.context.self_refs.push(Box::new(move |instance| {
+            if let Ok(instance) = instance.clone().downcast::<T>() {
+                let _ = weak.set(Arc::downgrade(&instance));
+            }
+        }));
+        Ok(self_ref)
+    }
+
+    /// Value attached with [`ScopeHandle::put`](crate::scope::ScopeHandle::put) to the innermost
+    /// active scope holding one
+    ///
+    /// Only beans scoped to that scope, or to a scope entered inside it, can read it: other
+    /// beans outlive the scope, they get `None`.
+    #[cfg(feature = "scopes")]
+    pub fn scope_value<T: Any + Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        let Scope::Scoped(name) = self
+            .container
+            .beans
+            .read()
+            .unwrap()
+            .get(self.context.creating.last()?)?
+            .scope
+        else {
+            return None;
+        };
+        // The scope instance the bean goes in, and the ones it was entered in
+        let own = self.context.scopes.iter().rposition(|s| s.name() == name)?;
+        self.context.scopes[..=own]
+            .iter()
+            .rev()
+            .find_map(|scope| scope.value::<T>())
+    }
+
+    #[cfg(any(feature = "scopes", feature = "modules"))]
+    pub(crate) fn container(&self) -> &BeanContainer {
+        self.container
+    }
+
     /// Get current dependency path (for debugging)
     pub fn current_path(&self) -> String {
         self.context.get_path()
     }
+
+    /// Number of beans currently being created on this path
+    pub fn depth(&self) -> usize {
+        self.context.creating.len()
+    }
+
+    /// Run `f` with another recursion limit, restoring the current one afterwards
+    pub fn with_max_depth<R>(&mut self, max_depth: usize, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.with_depth_limit(Some(max_depth), f)
+    }
+
+    /// Run `f` without recursion limit, e.g. for a plugin loader that legitimately nests deeply
+    ///
+    /// Circular dependencies are still detected
+    pub fn with_relaxed_depth<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.with_depth_limit(None, f)
+    }
+
+    fn with_depth_limit<R>(&mut self, limit: Option<usize>, f: impl FnOnce(&mut Self) -> R) -> R {
+        let previous = std::mem::replace(&mut self.context.max_depth, limit);
+        let result = f(self);
+        self.context.max_depth = previous;
+        result
+    }
 }
 
 pub trait BeanFactory: Send + Sync {
-    fn create(&self, deps: &mut Dependencies) -> Result<Arc<dyn Any + Send + Sync>, String>;
+    fn create(&self, deps: &mut Dependencies) -> Result<Arc<dyn Any + Send + Sync>, BeanError>;
 }
 
 struct BeanDefinition {
     factory: Arc<dyn BeanFactory>,
+    type_id: TypeId,
+    type_name: &'static str,
     scope: Scope,
+    // Registration sequence number, for stable iteration
+    order: u64,
     instance: Option<Arc<dyn Any + Send + Sync>>,
+    state: BeanState,
+    // How long a factory failure is cached before retrying
+    #[cfg(feature = "resilience")]
+    failure_backoff: Option<Duration>,
+    // Last factory failure, with the time it happened
+    #[cfg(feature = "resilience")]
+    last_failure: Option<(Instant, BeanError)>,
+    // Beans to create concurrently once this one starts being created
+    #[cfg(feature = "prefetch")]
+    prefetch: Vec<fn(&BeanContainer) -> Option<Identifier>>,
+    // Bounds concurrent factory invocations
+    #[cfg(feature = "resilience")]
+    permits: Option<Arc<CreationPermits>>,
+    // Bounds the rate of factory invocations
+    #[cfg(feature = "resilience")]
+    rate_limit: Option<Arc<CreationRateLimit>>,
+    // Dependencies observed while creating the bean
+    dependencies: Vec<Identifier>,
+    // Dependencies declared with `depends_on`, known before the bean is created
+    #[cfg(feature = "wiring")]
+    requires: Vec<Dependency>,
+    #[cfg(feature = "introspection")]
+    description: Option<String>,
+    #[cfg(feature = "introspection")]
+    profile: Option<String>,
+    #[cfg(feature = "introspection")]
+    gate: Option<String>,
+    // Call site which defined the bean
+    #[cfg(feature = "introspection")]
+    location: Option<&'static Location<'static>>,
+    // Views of the instance as trait objects, keyed by the trait object type
+    #[cfg(feature = "wiring")]
+    upcasts: Vec<(TypeId, Arc<Upcast>)>,
+    // Created by `start`
+    #[cfg(feature = "lifecycle")]
+    eager: bool,
+    // Created by `start` in this phase
+    #[cfg(feature = "lifecycle")]
+    startup_phase: Option<String>,
+    #[cfg(feature = "lifecycle")]
+    deprecated: Option<String>,
+    // Dependency path the cached singleton was created on
+    creation_path: Option<String>,
+    // Failure may be tolerated by `initialize_all`
+    #[cfg(feature = "lifecycle")]
+    optional: bool,
+    // Error returned instead of creating the bean, after its optional initialization failed
+    #[cfg(feature = "lifecycle")]
+    unavailable: Option<BeanError>,
+    // Run by `run_services` when the bean is a background service
+    #[cfg(feature = "lifecycle")]
+    service: Option<ServiceSpec>,
+    // Definitions of the same key with a higher priority replace this one
+    #[cfg(feature = "modules")]
+    priority: i32,
+    #[cfg(feature = "modules")]
+    module: Option<String>,
+    // Only resolvable from beans of its module, or with a token of its module
+    #[cfg(feature = "modules")]
+    internal: bool,
+    #[cfg(feature = "modules")]
+    tags: Vec<String>,
+    // Threads using a bean with a declared access pattern, checked in debug builds
+    #[cfg(feature = "modules")]
+    access: Option<Arc<AccessCheck>>,
+    // Bean this one resolves on demand, e.g. as a `Provider`
+    defers_to: Option<DeferredTarget>,
+    // Policies run before each resolution, any of them can deny it
+    #[cfg(feature = "modules")]
+    guards: Vec<Arc<Guard>>,
+    // Run on each instance after the factory returns, before it is cached
+    #[cfg(feature = "lifecycle")]
+    init: Option<Arc<InitFn>>,
+    // Run on each instance once the creation stack unwound
+    #[cfg(feature = "wiring")]
+    setter: Option<Arc<SetterFn>>,
+    // Run on the cached singleton at shutdown
+    #[cfg(feature = "lifecycle")]
+    dispose: Option<Arc<DisposeFn>>,
+    #[cfg(feature = "lifecycle")]
+    shutdown_phase: Option<String>,
+    // Creation sequence number of the cached singleton, disposal runs in reverse
+    #[cfg(feature = "lifecycle")]
+    created_seq: Option<u64>,
+    // Expiry of the instance of a `Cached` bean
+    #[cfg(feature = "scopes")]
+    expires_at: Option<Instant>,
+    // Instances of a `Pooled` bean, idle while the pool holds the only reference
+    #[cfg(feature = "scopes")]
+    pooled: Vec<Arc<dyn Any + Send + Sync>>,
+    // Instances of a `Pooled` bean being created, counted against its size
+    #[cfg(feature = "scopes")]
+    pool_creating: usize,
+    // Named bean this by-type alias resolves to, sharing its instance
+    alias_of: Option<Identifier>,
 }
 
-pub struct BeanContainer {
-    beans: RwLock<HashMap<Identifier, BeanDefinition>>,
-}
+#[cfg(feature = "lifecycle")]
+type InitFn = dyn Fn(&Arc<dyn Any + Send + Sync>) -> Result<(), BeanError> + Send + Sync;
 
-impl BeanContainer {
-    pub fn new() -> Self {
-        BeanContainer {
-            beans: RwLock::new(HashMap::new()),
-        }
-    }
+#[cfg(feature = "wiring")]
+type SetterFn =
+    dyn Fn(&Arc<dyn Any + Send + Sync>, &mut Dependencies) -> Result<(), BeanError> + Send + Sync;
 
-    pub fn register<T, F>(&self, scope: Scope, factory: F) -> Result<(), String>
-    where
-        T: Any + Send + Sync + 'static,
-        F: Fn(&mut Dependencies) -> Result<T, String> + Send + Sync + 'static,
-    {
-        let type_spec_id = Identifier::type_spec::<T>();
-        let unnamed_id = Identifier::unnamed::<T>();
+#[cfg(feature = "lifecycle")]
+pub(crate) type DisposeFn =
+    dyn Fn(Arc<dyn Any + Send + Sync>) -> Result<(), BeanError> + Send + Sync;
 
-        let bean_factory: Arc<dyn BeanFactory> = Arc::new(move |deps: &mut Dependencies| {
-            let instance = factory(deps)?;
-            Ok(Arc::new(instance) as Arc<dyn Any + Send + Sync>)
-        });
+#[cfg(feature = "modules")]
+type Guard = dyn Fn(&GuardContext) -> Result<(), String> + Send + Sync;
 
-        let definition = BeanDefinition {
-            factory: bean_factory,
-            scope,
-            instance: None,
-        };
+type DeferredTarget = fn(&BeanContainer) -> Option<Identifier>;
 
-        let mut beans = self.beans.write().unwrap();
+#[cfg(feature = "wiring")]
+type Upcast = dyn Fn(Arc<dyn Any + Send + Sync>) -> Option<Box<dyn Any>> + Send + Sync;
 
-        // If TypeSpec exists, throw error
-        if beans.contains_key(&type_spec_id) {
-            return Err(format!("Bean already registered: {}", &type_spec_id));
+impl BeanDefinition {
+    /// Declared dependencies, then the observed ones not declared
+    fn dependency_keys(
+        &self,
+        #[cfg_attr(not(feature = "wiring"), allow(unused_variables))] beans: &HashMap<
+            Identifier,
+            BeanDefinition,
+        >,
+    ) -> Vec<Identifier> {
+        #[cfg(feature = "wiring")]
+        let mut keys: Vec<Identifier> = self.requires.iter().map(|d| d.key(beans)).collect();
+        #[cfg(not(feature = "wiring"))]
+        let mut keys: Vec<Identifier> = Vec::new();
+        for dependency in &self.dependencies {
+            if !keys.contains(dependency) {
+                keys.push(dependency.clone());
+            }
         }
-        // If unnamed exists, remove it
-        beans.remove(&unnamed_id);
-        // Add TypeSpec
-        beans.insert(type_spec_id, definition);
-
-        Ok(())
+        keys
     }
 
-    pub fn register_named<T, F>(&self, name: &str, scope: Scope, factory: F) -> Result<(), String>
-    where
-        T: Any + Send + Sync + 'static,
-        F: Fn(&mut Dependencies) -> Result<T, String> + Send + Sync + 'static,
-    {
-        let named_id = Identifier::named(name);
-        let type_spec_id = Identifier::type_spec::<T>();
-        let unnamed_id = Identifier::unnamed::<T>();
-
-        let bean_factory: Arc<dyn BeanFactory> = Arc::new(move |deps: &mut Dependencies| {
-            let instance = factory(deps)?;
-            Ok(Arc::new(instance) as Arc<dyn Any + Send + Sync>)
-        });
-
-        let definition = BeanDefinition {
-            factory: bean_factory.clone(),
+    fn new(
+        factory: Arc<dyn BeanFactory>,
+        type_id: TypeId,
+        type_name: &'static str,
+        scope: Scope,
+        order: u64,
+    ) -> Self {
+        BeanDefinition {
+            factory,
+            type_id,
+            type_name,
             scope,
+            order,
             instance: None,
-        };
-
-        let mut beans = self.beans.write().unwrap();
-
-        // Check if Named already exists
-        if beans.contains_key(&named_id) {
-            return Err(format!("Bean already registered with name: {}", name));
+            state: BeanState::Registered,
+            #[cfg(feature = "resilience")]
+            failure_backoff: None,
+            #[cfg(feature = "resilience")]
+            last_failure: None,
+            #[cfg(feature = "prefetch")]
+            prefetch: Vec::new(),
+            #[cfg(feature = "resilience")]
+            permits: None,
+            #[cfg(feature = "resilience")]
+            rate_limit: None,
+            dependencies: Vec::new(),
+            #[cfg(feature = "wiring")]
+            requires: Vec::new(),
+            #[cfg(feature = "introspection")]
+            description: None,
+            #[cfg(feature = "introspection")]
+            profile: None,
+            #[cfg(feature = "introspection")]
+            gate: None,
+            #[cfg(feature = "introspection")]
+            location: None,
+            #[cfg(feature = "wiring")]
+            upcasts: Vec::new(),
+            #[cfg(feature = "lifecycle")]
+            eager: false,
+            #[cfg(feature = "lifecycle")]
+            startup_phase: None,
+            #[cfg(feature = "lifecycle")]
+            deprecated: None,
+            creation_path: None,
+            #[cfg(feature = "lifecycle")]
+            optional: false,
+            #[cfg(feature = "lifecycle")]
+            unavailable: None,
+            #[cfg(feature = "lifecycle")]
+            service: None,
+            #[cfg(feature = "modules")]
+            priority: 0,
+            #[cfg(feature = "modules")]
+            module: None,
+            #[cfg(feature = "modules")]
+            internal: false,
+            #[cfg(feature = "modules")]
+            tags: Vec::new(),
+            #[cfg(feature = "modules")]
+            access: None,
+            defers_to: None,
+            #[cfg(feature = "modules")]
+            guards: Vec::new(),
+            #[cfg(feature = "lifecycle")]
+            init: None,
+            #[cfg(feature = "wiring")]
+            setter: None,
+            #[cfg(feature = "lifecycle")]
+            dispose: None,
+            #[cfg(feature = "lifecycle")]
+            shutdown_phase: None,
+            #[cfg(feature = "lifecycle")]
+            created_seq: None,
+            #[cfg(feature = "scopes")]
+            expires_at: None,
+            #[cfg(feature = "scopes")]
+            pooled: Vec::new(),
+            #[cfg(feature = "scopes")]
+            pool_creating: 0,
+            alias_of: None,
         }
+    }
 
-        // Register Named
-        beans.insert(named_id, definition);
+    /// Copy of the definition without its instance and runtime state
+    fn detached(&self) -> Self {
+        BeanDefinition {
+            factory: self.factory.clone(),
+            type_id: self.type_id,
+            type_name: self.type_name,
+            scope: self.scope,
+            order: self.order,
+            instance: None,
+            state: BeanState::Registered,
+            #[cfg(feature = "resilience")]
+            failure_backoff: self.failure_backoff,
+            #[cfg(feature = "resilience")]
+            last_failure: None,
+            #[cfg(feature = "prefetch")]
+            prefetch: self.prefetch.clone(),
+            #[cfg(feature = "resilience")]
+            permits: self.permits.clone(),
+            #[cfg(feature = "resilience")]
+            rate_limit: self.rate_limit.clone(),
+            dependencies: self.dependencies.clone(),
+            #[cfg(feature = "wiring")]
+            requires: self.requires.clone(),
+            #[cfg(feature = "introspection")]
+            description: self.description.clone(),
+            #[cfg(feature = "introspection")]
+            profile: self.profile.clone(),
+            #[cfg(feature = "introspection")]
+            gate: self.gate.clone(),
+            #[cfg(feature = "introspection")]
+            location: self.location,
+            #[cfg(feature = "wiring")]
+            upcasts: self.upcasts.clone(),
+            #[cfg(feature = "lifecycle")]
+            eager: self.eager,
+            #[cfg(feature = "lifecycle")]
+            startup_phase: self.startup_phase.clone(),
+            #[cfg(feature = "lifecycle")]
+            deprecated: self.deprecated.clone(),
+            creation_path: None,
+            #[cfg(feature = "lifecycle")]
+            optional: self.optional,
+            #[cfg(feature = "lifecycle")]
+            unavailable: None,
+            #[cfg(feature = "lifecycle")]
+            service: self.service.clone(),
+            #[cfg(feature = "modules")]
+            priority: self.priority,
+            #[cfg(feature = "modules")]
+            module: self.module.clone(),
+            #[cfg(feature = "modules")]
+            internal: self.internal,
+            #[cfg(feature = "modules")]
+            tags: self.tags.clone(),
+            #[cfg(feature = "modules")]
+            access: self
+                .access
+                .as_ref()
+                .map(|check| Arc::new(AccessCheck::new(check.pattern()))),
+            defers_to: self.defers_to,
+            #[cfg(feature = "modules")]
+            guards: self.guards.clone(),
+            #[cfg(feature = "lifecycle")]
+            init: self.init.clone(),
+            #[cfg(feature = "wiring")]
+            setter: self.setter.clone(),
+            #[cfg(feature = "lifecycle")]
+            dispose: self.dispose.clone(),
+            #[cfg(feature = "lifecycle")]
+            shutdown_phase: self.shutdown_phase.clone(),
+            #[cfg(feature = "lifecycle")]
+            created_seq: None,
+            #[cfg(feature = "scopes")]
+            expires_at: None,
+            #[cfg(feature = "scopes")]
+            pooled: Vec::new(),
+            #[cfg(feature = "scopes")]
+            pool_creating: 0,
+            alias_of: self.alias_of.clone(),
+        }
+    }
 
-        // Rule 1: If TypeSpec and Unnamed do not exist, add Unnamed
-        if !beans.contains_key(&type_spec_id) && !beans.contains_key(&unnamed_id) {
-            let unnamed_definition = BeanDefinition {
-                factory: bean_factory,
-                scope,
-                instance: None,
-            };
-            beans.insert(unnamed_id, unnamed_definition);
+    #[cfg(feature = "introspection")]
+    fn info(&self, id: &Identifier) -> BeanInfo {
+        #[cfg_attr(not(feature = "lifecycle"), allow(unused_mut))]
+        let mut info = BeanInfo {
+            id: BeanId::new(id),
+            name: id.to_string(),
+            type_name: self.type_name,
+            scope: self.scope,
+            state: self.state,
+            description: self.description.clone(),
+            dependencies: self.dependencies.iter().map(BeanId::new).collect(),
+            profile: self.profile.clone(),
+            gate: self.gate.clone(),
+            location: self.location.map(|l| format!("{}:{}", l.file(), l.line())),
+            eager: false,
+            phase: None,
+            deprecated: None,
+            optional: false,
+            module: None,
+            internal: false,
+            tags: Vec::new(),
+        };
+        #[cfg(feature = "lifecycle")]
+        {
+            info.eager = self.eager;
+            info.phase = self.startup_phase.clone();
+            info.deprecated = self.deprecated.clone();
+            info.optional = self.optional;
         }
+        #[cfg(feature = "modules")]
+        {
+            info.module = self.module.clone();
+            info.internal = self.internal;
+            info.tags = self.tags.clone();
+        }
+        info
+    }
 
-        Ok(())
+    /// Instance to reuse: the singleton, or the instance of a `Cached` bean until it expires
+    ///
+    /// `now` is the time of the container's clock, see [`follows_clock`](Self::follows_clock)
+    fn reusable_instance(
+        &self,
+        #[cfg_attr(not(feature = "scopes"), allow(unused_variables))] now: Option<Instant>,
+    ) -> Option<&Arc<dyn Any + Send + Sync>> {
+        match self.scope {
+            Scope::Singleton => self.instance.as_ref(),
+            #[cfg(feature = "scopes")]
+            Scope::Cached(_) => self.instance.as_ref().filter(|_| {
+                now.zip(self.expires_at)
+                    .is_some_and(|(now, expires_at)| now < expires_at)
+            }),
+            _ => None,
+        }
     }
 
-    /// Get bean by type
-    /// **NOTE**: panics if bean not found
-    pub fn get<T: Any + Send + Sync + 'static>(&self) -> Arc<T> {
-        self.try_get::<T>(None).unwrap()
+    /// Instance of a `Pooled` bean to check out, one no one but the pool holds
+    #[cfg(feature = "scopes")]
+    fn idle_instance(&self) -> Option<&Arc<dyn Any + Send + Sync>> {
+        self.pooled
+            .iter()
+            .find(|instance| Arc::strong_count(instance) == 1)
     }
 
-    /// Get named bean by type
-    /// **NOTE**: panics if bean not found
-    pub fn get_named<T: Any + Send + Sync + 'static>(&self, name: &str) -> Arc<T> {
-        self.try_get::<T>(Some(name)).unwrap()
+    /// Give back the slot reserved for an instance of a `Pooled` bean which wasn't created
+    #[cfg(feature = "scopes")]
+    fn release_pool_slot(&mut self) {
+        if let Scope::Pooled(_) = self.scope {
+            self.pool_creating = self.pool_creating.saturating_sub(1);
+        }
     }
 
-    fn try_get<T: Any + Send + Sync + 'static>(
+    /// Cached failure still inside the backoff window, or the failure the bean was marked
+    /// unavailable with
+    #[cfg(any(feature = "lifecycle", feature = "resilience"))]
+    fn cached_failure(
         &self,
-        name: Option<&str>,
-    ) -> Result<Arc<T>, String> {
-        let mut context = CreationContext::new();
-        self.get_with_context::<T>(name, &mut context)
+        #[cfg_attr(not(feature = "resilience"), allow(unused_variables))] now: Option<Instant>,
+    ) -> Option<&BeanError> {
+        #[cfg(feature = "lifecycle")]
+        if let Some(err) = &self.unavailable {
+            return Some(err);
+        }
+        #[cfg(feature = "resilience")]
+        if let Some(backoff) = self.failure_backoff
+            && let Some((at, err)) = &self.last_failure
+        {
+            return (now?.duration_since(*at) < backoff).then_some(err);
+        }
+        None
     }
 
-    fn get_with_context<T: Any + Send + Sync + 'static>(
-        &self,
-        name: Option<&str>,
-        context: &mut CreationContext,
-    ) -> Result<Arc<T>, String> {
-        // Determine the key to look up
-        let id = if let Some(n) = name {
-            Identifier::named(n)
-        } else {
-            // Prefer TypeSpec, then Unnamed
-            let type_spec_id = Identifier::type_spec::<T>();
-            let unnamed_id = Identifier::unnamed::<T>();
+    /// Whether the bean reads the container's clock: for its TTL, failure backoff or creation
+    /// rate limit
+    fn follows_clock(&self) -> bool {
+        #[cfg(feature = "scopes")]
+        if matches!(self.scope, Scope::Cached(_)) {
+            return true;
+        }
+        #[cfg(feature = "resilience")]
+        if self.failure_backoff.is_some() || self.rate_limit.is_some() {
+            return true;
+        }
+        false
+    }
+}
 
-            let beans = self.beans.read().unwrap();
-            if beans.contains_key(&type_spec_id) {
-                type_spec_id
-            } else if beans.contains_key(&unnamed_id) {
-                unnamed_id
-            } else {
-                return Err(format!("Bean not found: {}", type_spec_id));
-            }
-        };
+/// Singleton released by the container at shutdown
+#[cfg(feature = "lifecycle")]
+pub(crate) struct DisposableInstance {
+    pub(crate) id: BeanId,
+    pub(crate) instance: Arc<dyn Any + Send + Sync>,
+    pub(crate) dispose: Option<Arc<DisposeFn>>,
+    pub(crate) phase: Option<String>,
+}
 
-        // Check for circular dependencies
-        context.enter(id.clone())?;
+/// Counting semaphore gating factory invocations of one bean
+#[cfg(feature = "resilience")]
+struct CreationPermits {
+    available: Mutex<usize>,
+    cond: Condvar,
+    // How long to wait for a permit, `None` to wait indefinitely
+    timeout: Option<Duration>,
+}
 
-        // Check if singleton is already created
-        {
-            let beans = self.beans.read().unwrap();
-            if let Some(definition) = beans.get(&id)
-                && definition.scope == Scope::Singleton
-                && let Some(inst) = &definition.instance
-            {
-                return inst
-                    .clone()
-                    .downcast::<T>()
-                    .map_err(|_| "Type downcast failed".to_string());
-            }
-        }
+/// Permit returned to its semaphore when dropped
+#[cfg(feature = "resilience")]
+struct CreationPermit<'a>(&'a CreationPermits);
 
-        let result = (|| -> Result<Arc<T>, String> {
+#[cfg(feature = "resilience")]
+impl CreationPermits {
+    fn new(permits: usize, timeout: Option<Duration>) -> Self {
+        CreationPermits {
+            available: Mutex::new(permits),
+            cond: Condvar::new(),
+            timeout,
+        }
+    }
+
+    /// Block until a permit is available, `None` once the timeout elapsed, or right away
+    /// unless `wait`
+    fn acquire(&self, wait: bool) -> Option<CreationPermit<'_>> {
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let mut available = self.available.lock().unwrap();
+        while *available == 0 {
+            if !wait {
+                return None;
+            }
+            available = match deadline {
+                Some(deadline) => {
+                    let left = deadline.checked_duration_since(Instant::now())?;
+                    self.cond.wait_timeout(available, left).unwrap().0
+                }
+                None => self.cond.wait(available).unwrap(),
+            };
+        }
+        *available -= 1;
+        Some(CreationPermit(self))
+    }
+}
+
+#[cfg(feature = "resilience")]
+impl Drop for CreationPermit<'_> {
+    fn drop(&mut self) {
+        *self.0.available.lock().unwrap() += 1;
+        self.0.cond.notify_one();
+    }
+}
+
+/// Token bucket bounding the factory invocations of one bean, holding up to one second of
+/// invocations
+#[cfg(feature = "resilience")]
+struct CreationRateLimit {
+    per_sec: f64,
+    // Invocations available, and when they were last replenished, `None` before the first one
+    tokens: Mutex<(f64, Option<Instant>)>,
+}
+
+#[cfg(feature = "resilience")]
+impl CreationRateLimit {
+    fn new(per_sec: u32) -> Self {
+        let per_sec = per_sec.max(1) as f64;
+        CreationRateLimit {
+            per_sec,
+            tokens: Mutex::new((per_sec, None)),
+        }
+    }
+
+    /// Take an invocation at `now`, or return how long until the next one is available
+    fn try_acquire(&self, now: Instant) -> Result<(), Duration> {
+        let mut tokens = self.tokens.lock().unwrap();
+        let (available, refilled) = &mut *tokens;
+        if let Some(refilled) = refilled {
+            *available = (*available + now.duration_since(*refilled).as_secs_f64() * self.per_sec)
+                .min(self.per_sec);
+        }
+        *refilled = Some(now);
+        if *available >= 1.0 {
+            *available -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - *available) / self.per_sec))
+        }
+    }
+}
+
+/// Handle of a freshly registered bean, used to tune its definition
+pub struct Registration<'a> {
+    #[cfg_attr(
+        not(any(
+            feature = "async",
+            feature = "lifecycle",
+            feature = "modules",
+            feature = "introspection",
+            feature = "prefetch",
+            feature = "resilience",
+            feature = "wiring"
+        )),
+        allow(dead_code)
+    )]
+    container: &'a BeanContainer,
+    ids: Vec<Identifier>,
+}
+
+impl Registration<'_> {
+    /// Cache factory failures for the given duration
+    ///
+    /// Resolutions within the window return the cached error without invoking the factory again
+    #[cfg(feature = "resilience")]
+    pub fn failure_backoff(self, backoff: Duration) -> Self {
+        self.update(|definition| definition.failure_backoff = Some(backoff));
+        self
+    }
+
+    /// Hint that bean `P` should be created concurrently as soon as this bean starts being created
+    ///
+    /// Useful to overlap independent, I/O-bound constructions during startup
+    #[cfg(feature = "prefetch")]
+    pub fn prefetch<P: Any + Send + Sync + 'static>(self) -> Self {
+        self.update(|definition| definition.prefetch.push(|c| c.find_id::<P>(None)));
+        self
+    }
+
+    /// Declare that the bean depends on `T`, so that [`BeanContainer::validate`] checks it
+    /// before the bean is ever created
+    ///
+    /// Fails with `CircularDependency`, declaring nothing, if the declared dependencies then
+    /// lead back to the bean
+    #[cfg(feature = "wiring")]
+    pub fn depends_on<T: Any + Send + Sync + 'static>(self) -> Result<Self, BeanError> {
+        self.declare(Dependency::on::<T>())
+    }
+
+    /// Declare that the bean depends on the bean named `name`, see [`depends_on`](Self::depends_on)
+    #[cfg(feature = "wiring")]
+    pub fn depends_on_named(self, name: &str) -> Result<Self, BeanError> {
+        self.declare(Dependency::named(name))
+    }
+
+    #[cfg(feature = "wiring")]
+    fn declare(self, dependency: Dependency) -> Result<Self, BeanError> {
+        #[cfg(feature = "naming")]
+        let dependency = dependency.normalized(self.container.name_policy());
+        let mut beans = self.container.beans.write().unwrap();
+        for id in &self.ids {
+            if let Some(definition) = beans.get_mut(id) {
+                definition.requires.push(dependency.clone());
+            }
+        }
+        if let Some(id) = self.ids.first()
+            && let Some(cycle) = dependency_cycle(&beans, id, &self.ids)
+        {
+            for id in &self.ids {
+                if let Some(definition) = beans.get_mut(id) {
+                    definition.requires.pop();
+                }
+            }
+            return Err(BeanError::CircularDependency(cycle));
+        }
+        drop(beans);
+        Ok(self)
+    }
+
+    /// Run `hook` on each instance right after the factory returns, before it is cached or
+    /// handed out, e.g. to warm up the fully constructed bean
+    ///
+    /// A failure of the hook is a failure of the creation
+    #[cfg(feature = "lifecycle")]
+    pub fn on_init<T, F>(self, hook: F) -> Self
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(&T) -> Result<(), BeanError> + Send + Sync + 'static,
+    {
+        let init: Arc<InitFn> = Arc::new(move |instance| {
+            let instance = instance
+                .downcast_ref::<T>()
+                .ok_or(BeanError::DowncastFailed(type_name::<T>()))?;
+            hook(instance)
+        });
+        self.update(|definition| definition.init = Some(init.clone()));
+        self
+    }
+
+    /// Run `hook` on the singleton when the container shuts down, see
+    /// [`BeanContainer::shutdown_with`]
+    #[cfg(feature = "lifecycle")]
+    pub fn on_dispose<T, F>(self, hook: F) -> Self
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(&T) -> Result<(), BeanError> + Send + Sync + 'static,
+    {
+        let dispose: Arc<DisposeFn> = Arc::new(move |instance| {
+            let instance = instance
+                .downcast::<T>()
+                .map_err(|_| BeanError::DowncastFailed(type_name::<T>()))?;
+            hook(&instance)
+        });
+        self.update(|definition| definition.dispose = Some(dispose.clone()));
+        self
+    }
+
+    /// Dispose the bean in the named phase of the shutdown, see [`ShutdownOptions`](crate::shutdown::ShutdownOptions)
+    #[cfg(feature = "lifecycle")]
+    pub fn shutdown_phase(self, phase: &str) -> Self {
+        self.update(|definition| definition.shutdown_phase = Some(phase.to_string()));
+        self
+    }
+
+    /// Describe the bean, surfaced in introspection (see [`BeanInfo`])
+    #[cfg(feature = "introspection")]
+    pub fn describe(self, description: &str) -> Self {
+        self.update(|definition| definition.description = Some(description.to_string()));
+        self
+    }
+
+    /// Expose the bean as the trait object `U`, e.g. `upcast::<Cache, dyn Flushable>(|c| c)`
+    ///
+    /// See [`BeanContainer::instances_as`]
+    #[cfg(feature = "wiring")]
+    pub fn upcast<T, U>(self, cast: fn(Arc<T>) -> Arc<U>) -> Self
+    where
+        T: Any + Send + Sync + 'static,
+        U: ?Sized + 'static,
+    {
+        let upcast: Arc<Upcast> = Arc::new(move |instance| {
+            let instance = instance.downcast::<T>().ok()?;
+            Some(Box::new(cast(instance)) as Box<dyn Any>)
+        });
+        self.update(|definition| definition.upcasts.push((TypeId::of::<U>(), upcast.clone())));
+        self
+    }
+
+    /// Only resolvable from factories of beans of the same module, or with a
+    /// [`ModuleToken`](crate::access::ModuleToken) of the module, see [`BeanDef::in_module`]
+    ///
+    /// Fails with [`BeanError::Internal`] if the bean belongs to no module, it would be
+    /// unresolvable
+    #[cfg(feature = "modules")]
+    pub fn internal(self) -> Result<Self, BeanError> {
+        if let Some(id) = self.ids.first() {
+            let beans = self.container.beans.read().unwrap();
+            if beans.get(id).is_some_and(|d| d.module.is_none()) {
+                return Err(BeanError::Internal {
+                    bean: BeanId::new(id),
+                    module: None,
+                });
+            }
+        }
+        self.update(|definition| definition.internal = true);
+        Ok(self)
+    }
+
+    /// Declare the bean as used by a single thread
+    ///
+    /// In debug builds, resolutions from another thread than the first one are reported to
+    /// observers as access violations, once per thread. Resolving it once and sending it to
+    /// another thread is only caught by uses through a [`Checked`] wrapper.
+    #[cfg(feature = "modules")]
+    pub fn single_consumer(self) -> Self {
+        self.declare_access(AccessPattern::SingleConsumer)
+    }
+
+    /// Declare the bean as used by one thread at a time, e.g. a client not safe for concurrent
+    /// use
+    ///
+    /// In debug builds, a use through a [`Checked`] wrapper while another thread uses it is
+    /// reported to observers as an access violation, once per thread
+    #[cfg(feature = "modules")]
+    pub fn assert_sync_access(self) -> Self {
+        self.declare_access(AccessPattern::Exclusive)
+    }
+
+    #[cfg(feature = "modules")]
+    fn declare_access(self, pattern: AccessPattern) -> Self {
+        // Shared by the named and by-type definitions of the bean
+        let check = Arc::new(AccessCheck::new(pattern));
+        self.update(|definition| definition.access = Some(check.clone()));
+        self
+    }
+
+    /// Record that the bean resolves `T` on demand, for [`BeanContainer::seal`]
+    #[cfg(feature = "scopes")]
+    pub(crate) fn defers_to<T: Any + Send + Sync + 'static>(self) -> Self {
+        self.update(|definition| {
+            definition.defers_to = Some(|container| container.find_id::<T>(None))
+        });
+        self
+    }
+
+    /// Create the bean when the container starts, see [`BeanContainer::start`]
+    #[cfg(feature = "lifecycle")]
+    pub fn eager(self) -> Self {
+        self.update(|definition| definition.eager = true);
+        self
+    }
+
+    /// Create the bean in the named phase when the container starts, see
+    /// [`BeanContainer::set_startup_phases`]
+    #[cfg(feature = "lifecycle")]
+    pub fn phase(self, phase: &str) -> Self {
+        self.update(|definition| definition.startup_phase = Some(phase.to_string()));
+        self
+    }
+
+    /// Tolerate failures of the bean in [`InitMode::WarnAndContinue`](crate::startup::InitMode)
+    #[cfg(feature = "lifecycle")]
+    pub fn optional(self) -> Self {
+        self.update(|definition| definition.optional = true);
+        self
+    }
+
+    /// Mark the bean as deprecated, reported as a warning at startup
+    #[cfg(feature = "lifecycle")]
+    pub fn deprecated(self, note: &str) -> Self {
+        self.update(|definition| definition.deprecated = Some(note.to_string()));
+        self
+    }
+
+    /// Tag the bean, e.g. for [`guard`](Self::guard) policies
+    #[cfg(feature = "modules")]
+    pub fn tag(self, tag: &str) -> Self {
+        self.update(|definition| definition.tags.push(tag.to_string()));
+        self
+    }
+
+    /// Run `guard` before each resolution of the bean, an error denies it
+    ///
+    /// ```text
+    /// container.register::<CryptoKeyRing, _>(Scope::Singleton, |_| Ok(CryptoKeyRing::load()))?
+    ///     .guard(|ctx| match ctx.requester_has_tag("crypto") {
+    ///         true => Ok(()),
+    ///         false => Err("only crypto beans may use the key ring".to_string()),
+    ///     });
+    /// ```
+    #[cfg(feature = "modules")]
+    pub fn guard<F>(self, guard: F) -> Self
+    where
+        F: Fn(&GuardContext) -> Result<(), String> + Send + Sync + 'static,
+    {
+        let guard: Arc<Guard> = Arc::new(guard);
+        self.update(|definition| definition.guards.push(guard.clone()));
+        self
+    }
+
+    /// Allow at most `n` concurrent invocations of the factory, further creations wait for a permit
+    ///
+    /// Smooths thundering herds of expensive prototypes against databases or rate-limited APIs.
+    /// Waits indefinitely, which deadlocks if a factory holding a permit waits on a creation
+    /// that itself waits for one; see
+    /// [`max_concurrent_creations_within`](Self::max_concurrent_creations_within) to bound it.
+    #[cfg(feature = "resilience")]
+    pub fn max_concurrent_creations(self, n: usize) -> Self {
+        let permits = Arc::new(CreationPermits::new(n.max(1), None));
+        self.update(|definition| definition.permits = Some(permits.clone()));
+        self
+    }
+
+    /// Like [`max_concurrent_creations`](Self::max_concurrent_creations), creations waiting
+    /// longer than `timeout` for a permit fail with [`BeanError::RateLimited`]
+    #[cfg(feature = "resilience")]
+    pub fn max_concurrent_creations_within(self, n: usize, timeout: Duration) -> Self {
+        let permits = Arc::new(CreationPermits::new(n.max(1), Some(timeout)));
+        self.update(|definition| definition.permits = Some(permits.clone()));
+        self
+    }
+
+    /// Allow at most `n_per_sec` invocations of the factory per second, in bursts of up to
+    /// `n_per_sec`, meant for prototypes
+    ///
+    /// Resolutions beyond the limit fail fast with [`BeanError::RateLimited`], while
+    /// [`AsyncBeanContainer`](crate::async_container::AsyncBeanContainer) waits and retries.
+    /// Protects downstream resources when a hot loop mints expensive prototypes by mistake.
+    #[cfg(feature = "resilience")]
+    pub fn creation_rate_limit(self, n_per_sec: u32) -> Self {
+        let limit = Arc::new(CreationRateLimit::new(n_per_sec));
+        self.update(|definition| definition.rate_limit = Some(limit.clone()));
+        self
+    }
+
+    #[cfg(feature = "lifecycle")]
+    pub(crate) fn with_service(self, service: ServiceSpec) -> Self {
+        self.update(|definition| definition.service = Some(service.clone()));
+        self
+    }
+
+    /// Identifiers of the registered definitions, e.g. a named bean and its by-type alias
+    #[cfg(feature = "async")]
+    pub(crate) fn ids(&self) -> &[Identifier] {
+        &self.ids
+    }
+
+    #[cfg(feature = "lifecycle")]
+    pub(crate) fn update_service(&self, f: impl Fn(&mut ServiceSpec)) {
+        self.update(|definition| {
+            if let Some(service) = &mut definition.service {
+                f(service);
+            }
+        });
+    }
+
+    /// Replace the factory of the registered beans by `wrap(factory)`
+    #[cfg(feature = "modules")]
+    pub(crate) fn wrap_factory(&self, wrap: impl Fn(Arc<dyn BeanFactory>) -> Arc<dyn BeanFactory>) {
+        self.update(|definition| definition.factory = wrap(definition.factory.clone()));
+    }
+
+    #[cfg_attr(
+        not(any(
+            feature = "lifecycle",
+            feature = "modules",
+            feature = "introspection",
+            feature = "prefetch",
+            feature = "resilience",
+            feature = "wiring"
+        )),
+        allow(dead_code)
+    )]
+    fn update(&self, f: impl Fn(&mut BeanDefinition)) {
+        let mut beans = self.container.beans.write().unwrap();
+        for id in &self.ids {
+            if let Some(definition) = beans.get_mut(id) {
+                f(definition);
+            }
+        }
+    }
+}
+
+/// Definition of a bean to register, see [`BeanContainer::register_all`]
+pub struct BeanDef {
+    name: Option<String>,
+    type_name: &'static str,
+    type_spec_id: Identifier,
+    unnamed_id: Identifier,
+    scope: Scope,
+    factory: Arc<dyn BeanFactory>,
+    // Replace an existing definition instead of failing
+    replace: bool,
+    // Occupies neither its type nor any name, see `BeanContainer::register_anonymous`
+    anonymous: bool,
+    profile: Option<String>,
+    // Cargo cfg predicate the registration is compiled under, see `register_cfg!`
+    #[cfg(feature = "introspection")]
+    gate: Option<String>,
+    // Call site which defined the bean
+    #[cfg(feature = "introspection")]
+    location: &'static Location<'static>,
+    #[cfg(feature = "modules")]
+    priority: i32,
+    #[cfg(feature = "modules")]
+    module: Option<String>,
+    #[cfg(feature = "modules")]
+    internal: bool,
+    #[cfg(feature = "wiring")]
+    requires: Vec<Dependency>,
+}
+
+/// Dependency declared ahead of creation, see [`BeanDef::depends_on`]
+#[cfg(feature = "wiring")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency(DependencyKey);
+
+#[cfg(feature = "wiring")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DependencyKey {
+    Type(TypeId, &'static str),
+    Named(String),
+}
+
+#[cfg(feature = "wiring")]
+impl Dependency {
+    /// The bean of type `T`
+    pub fn on<T: Any + Send + Sync + 'static>() -> Self {
+        Dependency(DependencyKey::Type(TypeId::of::<T>(), type_name::<T>()))
+    }
+
+    /// The bean named `name`
+    pub fn named(name: &str) -> Self {
+        Dependency(DependencyKey::Named(name.to_string()))
+    }
+
+    /// Same dependency with its name normalized by the policy
+    #[cfg(feature = "naming")]
+    fn normalized(self, policy: NamePolicy) -> Self {
+        match self.0 {
+            DependencyKey::Named(name) => Dependency::named(&policy.normalize(&name)),
+            key => Dependency(key),
+        }
+    }
+
+    /// Identifier the dependency resolves to, like [`BeanContainer::find_id`]
+    fn key(&self, beans: &HashMap<Identifier, BeanDefinition>) -> Identifier {
+        match &self.0 {
+            DependencyKey::Named(name) => Identifier::named(name),
+            DependencyKey::Type(type_id, name) => {
+                let unnamed = Identifier::Unnamed(*type_id, name);
+                let type_spec = Identifier::TypeSpec(*type_id, name);
+                if !beans.contains_key(&type_spec) && beans.contains_key(&unnamed) {
+                    unnamed
+                } else {
+                    type_spec
+                }
+            }
+        }
+    }
+}
+
+/// Changes made by applying a [`BeanDef`], kept to roll them back
+struct Applied {
+    // Inserted identifiers, main one first
+    ids: Vec<Identifier>,
+    // Definitions replaced by the insertion
+    displaced: Vec<(Identifier, BeanDefinition)>,
+}
+
+impl BeanDef {
+    /// Bean registered by type
+    #[track_caller]
+    pub fn new<T, F>(scope: Scope, factory: F) -> Self
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(&mut Dependencies) -> Result<T, BeanError> + Send + Sync + 'static,
+    {
+        BeanDef {
+            name: None,
+            type_name: type_name::<T>(),
+            type_spec_id: Identifier::type_spec::<T>(),
+            unnamed_id: Identifier::unnamed::<T>(),
+            scope,
+            factory: into_bean_factory(factory),
+            replace: false,
+            anonymous: false,
+            profile: None,
+            #[cfg(feature = "introspection")]
+            gate: None,
+            #[cfg(feature = "introspection")]
+            location: Location::caller(),
+            #[cfg(feature = "modules")]
+            priority: 0,
+            #[cfg(feature = "modules")]
+            module: None,
+            #[cfg(feature = "modules")]
+            internal: false,
+            #[cfg(feature = "wiring")]
+            requires: Vec::new(),
+        }
+    }
+
+    /// Bean registered by type with a factory producing type-erased instances
+    #[track_caller]
+    #[cfg(any(feature = "modules", feature = "async"))]
+    pub(crate) fn from_factory<T: Any + Send + Sync + 'static>(
+        scope: Scope,
+        factory: Arc<dyn BeanFactory>,
+    ) -> Self {
+        BeanDef {
+            name: None,
+            type_name: type_name::<T>(),
+            type_spec_id: Identifier::type_spec::<T>(),
+            unnamed_id: Identifier::unnamed::<T>(),
+            scope,
+            factory,
+            replace: false,
+            anonymous: false,
+            profile: None,
+            #[cfg(feature = "introspection")]
+            gate: None,
+            #[cfg(feature = "introspection")]
+            location: Location::caller(),
+            #[cfg(feature = "modules")]
+            priority: 0,
+            #[cfg(feature = "modules")]
+            module: None,
+            #[cfg(feature = "modules")]
+            internal: false,
+            #[cfg(feature = "wiring")]
+            requires: Vec::new(),
+        }
+    }
+
+    /// Register with given name instead of by type
+    pub fn named(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Replace the existing definition with the same key, if any, instead of failing
+    ///
+    /// The replaced definition's cached instance is dropped from the container
+    pub fn replacing(mut self) -> Self {
+        self.replace = true;
+        self
+    }
+
+    /// Only register the bean when the profile is active,
+    /// see [`BeanContainer::set_active_profiles`]
+    pub fn profile(mut self, profile: &str) -> Self {
+        self.profile = Some(profile.to_string());
+        self
+    }
+
+    /// Priority against definitions of the same type or name, defaults to 0
+    ///
+    /// A definition of higher priority replaces the registered one, one of lower priority is
+    /// skipped, whatever the registration order. Lets a library provide a default the
+    /// application overrides by registering its own bean.
+    #[cfg(feature = "modules")]
+    pub fn default_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Module the bean belongs to, set by [`ContainerBuilder::module`](crate::builder::ContainerBuilder::module)
+    #[cfg(feature = "modules")]
+    pub fn in_module(mut self, module: &str) -> Self {
+        self.module = Some(module.to_string());
+        self
+    }
+
+    /// Only resolvable from factories of beans of the same module, or with a
+    /// [`ModuleToken`](crate::access::ModuleToken) of the module
+    ///
+    /// Registering it fails with [`BeanError::Internal`] if it belongs to no module
+    #[cfg(feature = "modules")]
+    pub fn internal(mut self) -> Self {
+        self.internal = true;
+        self
+    }
+
+    /// Declare that the bean depends on `T`
+    ///
+    /// Registering a definition whose declared dependencies lead back to it fails with
+    /// `CircularDependency`, and [`BeanContainer::validate`] reports the declared dependencies
+    /// which are missing, before any bean is created
+    #[cfg(feature = "wiring")]
+    pub fn depends_on<T: Any + Send + Sync + 'static>(mut self) -> Self {
+        self.requires.push(Dependency::on::<T>());
+        self
+    }
+
+    /// Declare that the bean depends on the bean named `name`, see [`depends_on`](Self::depends_on)
+    #[cfg(feature = "wiring")]
+    pub fn depends_on_named(mut self, name: &str) -> Self {
+        self.requires.push(Dependency::named(name));
+        self
+    }
+
+    /// Declare dependencies, see [`depends_on`](Self::depends_on)
+    #[cfg(feature = "wiring")]
+    pub fn with_dependencies(mut self, dependencies: impl IntoIterator<Item = Dependency>) -> Self {
+        self.requires.extend(dependencies);
+        self
+    }
+
+    #[cfg(feature = "modules")]
+    pub(crate) fn module_name(&self) -> Option<&str> {
+        self.module.as_deref()
+    }
+
+    /// Record the cfg predicate the registration is compiled under, set by [`register_cfg!`](crate::register_cfg)
+    ///
+    /// Only surfaced in introspection, a no-op without the `introspection` feature
+    #[cfg_attr(not(feature = "introspection"), allow(unused_variables, unused_mut))]
+    pub fn gated_by(mut self, cfg: &str) -> Self {
+        #[cfg(feature = "introspection")]
+        {
+            self.gate = Some(cfg.to_string());
+        }
+        self
+    }
+
+    /// Identifier the definition is registered under
+    pub(crate) fn key(&self) -> Identifier {
+        match &self.name {
+            Some(name) => Identifier::named(name),
+            None => self.type_spec_id.clone(),
+        }
+    }
+
+    /// Same definition with its name, and the names of its declared dependencies, normalized
+    /// by the policy
+    #[cfg(feature = "naming")]
+    pub(crate) fn normalized(mut self, policy: NamePolicy) -> Self {
+        self.name = self.name.map(|name| policy.normalize(&name));
+        #[cfg(feature = "wiring")]
+        {
+            self.requires = self
+                .requires
+                .into_iter()
+                .map(|dependency| dependency.normalized(policy))
+                .collect();
+        }
+        self
+    }
+
+    /// Name, or type name for beans registered by type
+    #[cfg(feature = "modules")]
+    pub(crate) fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(self.type_name)
+    }
+
+    #[cfg(feature = "reload")]
+    pub(crate) fn is_replacing(&self) -> bool {
+        self.replace
+    }
+
+    /// Whether registering both definitions fails, rather than one replacing the other
+    #[cfg(any(feature = "modules", feature = "reload"))]
+    pub(crate) fn conflicts_with(&self, other: &BeanDef) -> bool {
+        #[cfg(feature = "modules")]
+        let same_priority = self.priority == other.priority;
+        #[cfg(not(feature = "modules"))]
+        let same_priority = true;
+        !self.replace && self.key() == other.key() && same_priority
+    }
+
+    fn definition(&self, order: u64) -> BeanDefinition {
+        let type_id = self.type_spec_id.type_id().unwrap();
+        #[cfg_attr(
+            not(any(feature = "introspection", feature = "modules", feature = "wiring")),
+            allow(unused_mut)
+        )]
+        let mut definition = BeanDefinition::new(
+            self.factory.clone(),
+            type_id,
+            self.type_name,
+            self.scope,
+            order,
+        );
+        #[cfg(feature = "introspection")]
+        {
+            definition.profile = self.profile.clone();
+            definition.gate = self.gate.clone();
+            definition.location = Some(self.location);
+        }
+        #[cfg(feature = "modules")]
+        {
+            definition.priority = self.priority;
+            definition.module = self.module.clone();
+            definition.internal = self.internal;
+        }
+        #[cfg(feature = "wiring")]
+        {
+            definition.requires = self.requires.clone();
+        }
+        definition
+    }
+
+    fn apply(
+        self,
+        beans: &mut HashMap<Identifier, BeanDefinition>,
+        order: u64,
+    ) -> Result<Applied, BeanError> {
+        let mut applied = Applied {
+            ids: Vec::new(),
+            displaced: Vec::new(),
+        };
+
+        #[cfg(feature = "scopes")]
+        if self.scope == Scope::Pooled(0) {
+            return Err(BeanError::Config(format!(
+                "Pool of {} must hold at least one instance",
+                self.type_name
+            )));
+        }
+
+        if self.anonymous {
+            let id = Identifier::Anonymous(order, self.type_name);
+            beans.insert(id.clone(), self.definition(order));
+            applied.ids.push(id);
+            return Ok(applied);
+        }
+
+        let key = self.key();
+        #[cfg(feature = "modules")]
+        let replace = match beans.get(&key) {
+            Some(existing) if !self.replace && existing.priority > self.priority => {
+                // Overridden by the registered definition
+                return Ok(applied);
+            }
+            Some(existing) => self.replace || existing.priority != self.priority,
+            None => self.replace,
+        };
+        #[cfg(not(feature = "modules"))]
+        let replace = self.replace;
+        let displaced = if replace { beans.remove(&key) } else { None };
+        if let Some(previous) = displaced {
+            // The by-type alias of a displaced named bean goes with it, and is added back
+            // for the new definition below
+            if self.name.is_some()
+                && let Some(alias) = beans.get(&self.unnamed_id)
+                && alias.order == previous.order
+            {
+                let alias = beans.remove(&self.unnamed_id).unwrap();
+                applied.displaced.push((self.unnamed_id.clone(), alias));
+            }
+            applied.displaced.push((key, previous));
+        }
+
+        let Some(name) = self.name.clone() else {
+            // If TypeSpec exists, throw error
+            if beans.contains_key(&self.type_spec_id) {
+                return Err(BeanError::AlreadyRegistered(BeanId::new(
+                    &self.type_spec_id,
+                )));
+            }
+            // If unnamed exists, remove it
+            if let Some(unnamed) = beans.remove(&self.unnamed_id) {
+                applied.displaced.push((self.unnamed_id.clone(), unnamed));
+            }
+            // Add TypeSpec
+            beans.insert(self.type_spec_id.clone(), self.definition(order));
+            applied.ids.push(self.type_spec_id);
+            return Ok(applied);
+        };
+
+        // Check if Named already exists
+        let named_id = Identifier::named(&name);
+        if beans.contains_key(&named_id) {
+            return Err(BeanError::AlreadyRegistered(BeanId::new(&named_id)));
+        }
+
+        // Register Named
+        beans.insert(named_id.clone(), self.definition(order));
+        applied.ids.push(named_id.clone());
+
+        // Rule 1: If TypeSpec and Unnamed do not exist, add Unnamed
+        if !beans.contains_key(&self.type_spec_id) && !beans.contains_key(&self.unnamed_id) {
+            let mut alias = self.definition(order);
+            alias.alias_of = Some(named_id.clone());
+            beans.insert(self.unnamed_id.clone(), alias);
+            applied.ids.push(self.unnamed_id);
+        }
+        Ok(applied)
+    }
+}
+
+/// Observers of a container, shared with the [`Checked`] beans reporting to them
+#[cfg(feature = "observers")]
+pub(crate) type Observers = Arc<RwLock<Vec<Arc<dyn BeanObserver>>>>;
+
+/// Container held by one of its own beans, e.g. a provider, weak to not keep it alive
+#[cfg(feature = "scopes")]
+pub(crate) type BeanRef = Weak<BeanContainer>;
+
+/// Identifiers of the registered beans, in registration order
+fn ordered_ids(beans: &HashMap<Identifier, BeanDefinition>) -> Vec<Identifier> {
+    let mut ids = beans
+        .iter()
+        .map(|(id, definition)| (definition.order, id.clone()))
+        .collect::<Vec<_>>();
+    // A named bean and its unnamed alias share the order, name first
+    ids.sort_by_key(|(order, id)| (*order, matches!(id, Identifier::Unnamed(..))));
+    ids.into_iter().map(|(_, id)| id).collect()
+}
+
+/// Whether a bean is registered under `id`, a by-type lookup also matching a named bean
+#[cfg(feature = "introspection")]
+fn registered_in(beans: &HashMap<Identifier, BeanDefinition>, id: &Identifier) -> bool {
+    match id {
+        Identifier::TypeSpec(type_id, name) => {
+            beans.contains_key(id) || beans.contains_key(&Identifier::Unnamed(*type_id, name))
+        }
+        _ => beans.contains_key(id),
+    }
+}
+
+/// Shortest path from `start` to a node matching `is_target`, both ends included, through the
+/// `successors` of each node
+///
+/// The target is at least one step away, `start` itself matches only as the end of a cycle.
+#[cfg(any(feature = "wiring", feature = "graph", feature = "testing"))]
+pub(crate) fn shortest_path<N, I>(
+    start: N,
+    successors: impl Fn(&N) -> I,
+    is_target: impl Fn(&N) -> bool,
+) -> Option<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    // Breadth-first, remembering the node each one was reached from
+    let mut reached_from: HashMap<N, N> = HashMap::new();
+    let mut queue = VecDeque::from([start.clone()]);
+    while let Some(current) = queue.pop_front() {
+        for next in successors(&current) {
+            if is_target(&next) {
+                let mut path = vec![next, current];
+                while let Some(previous) = reached_from.get(path.last().unwrap()) {
+                    path.push(previous.clone());
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if next != start && !reached_from.contains_key(&next) {
+                reached_from.insert(next.clone(), current.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Identifiers of the definition registered under `id`, e.g. a named bean and its by-type alias
+fn definition_ids(beans: &HashMap<Identifier, BeanDefinition>, id: &Identifier) -> Vec<Identifier> {
+    let Some(order) = beans.get(id).map(|d| d.order) else {
+        return Vec::new();
+    };
+    beans
+        .iter()
+        .filter(|(_, definition)| definition.order == order)
+        .map(|(id, _)| id.clone())
+        .collect()
+}
+
+/// Dependency path from the bean `id` back to itself, i.e. to one of its `ids`, through
+/// declared and observed dependencies, e.g. `Bean(a) -> Bean(b) -> Bean(a)`
+#[cfg(feature = "wiring")]
+fn dependency_cycle(
+    beans: &HashMap<Identifier, BeanDefinition>,
+    id: &Identifier,
+    ids: &[Identifier],
+) -> Option<String> {
+    let path = shortest_path(
+        id.clone(),
+        |current| {
+            beans
+                .get(current)
+                .map(|definition| definition.dependency_keys(beans))
+                .unwrap_or_default()
+        },
+        |next| ids.contains(next),
+    )?;
+    let path: Vec<String> = path.iter().map(|id| id.to_string()).collect();
+    Some(path.join(" -> "))
+}
+
+impl Applied {
+    fn undo(self, beans: &mut HashMap<Identifier, BeanDefinition>) {
+        for id in &self.ids {
+            beans.remove(id);
+        }
+        beans.extend(self.displaced);
+    }
+}
+
+/// Shared singleton view of a bean registered with [`BeanContainer::register_dual`]
+pub struct Shared<T>(Arc<T>);
+
+impl<T> Shared<T> {
+    /// The shared instance
+    pub fn arc(&self) -> Arc<T> {
+        self.0.clone()
+    }
+}
+
+impl<T> Deref for Shared<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+static NEXT_CONTAINER_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Beans registered on their first lookup by type, unless the application registered its own
+#[cfg(feature = "config")]
+pub(crate) const BUILT_IN_BEANS: &[BuiltInBean] = &[
+    (
+        TypeId::of::<Environment>,
+        BeanContainer::provide_environment,
+    ),
+    (TypeId::of::<AppInfo>, BeanContainer::provide_app_info),
+    (TypeId::of::<Clock>, BeanContainer::provide_clock),
+];
+
+/// No built-in beans without the `config` feature
+#[cfg(not(feature = "config"))]
+pub(crate) const BUILT_IN_BEANS: &[BuiltInBean] = &[];
+
+type BuiltIn = fn(&BeanContainer) -> Result<(), BeanError>;
+
+type BuiltInBean = (fn() -> TypeId, BuiltIn);
+
+/// Default longest dependency chain allowed during one resolution
+pub const DEFAULT_MAX_DEPTH: usize = 100;
+
+pub struct BeanContainer {
+    // Unique id, used to bind entered scopes to this container
+    id: u64,
+    beans: RwLock<HashMap<Identifier, BeanDefinition>>,
+    #[cfg(feature = "scopes")]
+    pub(crate) leak_tracker: OnceLock<Arc<LeakTracker>>,
+    // Snapshots of the container, see `record_history`
+    #[cfg(feature = "introspection")]
+    pub(crate) recorder: OnceLock<crate::history::Recorder>,
+    // Last resolutions, see `enable_trace`
+    #[cfg(feature = "introspection")]
+    pub(crate) trace: OnceLock<ResolutionTrace>,
+    // Observer feeding the streams of `events`
+    #[cfg(feature = "tokio")]
+    pub(crate) event_hub: OnceLock<crate::events::EventHub>,
+    max_depth: AtomicUsize,
+    #[cfg(feature = "observers")]
+    observers: Observers,
+    next_order: AtomicU64,
+    // Creation sequence of singletons
+    #[cfg(feature = "lifecycle")]
+    next_creation: AtomicU64,
+    // Seed of the deterministic mode, if enabled
+    deterministic_seed: RwLock<Option<u64>>,
+    active_profiles: RwLock<Vec<String>>,
+    #[cfg(feature = "naming")]
+    name_policy: RwLock<NamePolicy>,
+    // Conversions satisfying requests of unregistered types, keyed by target type
+    #[cfg(feature = "wiring")]
+    converters: RwLock<HashMap<TypeId, Arc<dyn BeanFactory>>>,
+    // Declared parent of nested scopes
+    #[cfg(feature = "scopes")]
+    pub(crate) scope_parents: RwLock<HashMap<&'static str, &'static str>>,
+    // Beans left out of the build by `register_cfg!`, with their cfg predicate
+    gated_out: RwLock<Vec<(String, String)>>,
+    #[cfg(feature = "reload")]
+    watches: Watches,
+    // Order of the startup phases
+    #[cfg(feature = "lifecycle")]
+    pub(crate) startup_phases: RwLock<Vec<String>>,
+    // Registrations are rejected once sealed
+    #[cfg(feature = "introspection")]
+    pub(crate) sealed: AtomicBool,
+    // Cycles tolerated by `seal`, as bean names
+    #[cfg(feature = "introspection")]
+    pub(crate) allowed_cycles: RwLock<Vec<Vec<String>>>,
+    // Modules installed successfully
+    #[cfg(feature = "modules")]
+    pub(crate) installed_modules: RwLock<Vec<String>>,
+    // One-time initializers, see `once`
+    #[cfg(feature = "lifecycle")]
+    pub(crate) initializers: crate::once::Initializers,
+    // Decorator stacks by name
+    #[cfg(feature = "modules")]
+    pub(crate) decorator_stacks: RwLock<HashMap<String, Arc<crate::decorator::StackFn>>>,
+    #[cfg(feature = "testing")]
+    pub(crate) injected_failures: crate::testing::InjectedFailures,
+    #[cfg(feature = "testing")]
+    pub(crate) overrides: crate::testing::Overrides,
+    // Fallbacks of the beans not registered, see `TestContainer`
+    #[cfg(feature = "testing")]
+    pub(crate) stubs: crate::testing::Stubs,
+    // Schemas of config beans, keyed by prefix
+    #[cfg(feature = "schema")]
+    pub(crate) config_schemas: RwLock<Vec<(String, serde_json::Value)>>,
+    // Container lookups fall back to when a bean isn't registered here
+    parent: Option<Arc<BeanContainer>>,
+    // Wall-clock and monotonic time the container was built
+    #[cfg(feature = "config")]
+    created_at: (SystemTime, Instant),
+}
+
+impl BeanContainer {
+    pub fn new() -> Self {
+        BeanContainer {
+            id: NEXT_CONTAINER_ID.fetch_add(1, Ordering::Relaxed),
+            beans: RwLock::new(HashMap::new()),
+            #[cfg(feature = "scopes")]
+            leak_tracker: OnceLock::new(),
+            #[cfg(feature = "introspection")]
+            recorder: OnceLock::new(),
+            #[cfg(feature = "introspection")]
+            trace: OnceLock::new(),
+            #[cfg(feature = "tokio")]
+            event_hub: OnceLock::new(),
+            max_depth: AtomicUsize::new(DEFAULT_MAX_DEPTH),
+            #[cfg(feature = "observers")]
+            observers: Observers::default(),
+            next_order: AtomicU64::new(0),
+            #[cfg(feature = "lifecycle")]
+            next_creation: AtomicU64::new(0),
+            deterministic_seed: RwLock::new(None),
+            active_profiles: RwLock::new(Vec::new()),
+            #[cfg(feature = "naming")]
+            name_policy: RwLock::new(NamePolicy::Exact),
+            #[cfg(feature = "wiring")]
+            converters: RwLock::new(HashMap::new()),
+            #[cfg(feature = "scopes")]
+            scope_parents: RwLock::new(HashMap::new()),
+            gated_out: RwLock::new(Vec::new()),
+            #[cfg(feature = "reload")]
+            watches: Watches::default(),
+            #[cfg(feature = "lifecycle")]
+            startup_phases: RwLock::new(Vec::new()),
+            #[cfg(feature = "introspection")]
+            sealed: AtomicBool::new(false),
+            #[cfg(feature = "introspection")]
+            allowed_cycles: RwLock::new(Vec::new()),
+            #[cfg(feature = "modules")]
+            installed_modules: RwLock::new(Vec::new()),
+            #[cfg(feature = "lifecycle")]
+            initializers: Default::default(),
+            #[cfg(feature = "modules")]
+            decorator_stacks: RwLock::new(HashMap::new()),
+            #[cfg(feature = "testing")]
+            injected_failures: Default::default(),
+            #[cfg(feature = "testing")]
+            overrides: Default::default(),
+            #[cfg(feature = "testing")]
+            stubs: Default::default(),
+            #[cfg(feature = "schema")]
+            config_schemas: RwLock::new(Vec::new()),
+            parent: None,
+            #[cfg(feature = "config")]
+            created_at: (SystemTime::now(), Instant::now()),
+        }
+    }
+
+    #[track_caller]
+    pub fn register<T, F>(&self, scope: Scope, factory: F) -> Result<Registration<'_>, BeanError>
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(&mut Dependencies) -> Result<T, BeanError> + Send + Sync + 'static,
+    {
+        self.register_def(BeanDef::new::<T, F>(scope, factory))
+    }
+
+    #[track_caller]
+    pub fn register_named<T, F>(
+        &self,
+        name: &str,
+        scope: Scope,
+        factory: F,
+    ) -> Result<Registration<'_>, BeanError>
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(&mut Dependencies) -> Result<T, BeanError> + Send + Sync + 'static,
+    {
+        self.register_def(BeanDef::new::<T, F>(scope, factory).named(name))
+    }
+
+    /// Register a bean whose `setter` runs on each new instance once the creation stack
+    /// unwound, e.g. to wire two singletons referencing each other
+    ///
+    /// The beans the setter resolves can depend on the instance, they get it along the
+    /// resolution. Other resolutions only see it, and the beans created after it on the
+    /// resolution, once the setters ran: if one fails, the resolution fails and they are
+    /// dropped. The setter sets the references through interior mutability, e.g. a `OnceLock`.
+    ///
+    /// ```text
+    /// container.register_with_setters::<Node, _, _>(
+    ///     Scope::Singleton,
+    ///     |_| Ok(Node::default()),
+    ///     |node, deps| node.set_peer(deps.get::<Peer>()?),
+    /// )?;
+    /// ```
+    #[track_caller]
+    #[cfg(feature = "wiring")]
+    pub fn register_with_setters<T, F, S>(
+        &self,
+        scope: Scope,
+        factory: F,
+        setter: S,
+    ) -> Result<Registration<'_>, BeanError>
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(&mut Dependencies) -> Result<T, BeanError> + Send + Sync + 'static,
+        S: Fn(&T, &mut Dependencies) -> Result<(), BeanError> + Send + Sync + 'static,
+    {
+        let setter: Arc<SetterFn> = Arc::new(move |instance, deps| {
+            let instance = instance
+                .downcast_ref::<T>()
+                .ok_or(BeanError::DowncastFailed(type_name::<T>()))?;
+            setter(instance, deps)
+        });
+        let registration = self.register::<T, F>(scope, factory)?;
+        registration.update(|definition| definition.setter = Some(setter.clone()));
+        Ok(registration)
+    }
+
+    /// Register a small `Clone` value as a singleton, e.g. `ServerPort(8080)`
+    ///
+    /// Get clones of it instead of an `Arc` with [`get_value_bean`](Self::get_value_bean)
+    #[track_caller]
+    pub fn register_value<T: Clone + Any + Send + Sync + 'static>(
+        &self,
+        value: T,
+    ) -> Result<Registration<'_>, BeanError> {
+        self.register::<T, _>(Scope::Singleton, move |_| Ok(value.clone()))
+    }
+
+    /// Register one factory under two keys: `T` as prototype and `Shared<T>` as singleton
+    ///
+    /// Callers pick a fresh instance with `create::<T>()` (or `get::<T>()`),
+    /// or the shared one with `get::<Shared<T>>()`
+    #[track_caller]
+    pub fn register_dual<T, F>(&self, factory: F) -> Result<Registration<'_>, BeanError>
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(&mut Dependencies) -> Result<T, BeanError> + Send + Sync + 'static,
+    {
+        let fresh = BeanDef::new::<T, F>(Scope::Prototype, factory);
+        let shared = BeanDef::new::<Shared<T>, _>(Scope::Singleton, |deps: &mut Dependencies| {
+            Ok(Shared(deps.get::<T>()?))
+        });
+
+        // Register both keys or none
+        let registrations = self.register_all([fresh, shared])?;
+        Ok(Registration {
+            container: self,
+            ids: registrations.into_iter().flat_map(|r| r.ids).collect(),
+        })
+    }
+
+    /// Replace the bean registered by type, or register it if absent
+    ///
+    /// A cached singleton of the previous definition is dropped from the container,
+    /// the next resolution creates a new instance
+    #[track_caller]
+    pub fn replace<T, F>(&self, scope: Scope, factory: F) -> Result<Registration<'_>, BeanError>
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(&mut Dependencies) -> Result<T, BeanError> + Send + Sync + 'static,
+    {
+        self.register_def(BeanDef::new::<T, F>(scope, factory).replacing())
+    }
+
+    /// Child container whose lookups fall back to this one for beans it doesn't register,
+    /// e.g. to layer module-specific registrations over application-wide singletons
+    ///
+    /// Beans of this container are resolved here, with their dependencies looked up in this
+    /// container only. `get_all` and `get_named_map` don't look into the parent. The child starts
+    /// with the name policy, active profiles and observers of this container, later changes to
+    /// either container don't carry over. Its beans keep access to the internal beans of their
+    /// module in the parent.
+    pub fn new_child(self: &Arc<Self>) -> BeanContainer {
+        let mut child = BeanContainer::new();
+        child.parent = Some(self.clone());
+        #[cfg(feature = "naming")]
+        child.set_name_policy(self.name_policy());
+        *child.active_profiles.write().unwrap() = self.active_profiles();
+        #[cfg(feature = "observers")]
+        {
+            *child.observers.write().unwrap() = self.observers.read().unwrap().clone();
+        }
+        child
+    }
+
+    /// Container lookups fall back to, see [`new_child`](Self::new_child)
+    pub fn parent(&self) -> Option<&Arc<BeanContainer>> {
+        self.parent.as_ref()
+    }
+
+    /// Register one definition, see [`BeanDef`]
+    pub fn register_def(&self, def: BeanDef) -> Result<Registration<'_>, BeanError> {
+        let mut registrations = self.register_all([def])?;
+        Ok(registrations.remove(0))
+    }
+
+    /// Register a bean which occupies neither its type nor any name, resolvable only through
+    /// the returned handle
+    ///
+    /// Lets libraries create internal beans without interfering with the application's
+    /// registrations of the same type. Registered like [`register_all`](Self::register_all),
+    /// so it fails once the container is sealed and joins the module being installed
+    #[track_caller]
+    pub fn register_anonymous<T, F>(
+        &self,
+        scope: Scope,
+        factory: F,
+    ) -> Result<BeanHandle<T>, BeanError>
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(&mut Dependencies) -> Result<T, BeanError> + Send + Sync + 'static,
+    {
+        let mut def = BeanDef::new::<T, F>(scope, factory);
+        def.anonymous = true;
+        let registration = self.register_def(def)?;
+        Ok(BeanHandle {
+            id: BeanId::new(&registration.ids[0]),
+            _marker: PhantomData,
+        })
+    }
+
+    /// Get the bean of a handle
+    /// **NOTE**: panics if creation fails
+    pub fn get_handle<T: Any + Send + Sync + 'static>(&self, handle: &BeanHandle<T>) -> Arc<T> {
+        self.try_get_handle(handle).unwrap()
+    }
+
+    /// Get the bean of a handle
+    pub fn try_get_handle<T: Any + Send + Sync + 'static>(
+        &self,
+        handle: &BeanHandle<T>,
+    ) -> Result<Arc<T>, BeanError> {
+        let mut context = self.new_context();
+        self.get_handle_with_context(handle, &mut context)
+    }
+
+    fn get_handle_with_context<T: Any + Send + Sync + 'static>(
+        &self,
+        handle: &BeanHandle<T>,
+        context: &mut CreationContext,
+    ) -> Result<Arc<T>, BeanError> {
+        self.resolve(handle.id.identifier(), context)?
+            .downcast::<T>()
+            .map_err(|_| BeanError::DowncastFailed(type_name::<T>()))
+    }
+
+    /// Record a bean left out of the build by [`register_cfg!`](crate::register_cfg)
+    #[doc(hidden)]
+    pub fn register_gated_out(&self, bean: &str, cfg: &str) -> Result<Registration<'_>, BeanError> {
+        let mut gated_out = self.gated_out.write().unwrap();
+        gated_out.push((bean.to_string(), cfg.to_string()));
+        Ok(Registration {
+            container: self,
+            ids: Vec::new(),
+        })
+    }
+
+    /// Beans left out of the build by [`register_cfg!`](crate::register_cfg),
+    /// with the cfg predicate they require
+    pub fn gated_out(&self) -> Vec<(String, String)> {
+        self.gated_out.read().unwrap().clone()
+    }
+
+    /// Register definitions atomically: either all of them are registered, or none if any fails
+    ///
+    /// Returns one registration handle per definition, in order
+    pub fn register_all(
+        &self,
+        defs: impl IntoIterator<Item = BeanDef>,
+    ) -> Result<Vec<Registration<'_>>, BeanError> {
+        #[cfg(feature = "introspection")]
+        if self.sealed.load(Ordering::Acquire) {
+            return Err(BeanError::Sealed);
+        }
+        let active_profiles = self.active_profiles();
+        #[cfg(feature = "modules")]
+        let module = crate::module::installing(self.id);
+        let mut beans = self.beans.write().unwrap();
+        let mut applied: Vec<Applied> = Vec::new();
+        let mut registered = Vec::new();
+
+        for def in defs {
+            let def = self.normalize(def);
+            #[cfg(feature = "modules")]
+            let def = match &module {
+                Some(module) => module.adopt(def),
+                None => def,
+            };
+            let scope = def.scope;
+            if let Some(profile) = &def.profile
+                && !active_profiles.contains(profile)
+            {
+                // Not part of the current environment
+                registered.push((Vec::new(), scope));
+                continue;
+            }
+            let order = self.next_order.fetch_add(1, Ordering::Relaxed);
+            #[cfg(feature = "wiring")]
+            let declares = !def.requires.is_empty();
+            #[cfg(feature = "modules")]
+            let outcome = if def.internal && def.module.is_none() {
+                // Unresolvable
+                Err(BeanError::Internal {
+                    bean: BeanId::new(&def.key()),
+                    module: None,
+                })
+            } else {
+                def.apply(&mut beans, order)
+            };
+            #[cfg(not(feature = "modules"))]
+            let outcome = def.apply(&mut beans, order);
+            #[cfg(feature = "wiring")]
+            let outcome = outcome.and_then(|a| match a.ids.first().filter(|_| declares) {
+                Some(id) => match dependency_cycle(&beans, id, &a.ids) {
+                    Some(cycle) => {
+                        a.undo(&mut beans);
+                        Err(BeanError::CircularDependency(cycle))
+                    }
+                    None => Ok(a),
+                },
+                None => Ok(a),
+            });
+            match outcome {
+                Ok(a) => {
+                    registered.push((a.ids.clone(), scope));
+                    applied.push(a);
+                }
+                Err(e) => {
+                    // Roll back what was applied, last first
+                    for a in applied.into_iter().rev() {
+                        a.undo(&mut beans);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        drop(beans);
+
+        #[cfg_attr(not(feature = "observers"), allow(unused_variables))]
+        for (ids, scope) in &registered {
+            #[cfg(feature = "reload")]
+            self.watches.bump(ids);
+            #[cfg(feature = "observers")]
+            if let Some(id) = ids.first() {
+                self.notify(|o| o.on_registered(BeanId::new(id), *scope));
+            }
+        }
+        #[cfg(feature = "introspection")]
+        if registered.iter().any(|(ids, _)| !ids.is_empty()) {
+            self.record(RecordPoint::Registrations, || {
+                HistoryEvent::Registered(
+                    registered
+                        .iter()
+                        .filter_map(|(ids, _)| ids.first().map(BeanId::new))
+                        .collect(),
+                )
+            });
+        }
+        Ok(registered
+            .into_iter()
+            .map(|(ids, _)| Registration {
+                container: self,
+                ids,
+            })
+            .collect())
+    }
+
+    /// Get bean by type
+    /// **NOTE**: panics if bean not found
+    pub fn get<T: Any + Send + Sync + 'static>(&self) -> Arc<T> {
+        self.try_get::<T>(None).unwrap()
+    }
+
+    /// Get named bean by type
+    /// **NOTE**: panics if bean not found
+    pub fn get_named<T: Any + Send + Sync + 'static>(&self, name: &str) -> Arc<T> {
+        self.try_get::<T>(Some(name)).unwrap()
+    }
+
+    /// Get every bean of type `T` registered by type or by name, in registration order,
+    /// e.g. to aggregate plugins without hard-coding their names
+    /// **NOTE**: panics if a creation fails
+    pub fn get_all<T: Any + Send + Sync + 'static>(&self) -> Vec<Arc<T>> {
+        self.try_get_all::<T>().unwrap()
+    }
+
+    /// Get every bean of type `T`, see [`get_all`](Self::get_all)
+    pub fn try_get_all<T: Any + Send + Sync + 'static>(&self) -> Result<Vec<Arc<T>>, BeanError> {
+        let mut context = self.new_context();
+        let all = self.get_all_with_context::<T>(&mut context, false)?;
+        Ok(all.into_iter().map(|(_, instance)| instance).collect())
+    }
+
+    /// Get the beans of type `T` registered by name, keyed by their name, e.g. to select
+    /// a strategy by a key known at runtime
+    /// **NOTE**: panics if a creation fails
+    pub fn get_named_map<T: Any + Send + Sync + 'static>(&self) -> HashMap<String, Arc<T>> {
+        self.try_get_named_map::<T>().unwrap()
+    }
+
+    /// Get the beans of type `T` registered by name, see [`get_named_map`](Self::get_named_map)
+    pub fn try_get_named_map<T: Any + Send + Sync + 'static>(
+        &self,
+    ) -> Result<HashMap<String, Arc<T>>, BeanError> {
+        let mut context = self.new_context();
+        let named = self.get_all_with_context::<T>(&mut context, true)?;
+        Ok(Self::by_name(named))
+    }
+
+    fn by_name<T>(named: Vec<(Identifier, Arc<T>)>) -> HashMap<String, Arc<T>> {
+        named
+            .into_iter()
+            .filter_map(|(id, instance)| match id {
+                Identifier::Named(name) => Some((name, instance)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Get a clone of a value bean, see [`register_value`](Self::register_value)
+    /// **NOTE**: panics if bean not found
+    pub fn get_value_bean<T: Clone + Any + Send + Sync + 'static>(&self) -> T {
+        T::clone(&self.get::<T>())
+    }
+
+    /// Create a fresh instance of a bean, bypassing singleton and scope caching
+    ///
+    /// Resolved like [`get`](Self::get) otherwise: access checks, guards, init hooks and setters
+    /// apply, and its dependencies are reused.
+    /// **NOTE**: panics if bean not found or creation failed
+    pub fn create<T: Any + Send + Sync + 'static>(&self) -> Arc<T> {
+        self.try_create::<T>().unwrap()
+    }
+
+    pub(crate) fn try_create<T: Any + Send + Sync + 'static>(&self) -> Result<Arc<T>, BeanError> {
+        let id = self
+            .find_id::<T>(None)
+            .ok_or_else(|| BeanError::NotFound(BeanId::new(&Identifier::type_spec::<T>())))?;
+        let mut context = self.new_context();
+        context.fresh = Some(self.instance_id(&id));
+        self.check_access(&id, &context, None)?;
+        self.resolve(&id, &mut context)?
+            .downcast::<T>()
+            .map_err(|_| BeanError::DowncastFailed(type_name::<T>()))
+    }
+
+    /// Get bean by type, or named bean if `name` is given, without panicking
+    pub fn try_get<T: Any + Send + Sync + 'static>(
+        &self,
+        name: Option<&str>,
+    ) -> Result<Arc<T>, BeanError> {
+        let mut context = self.new_context();
+        self.get_with_context::<T>(name, &mut context)
+    }
+
+    /// Get a bean, including internal beans of the token's module
+    #[cfg(feature = "modules")]
+    pub fn get_internal<T: Any + Send + Sync + 'static>(
+        &self,
+        token: &ModuleToken,
+        name: Option<&str>,
+    ) -> Result<Arc<T>, BeanError> {
+        let mut context = self.new_context();
+        self.get_with_access::<T>(name, &mut context, Some(token.module()))
+    }
+
+    /// Resolve a bean by its identifier, type-erased
+    #[cfg(any(feature = "scopes", feature = "lifecycle"))]
+    pub(crate) fn resolve_id(&self, id: &BeanId) -> Result<Arc<dyn Any + Send + Sync>, BeanError> {
+        let mut context = self.new_context();
+        self.resolve(id.identifier(), &mut context)
+    }
+
+    /// Background services in registration order, aliases excluded
+    #[cfg(feature = "lifecycle")]
+    pub(crate) fn services(&self) -> Vec<(BeanId, ServiceSpec)> {
+        let ids = self.ids_in_order();
+        let beans = self.beans.read().unwrap();
+        ids.into_iter()
+            .map(|id| BeanId::new(&id))
+            .filter(|id| !id.is_alias())
+            .filter_map(|id| {
+                let service = beans.get(id.identifier())?.service.clone()?;
+                Some((id, service))
+            })
+            .collect()
+    }
+
+    /// Register the built-in beans not registered by the application, see [`BUILT_IN_BEANS`]
+    ///
+    /// A child container resolves those of its parent instead
+    #[cfg(feature = "introspection")]
+    pub(crate) fn provide_built_ins(&self) {
+        if self.parent.is_some() {
+            return;
+        }
+        for (type_id, provide) in BUILT_IN_BEANS {
+            let registered = self
+                .beans
+                .read()
+                .unwrap()
+                .keys()
+                .any(|id| id.type_id() == Some(type_id()));
+            if !registered {
+                // Fails if another thread registered one meanwhile
+                let _ = provide(self);
+            }
+        }
+    }
+
+    /// Make the bean fail with `error` instead of being created, until refreshed
+    #[cfg(feature = "lifecycle")]
+    pub(crate) fn mark_unavailable(&self, id: &BeanId, error: BeanError) {
+        let mut beans = self.beans.write().unwrap();
+        for id in definition_ids(&beans, id.identifier()) {
+            if let Some(definition) = beans.get_mut(&id) {
+                definition.unavailable = Some(error.clone());
+            }
+        }
+    }
+
+    /// Context of a new resolution, made on behalf of the factory running on this thread if any
+    fn new_context(&self) -> CreationContext {
+        let mut context = CreationContext::new(Some(self.max_depth.load(Ordering::Relaxed)));
+        #[cfg(feature = "scopes")]
+        {
+            context.scopes = scope::active_scopes(self.id);
+        }
+        context.outer = Running::path(self.id);
+        context.requester = context.outer.last().cloned();
+        context
+    }
+
+    /// Get a bean on behalf of `requester`, e.g. the bean a provider was injected into, which
+    /// grants access to the internal beans of its module
+    #[cfg(any(feature = "scopes", feature = "async"))]
+    pub(crate) fn get_requested<T: Any + Send + Sync + 'static>(
+        &self,
+        name: Option<&str>,
+        requester: Option<&Identifier>,
+    ) -> Result<Arc<T>, BeanError> {
+        let mut context = self.requested_context(requester);
+        self.get_with_context::<T>(name, &mut context)
+    }
+
+    #[cfg(any(feature = "scopes", feature = "async"))]
+    fn requested_context(&self, requester: Option<&Identifier>) -> CreationContext {
+        let mut context = self.new_context();
+        if let Some(requester) = requester {
+            context.requester = Some(requester.clone());
+        }
+        context
+    }
+
+    /// Add an observer notified of registrations, creations and failures
+    #[cfg(feature = "observers")]
+    pub fn add_observer<O: BeanObserver + 'static>(&self, observer: O) {
+        self.observers.write().unwrap().push(Arc::new(observer));
+    }
+
+    #[cfg(feature = "wiring")]
+    pub(crate) fn converters(&self) -> &RwLock<HashMap<TypeId, Arc<dyn BeanFactory>>> {
+        &self.converters
+    }
+
+    /// Time the container was built
+    #[cfg(feature = "config")]
+    pub(crate) fn created_at(&self) -> (SystemTime, Instant) {
+        self.created_at
+    }
+
+    #[cfg(feature = "reload")]
+    pub(crate) fn watches(&self) -> &Watches {
+        &self.watches
+    }
+
+    #[cfg(feature = "observers")]
+    pub(crate) fn notify(&self, f: impl Fn(&dyn BeanObserver)) {
+        let observers = self.observers.read().unwrap().clone();
+        for observer in &observers {
+            f(observer.as_ref());
+        }
+    }
+
+    /// Set the longest dependency chain allowed during one resolution,
+    /// defaults to [`DEFAULT_MAX_DEPTH`]
+    ///
+    /// Factories can still override it for nested resolutions, see [`Dependencies::with_max_depth`]
+    pub fn set_max_depth(&self, max_depth: usize) {
+        self.max_depth.store(max_depth, Ordering::Relaxed);
+    }
+
+    /// Set how bean names are matched, defaults to [`NamePolicy::Exact`]
+    ///
+    /// Names are normalized when registered, so the policy must be set before registering
+    /// named beans
+    #[cfg(feature = "naming")]
+    pub fn set_name_policy(&self, policy: NamePolicy) {
+        *self.name_policy.write().unwrap() = policy;
+    }
+
+    #[cfg(feature = "naming")]
+    pub fn name_policy(&self) -> NamePolicy {
+        *self.name_policy.read().unwrap()
+    }
+
+    /// Identifier of a named bean under the name policy
+    pub(crate) fn named_id(&self, name: &str) -> Identifier {
+        #[cfg(feature = "naming")]
+        let name = &self.name_policy().normalize(name);
+        Identifier::named(name)
+    }
+
+    /// Same definition with its names normalized by the name policy
+    #[cfg(feature = "naming")]
+    pub(crate) fn normalize(&self, def: BeanDef) -> BeanDef {
+        def.normalized(self.name_policy())
+    }
+
+    /// Names are matched exactly without the `naming` feature
+    #[cfg(not(feature = "naming"))]
+    pub(crate) fn normalize(&self, def: BeanDef) -> BeanDef {
+        def
+    }
+
+    /// Get a bean, failing with `RateLimited` instead of waiting for a creation permit, for
+    /// async callers which wait without blocking their thread
+    #[cfg(feature = "async")]
+    pub(crate) fn try_get_without_waiting<T: Any + Send + Sync + 'static>(
+        &self,
+        name: Option<&str>,
+        requester: Option<&Identifier>,
+    ) -> Result<Arc<T>, BeanError> {
+        #[cfg_attr(not(feature = "resilience"), allow(unused_mut))]
+        let mut context = self.requested_context(requester);
+        #[cfg(feature = "resilience")]
+        {
+            context.no_wait = true;
+        }
+        self.get_with_context::<T>(name, &mut context)
+    }
+
+    /// Fail if a lookup of the bean on behalf of `requester` would be denied, e.g. before
+    /// creating it elsewhere
+    #[cfg(feature = "async")]
+    pub(crate) fn check_lookup(
+        &self,
+        id: &Identifier,
+        requester: Option<&Identifier>,
+    ) -> Result<(), BeanError> {
+        self.check_access(id, &self.requested_context(requester), None)
+    }
+
+    /// Fail if the bean is internal to a module the requester doesn't belong to, `module`
+    /// granting access to the internal beans of its own
+    #[cfg(feature = "modules")]
+    fn check_access(
+        &self,
+        id: &Identifier,
+        context: &CreationContext,
+        module: Option<&str>,
+    ) -> Result<(), BeanError> {
+        if cfg!(debug_assertions) {
+            self.check_consumer(id);
+        }
+
+        let beans = self.beans.read().unwrap();
+        let Some(definition) = beans.get(id) else {
+            return Ok(());
+        };
+        let requester = context.creating.last().or(context.requester.as_ref());
+        let requester_definition = requester.and_then(|parent| beans.get(parent));
+
+        if definition.internal {
+            let own = definition.module.as_deref();
+            let requester_module = requester_definition.and_then(|p| p.module.as_deref());
+            if own.is_none() || (requester_module != own && module != own) {
+                return Err(BeanError::Internal {
+                    bean: BeanId::new(id),
+                    module: own.map(str::to_string),
+                });
+            }
+        }
+
+        if !definition.guards.is_empty() {
+            let guards = definition.guards.clone();
+            let guard_context = GuardContext {
+                bean: BeanId::new(id),
+                requester: requester.map(BeanId::new),
+                requester_tags: requester_definition
+                    .map(|p| p.tags.clone())
+                    .unwrap_or_default(),
+                path: context.get_path(),
+                #[cfg(feature = "scopes")]
+                active_scopes: context.scopes.iter().map(|s| s.name()).collect(),
+                #[cfg(not(feature = "scopes"))]
+                active_scopes: Vec::new(),
+            };
+            // Guards may use the container
+            drop(beans);
+            for guard in guards {
+                guard(&guard_context).map_err(|reason| BeanError::Denied {
+                    bean: BeanId::new(id),
+                    reason,
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// No bean is internal without the `modules` feature
+    #[cfg(not(feature = "modules"))]
+    fn check_access(
+        &self,
+        _id: &Identifier,
+        _context: &CreationContext,
+        _module: Option<&str>,
+    ) -> Result<(), BeanError> {
+        Ok(())
+    }
+
+    /// Report a resolution of a `single_consumer` bean from another thread than its first one
+    #[cfg(feature = "modules")]
+    fn check_consumer(&self, id: &Identifier) {
+        let check = self
+            .beans
+            .read()
+            .unwrap()
+            .get(id)
+            .and_then(|d| d.access.clone());
+        if let Some(violation) = check.and_then(|check| check.resolved()) {
+            self.notify(|o| o.on_access_violation(BeanId::new(id), &violation));
+        }
+    }
+
+    /// Determine the identifier a lookup resolves to
+    pub(crate) fn find_id<T: Any + Send + Sync + 'static>(
+        &self,
+        name: Option<&str>,
+    ) -> Option<Identifier> {
+        if let Some(n) = name {
+            return Some(self.named_id(n));
+        }
+        // Prefer TypeSpec, then Unnamed
+        let type_spec_id = Identifier::type_spec::<T>();
+        let unnamed_id = Identifier::unnamed::<T>();
+
+        let beans = self.beans.read().unwrap();
+        if beans.contains_key(&type_spec_id) {
+            Some(type_spec_id)
+        } else if beans.contains_key(&unnamed_id) {
+            Some(unnamed_id)
+        } else {
+            None
+        }
+    }
+
+    fn get_with_context<T: Any + Send + Sync + 'static>(
+        &self,
+        name: Option<&str>,
+        context: &mut CreationContext,
+    ) -> Result<Arc<T>, BeanError> {
+        self.get_with_access::<T>(name, context, None)
+    }
+
+    /// Every bean of type `T` registered by name, or also by type unless `named_only`, in
+    /// registration order, leaving out the internal beans of other modules
+    fn get_all_with_context<T: Any + Send + Sync + 'static>(
+        &self,
+        context: &mut CreationContext,
+        named_only: bool,
+    ) -> Result<Vec<(Identifier, Arc<T>)>, BeanError> {
+        let ids: Vec<Identifier> = {
+            let ids = self.ids_in_order();
+            let beans = self.beans.read().unwrap();
+            ids.into_iter()
+                .filter(|id| match id {
+                    Identifier::Named(_) => true,
+                    Identifier::TypeSpec(..) => !named_only,
+                    _ => false,
+                })
+                .filter(|id| {
+                    beans
+                        .get(id)
+                        .is_some_and(|definition| definition.type_id == TypeId::of::<T>())
+                })
+                .collect()
+        };
+        let mut instances = Vec::with_capacity(ids.len());
+        for id in ids {
+            match self.check_access(&id, context, None) {
+                Err(BeanError::Internal { .. }) => continue,
+                result => result?,
+            }
+            let instance = self
+                .resolve(&id, context)?
+                .downcast::<T>()
+                .map_err(|_| BeanError::DowncastFailed(type_name::<T>()))?;
+            instances.push((id, instance));
+        }
+        Ok(instances)
+    }
+
+    /// Get a bean, `module` grants access to its internal beans
+    fn get_with_access<T: Any + Send + Sync + 'static>(
+        &self,
+        name: Option<&str>,
+        context: &mut CreationContext,
+        module: Option<&str>,
+    ) -> Result<Arc<T>, BeanError> {
+        if let Some(parent) = &self.parent
+            && !self
+                .find_id::<T>(name)
+                .is_some_and(|id| self.beans.read().unwrap().contains_key(&id))
+        {
+            // The requester keeps access to the internal beans of its module. Its path isn't
+            // carried: beans of the parent never depend on the child's, and may share their ids
+            #[cfg(feature = "modules")]
+            let module = module.map(str::to_string).or_else(|| {
+                let requester = context.creating.last().or(context.requester.as_ref());
+                let beans = self.beans.read().unwrap();
+                beans.get(requester?)?.module.clone()
+            });
+            #[cfg(not(feature = "modules"))]
+            let module = module.map(str::to_string);
+            let mut parent_context = parent.new_context();
+            return parent.get_with_access::<T>(name, &mut parent_context, module.as_deref());
+        }
+        if name.is_none()
+            && let Some((_, provide)) = BUILT_IN_BEANS
+                .iter()
+                .find(|(type_id, _)| type_id() == TypeId::of::<T>())
+            && self.find_id::<T>(None).is_none()
+        {
+            // Fails if another thread registered one meanwhile, which is resolved instead
+            let _ = provide(self);
+        }
+        let Some(id) = self.find_id::<T>(name) else {
+            #[cfg(feature = "wiring")]
+            {
+                let converter = self
+                    .converters
+                    .read()
+                    .unwrap()
+                    .get(&TypeId::of::<T>())
+                    .cloned();
+                if let Some(converter) = converter.filter(|_| name.is_none()) {
+                    // On the path like a bean, so chains of converters leading back to `T` fail
+                    context.enter(Identifier::type_spec::<T>())?;
+                    let converted = converter.create(&mut Dependencies {
+                        container: self,
+                        context,
+                    });
+                    context.exit();
+                    return converted?
+                        .downcast::<T>()
+                        .map_err(|_| BeanError::DowncastFailed(type_name::<T>()));
+                }
+            }
+            #[cfg(feature = "testing")]
+            if name.is_none()
+                && let Some(stub) = self.stubs.get(TypeId::of::<T>())
+            {
+                // On the path like a bean, so stubs depending on each other fail
+                context.enter(Identifier::type_spec::<T>())?;
+                let stubbed = stub.resolve(&mut Dependencies {
+                    container: self,
+                    context,
+                });
+                context.exit();
+                return stubbed?
+                    .downcast::<T>()
+                    .map_err(|_| BeanError::DowncastFailed(type_name::<T>()));
+            }
+            return Err(BeanError::NotFound(BeanId::new(
+                &Identifier::type_spec::<T>(),
+            )));
+        };
+
+        self.check_access(&id, context, module)?;
+        self.resolve(&id, context)?
+            .downcast::<T>()
+            .map_err(|_| BeanError::DowncastFailed(type_name::<T>()))
+    }
+
+    /// Resolve the bean with given identifier, creating it if needed
+    fn resolve(
+        &self,
+        id: &Identifier,
+        context: &mut CreationContext,
+    ) -> Result<Arc<dyn Any + Send + Sync>, BeanError> {
+        let id = &self.instance_id(id);
+        let result = self.resolve_traced(id, context);
+        #[cfg(feature = "wiring")]
+        if context.creating.is_empty()
+            && !context.wiring
+            && (!context.setters.is_empty() || !context.unwired.is_empty())
+        {
+            return self.wire(id, context, result);
+        }
+        result
+    }
+
+    /// Run the setters of the instances created resolving `id`, then cache them, or drop them
+    /// if a setter failed
+    #[cfg(feature = "wiring")]
+    fn wire(
+        &self,
+        id: &Identifier,
+        context: &mut CreationContext,
+        result: Result<Arc<dyn Any + Send + Sync>, BeanError>,
+    ) -> Result<Arc<dyn Any + Send + Sync>, BeanError> {
+        // Instances are visible once wired, and dropped if a setter fails
+        let wired = self.run_setters(context);
+        let unwired = std::mem::take(&mut context.unwired);
+        let published = match &wired {
+            Ok(()) => self.publish(unwired),
+            Err(_) => {
+                self.evict(unwired);
+                Vec::new()
+            }
+        };
+        let instance = result?;
+        wired?;
+        Ok(published
+            .into_iter()
+            .find(|(published, _)| published == id)
+            .map_or(instance, |(_, instance)| instance))
+    }
+
+    /// Run the setters of the instances created, including those of the instances the
+    /// setters create, see [`register_with_setters`](Self::register_with_setters)
+    #[cfg(feature = "wiring")]
+    fn run_setters(&self, context: &mut CreationContext) -> Result<(), BeanError> {
+        context.wiring = true;
+        let mut wired = Ok(());
+        'wiring: while !context.setters.is_empty() {
+            for (instance, setter) in std::mem::take(&mut context.setters) {
+                wired = setter(
+                    &instance,
+                    &mut Dependencies {
+                        container: self,
+                        context,
+                    },
+                );
+                if wired.is_err() {
+                    context.setters.clear();
+                    break 'wiring;
+                }
+            }
+        }
+        context.wiring = false;
+        wired
+    }
+
+    /// Cache the instances created on a path once their setters ran, returning the instance
+    /// kept for each, another thread may have cached its own first
+    #[cfg(feature = "wiring")]
+    fn publish(&self, unwired: Vec<Unwired>) -> Vec<(Identifier, Arc<dyn Any + Send + Sync>)> {
+        let mut beans = self.beans.write().unwrap();
+        unwired
+            .into_iter()
+            .filter_map(|unwired| {
+                let definition = beans.get_mut(&unwired.id)?;
+                let id = unwired.id.clone();
+                Some((id, self.store_instance(definition, unwired)))
+            })
+            .collect()
+    }
+
+    /// Drop the instances created on a path whose setters failed
+    #[cfg(feature = "wiring")]
+    fn evict(&self, unwired: Vec<Unwired>) {
+        let mut beans = self.beans.write().unwrap();
+        for unwired in unwired {
+            if let Some(definition) = beans.get_mut(&unwired.id) {
+                #[cfg(feature = "scopes")]
+                definition.release_pool_slot();
+                if definition.state == BeanState::Creating {
+                    definition.state = BeanState::Failed;
+                }
+            }
+        }
+    }
+
+    /// Cache a created instance as its scope requires, returning the instance to hand out
+    fn store_instance(
+        &self,
+        definition: &mut BeanDefinition,
+        unwired: Unwired,
+    ) -> Arc<dyn Any + Send + Sync> {
+        definition.state = BeanState::Ready;
+        match definition.scope {
+            // Another thread may have won the race, keep the first instance
+            Scope::Singleton => {
+                if definition.instance.is_none() {
+                    definition.creation_path = Some(unwired.path);
+                    #[cfg(feature = "lifecycle")]
+                    {
+                        definition.created_seq =
+                            Some(self.next_creation.fetch_add(1, Ordering::Relaxed));
+                    }
+                }
+                definition.instance.get_or_insert(unwired.instance).clone()
+            }
+            #[cfg(feature = "scopes")]
+            Scope::Cached(ttl) => {
+                definition.creation_path = Some(unwired.path);
+                #[cfg(feature = "lifecycle")]
+                {
+                    definition.created_seq =
+                        Some(self.next_creation.fetch_add(1, Ordering::Relaxed));
+                }
+                definition.expires_at = unwired.created_at.map(|at| at + ttl);
+                definition.instance = Some(unwired.instance.clone());
+                unwired.instance
+            }
+            #[cfg(feature = "scopes")]
+            Scope::Pooled(_) => {
+                definition.creation_path = Some(unwired.path);
+                definition.pool_creating = definition.pool_creating.saturating_sub(1);
+                definition.pooled.push(unwired.instance.clone());
+                unwired.instance
+            }
+            #[cfg(feature = "scopes")]
+            Scope::Scoped(_) => match &unwired.scope {
+                Some(active) => active.store(&unwired.id, unwired.instance, unwired.path),
+                None => unwired.instance,
+            },
+            Scope::Prototype => unwired.instance,
+        }
+    }
+
+    /// Same as [`resolve`](Self::resolve) before the setters run
+    fn resolve_traced(
+        &self,
+        id: &Identifier,
+        context: &mut CreationContext,
+    ) -> Result<Arc<dyn Any + Send + Sync>, BeanError> {
+        #[cfg(feature = "introspection")]
+        if let Some(trace) = self.trace.get() {
+            let started = Instant::now();
+            let mut created = false;
+            let result = self.resolve_untraced(id, context, &mut created);
+            let outcome = match &result {
+                Err(_) => TraceOutcome::Failed,
+                Ok(_) if created => TraceOutcome::Created,
+                Ok(_) => TraceOutcome::Reused,
+            };
+            trace.record(BeanId::new(id), started.elapsed(), outcome);
+            return result;
+        }
+        self.resolve_untraced(id, context, &mut false)
+    }
+
+    /// Same as [`resolve`](Self::resolve), setting `created` if the factory ran
+    fn resolve_untraced(
+        &self,
+        id: &Identifier,
+        context: &mut CreationContext,
+        created: &mut bool,
+    ) -> Result<Arc<dyn Any + Send + Sync>, BeanError> {
+        if let Some(parent) = context.creating.last() {
+            self.record_dependency(parent, id);
+        }
+
+        #[cfg(feature = "testing")]
+        if let Some(error) = self.injected_failures.take(id) {
+            return Err(error);
+        }
+        #[cfg(feature = "testing")]
+        if let Some(inst) = self.overrides.get(id) {
+            return Ok(inst);
+        }
+
+        let fresh = context.creating.is_empty() && context.fresh.take_if(|f| f == id).is_some();
+
+        // Created on this path, its setters didn't run yet
+        #[cfg(feature = "wiring")]
+        if !fresh && let Some(inst) = context.unwired_instance(id) {
+            return Ok(inst);
+        }
+
+        // Already created in its singleton or active scope, nothing to resolve
+        if !fresh && let Some(inst) = self.existing_instance(id, context)? {
+            return Ok(inst);
+        }
+
+        // Singleton being prefetched in background, wait for it
+        #[cfg(feature = "prefetch")]
+        if !fresh
+            && let Some(inst) = context
+                .await_prefetch(id)
+                .and_then(|_| self.cached_instance(id))
+        {
+            return Ok(inst);
+        }
+
+        // Check for circular dependencies
+        context.enter(id.clone())?;
+
+        let result = (|| -> Result<Arc<dyn Any + Send + Sync>, BeanError> {
+            let now = self.clock_time(id);
+            // Hooks of the definition, read along with its factory
+            #[cfg(feature = "lifecycle")]
+            let init;
+            #[cfg(feature = "wiring")]
+            let setter;
+            #[cfg(feature = "prefetch")]
+            let prefetch;
+            #[cfg(feature = "resilience")]
+            let permits;
             let (factory, scope) = {
-                let beans = self.beans.read().unwrap();
+                let mut beans = self.beans.write().unwrap();
                 let definition = beans
-                    .get(&id)
-                    .ok_or_else(|| format!("Bean not found: {}", id))?;
+                    .get_mut(id)
+                    .ok_or_else(|| BeanError::NotFound(BeanId::new(id)))?;
+
+                if !fresh && let Some(inst) = definition.reusable_instance(now) {
+                    return Ok(inst.clone());
+                }
+
+                // Checked out under the write lock, no other resolution can take it meanwhile
+                #[cfg(feature = "scopes")]
+                if !fresh && let Some(inst) = definition.idle_instance() {
+                    return Ok(inst.clone());
+                }
+
+                #[cfg(any(feature = "lifecycle", feature = "resilience"))]
+                if let Some(err) = definition.cached_failure(now) {
+                    return Err(err.clone());
+                }
 
-                if definition.scope == Scope::Singleton
-                    && let Some(inst) = &definition.instance
+                #[cfg(feature = "resilience")]
+                if let Some(limit) = &definition.rate_limit
+                    && let Err(retry_after) = limit.try_acquire(now.unwrap_or_else(Instant::now))
                 {
-                    return inst
-                        .clone()
-                        .downcast::<T>()
-                        .map_err(|_| "Type downcast failed".to_string());
+                    return Err(BeanError::RateLimited {
+                        bean: BeanId::new(id),
+                        retry_after,
+                    });
+                }
+
+                #[cfg(feature = "scopes")]
+                if let Scope::Pooled(size) = definition.scope
+                    && !fresh
+                {
+                    if definition.pooled.len() + definition.pool_creating >= size {
+                        return Err(BeanError::PoolExhausted {
+                            bean: BeanId::new(id),
+                            size,
+                        });
+                    }
+                    definition.pool_creating += 1;
                 }
 
+                if !fresh {
+                    definition.state = BeanState::Creating;
+                }
+                *created = true;
+                #[cfg(feature = "lifecycle")]
+                {
+                    init = definition.init.clone();
+                }
+                #[cfg(feature = "wiring")]
+                {
+                    setter = definition.setter.clone();
+                }
+                #[cfg(feature = "prefetch")]
+                {
+                    prefetch = definition.prefetch.clone();
+                }
+                #[cfg(feature = "resilience")]
+                {
+                    permits = definition.permits.clone();
+                }
                 (definition.factory.clone(), definition.scope)
             };
 
-            let mut deps = Dependencies {
+            #[cfg(feature = "prefetch")]
+            let prefetch = {
+                let deterministic = self.deterministic_seed().is_some();
+                prefetch
+                    .iter()
+                    .filter(|_| !deterministic)
+                    .filter_map(|find| find(self))
+                    .filter(|pid| {
+                        !context.creating.contains(pid) && self.cached_instance(pid).is_none()
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            #[cfg(feature = "resilience")]
+            let permit = match &permits {
+                Some(permits) => Some(permits.acquire(!context.no_wait).ok_or_else(|| {
+                    #[cfg(feature = "scopes")]
+                    if !fresh && let Some(definition) = self.beans.write().unwrap().get_mut(id) {
+                        definition.release_pool_slot();
+                    }
+                    BeanError::RateLimited {
+                        bean: BeanId::new(id),
+                        retry_after: Duration::ZERO,
+                    }
+                })?),
+                None => None,
+            };
+            #[cfg(feature = "observers")]
+            let started = Instant::now();
+            #[cfg(feature = "wiring")]
+            let self_refs = context.self_refs.len();
+            let running = Running::enter(self.id, id);
+            #[cfg(feature = "prefetch")]
+            let created = self.create_prefetching(factory.as_ref(), context, prefetch);
+            #[cfg(not(feature = "prefetch"))]
+            let created = factory.create(&mut Dependencies {
                 container: self,
                 context,
-            };
-            let new_instance = factory.create(&mut deps)?;
+            });
+            drop(running);
+            #[cfg(feature = "wiring")]
+            context.fill_self_refs(self_refs, &created);
+            #[cfg(feature = "lifecycle")]
+            let created = created.and_then(|instance| match &init {
+                Some(init) => init(&instance).map(|_| instance),
+                None => Ok(instance),
+            });
+            #[cfg(feature = "resilience")]
+            drop(permit);
 
-            if scope == Scope::Singleton {
-                let mut beans = self.beans.write().unwrap();
-                if let Some(definition) = beans.get_mut(&id)
-                    && definition.instance.is_none()
-                {
-                    definition.instance = Some(new_instance.clone());
+            #[cfg(feature = "observers")]
+            match &created {
+                Ok(_) => {
+                    let elapsed = started.elapsed();
+                    self.notify(|o| o.on_created(BeanId::new(id), scope, elapsed));
+                }
+                // Dependents fail with the same error, only report where it happened
+                Err(e) if context.reported_failure.as_ref() != Some(e) => {
+                    let path = context.get_path();
+                    self.notify(|o| o.on_failed(BeanId::new(id), &path, &e.to_string()));
+                    context.reported_failure = Some(e.clone());
                 }
+                Err(_) => {}
             }
 
-            new_instance
-                .downcast::<T>()
-                .map_err(|_| "Type downcast failed".to_string())
+            #[cfg_attr(
+                not(any(feature = "scopes", feature = "resilience")),
+                allow(unused_variables)
+            )]
+            let now = self.clock_time(id);
+            let mut beans = self.beans.write().unwrap();
+            let definition = beans.get_mut(id);
+            let new_instance = match created {
+                Ok(instance) => instance,
+                Err(e) => {
+                    #[cfg_attr(not(feature = "resilience"), allow(clippy::collapsible_if))]
+                    if let Some(definition) = definition {
+                        if !fresh {
+                            definition.state = BeanState::Failed;
+                            #[cfg(feature = "scopes")]
+                            definition.release_pool_slot();
+                        }
+                        #[cfg(feature = "resilience")]
+                        if let Some(now) = now.filter(|_| definition.failure_backoff.is_some()) {
+                            definition.last_failure = Some((now, e.clone()));
+                        }
+                    }
+                    return Err(e);
+                }
+            };
+            let Some(definition) = definition.filter(|_| !fresh) else {
+                #[cfg(feature = "wiring")]
+                if let Some(setter) = setter {
+                    context.setters.push((new_instance.clone(), setter));
+                }
+                return Ok(new_instance);
+            };
+            #[cfg(feature = "resilience")]
+            {
+                definition.last_failure = None;
+            }
+            if let Scope::Prototype = scope {
+                definition.state = BeanState::Ready;
+                #[cfg(feature = "scopes")]
+                if let Some(innermost) = context.scopes.last() {
+                    innermost.track(id, &new_instance, context.get_path());
+                }
+                #[cfg(feature = "wiring")]
+                if let Some(setter) = setter {
+                    context.setters.push((new_instance.clone(), setter));
+                }
+                return Ok(new_instance);
+            }
+            // Another thread won the race, its instance is wired already
+            if let (Scope::Singleton, Some(existing)) = (scope, &definition.instance) {
+                definition.state = BeanState::Ready;
+                return Ok(existing.clone());
+            }
+            let unwired = Unwired {
+                #[cfg(any(feature = "wiring", feature = "scopes"))]
+                id: id.clone(),
+                instance: new_instance.clone(),
+                path: context.get_path(),
+                #[cfg(feature = "scopes")]
+                scope: match scope {
+                    Scope::Scoped(name) => context.active_scope(name).cloned(),
+                    _ => None,
+                },
+                #[cfg(feature = "scopes")]
+                created_at: now,
+            };
+            // Cached once wired, along with what was created after it on the path
+            #[cfg(feature = "wiring")]
+            if setter.is_some() || !context.unwired.is_empty() {
+                if let Some(setter) = setter {
+                    context.setters.push((new_instance.clone(), setter));
+                }
+                context.unwired.push(unwired);
+                return Ok(new_instance);
+            }
+            Ok(self.store_instance(definition, unwired))
         })();
 
         context.exit();
         result
     }
 
+    /// Run the factory, creating the beans it hints at concurrently
+    #[cfg(feature = "prefetch")]
+    fn create_prefetching(
+        &self,
+        factory: &dyn BeanFactory,
+        context: &mut CreationContext,
+        prefetch: Vec<Identifier>,
+    ) -> Result<Arc<dyn Any + Send + Sync>, BeanError> {
+        if prefetch.is_empty() {
+            return factory.create(&mut Dependencies {
+                container: self,
+                context,
+            });
+        }
+        // Create hinted beans concurrently while the factory runs
+        thread::scope(|s| {
+            let pending = context.prefetching.len();
+            let mut forks = Vec::new();
+            for pid in prefetch {
+                let signal = Arc::new(PrefetchSignal::default());
+                let mut forked = context.fork();
+                context.prefetching.push((pid.clone(), signal.clone()));
+                forks.push(s.spawn(move || {
+                    let _done = PrefetchDone(signal);
+                    // Errors are reported by the regular resolution later
+                    let _ = self.resolve(&pid, &mut forked);
+                    forked
+                }));
+            }
+            let created = factory.create(&mut Dependencies {
+                container: self,
+                context: &mut *context,
+            });
+            context.prefetching.truncate(pending);
+            // Setters of the prefetched beans run with this path's
+            for fork in forks {
+                #[cfg(feature = "wiring")]
+                if let Ok(forked) = fork.join() {
+                    context.setters.extend(forked.setters);
+                    context.unwired.extend(forked.unwired);
+                }
+                #[cfg(not(feature = "wiring"))]
+                let _ = fork.join();
+            }
+            created
+        })
+    }
+
+    /// Remember that `parent` depends on `id`
+    pub(crate) fn record_dependency(&self, parent: &Identifier, id: &Identifier) {
+        let known = {
+            let beans = self.beans.read().unwrap();
+            beans
+                .get(parent)
+                .is_none_or(|definition| definition.dependencies.contains(id))
+        };
+        if !known {
+            let mut beans = self.beans.write().unwrap();
+            if let Some(definition) = beans.get_mut(parent)
+                && !definition.dependencies.contains(id)
+            {
+                definition.dependencies.push(id.clone());
+            }
+        }
+    }
+
+    /// Drop the cached singleton of `T` and of the beans depending on it, transitively
+    ///
+    /// Next resolutions create new instances, and subscribers are notified (see [`subscribe`](Self::subscribe)).
+    /// Dependents are known once observed, i.e. after they have been created.
+    pub fn refresh<T: Any + Send + Sync + 'static>(&self) -> Result<(), BeanError> {
+        let id = self
+            .find_id::<T>(None)
+            .ok_or_else(|| BeanError::NotFound(BeanId::new(&Identifier::type_spec::<T>())))?;
+        self.invalidate(id);
+        Ok(())
+    }
+
+    /// Whether `a` and `b` are the same instance of `T`
+    pub fn same_instance<T: ?Sized>(&self, a: &Arc<T>, b: &Arc<T>) -> bool {
+        Arc::ptr_eq(a, b)
+    }
+
+    /// Test helper asserting that each bean of type `T`, resolved by type or by name, yields the
+    /// same instance on every resolution, including a named bean and its by-type alias
+    ///
+    /// Distinct named beans of type `T` are distinct instances, each is checked on its own.
+    ///
+    /// **NOTE**: panics with the creation paths of two distinct instances, or if resolution fails
+    #[track_caller]
+    pub fn assert_singleton<T: Any + Send + Sync + 'static>(&self) {
+        let by_type = self.find_id::<T>(None);
+        let mut ids: Vec<Identifier> = by_type.clone().into_iter().collect();
+        ids.extend(self.ids_in_order().into_iter().filter(|id| {
+            matches!(id, Identifier::Named(_))
+                && self.beans.read().unwrap().get(id).map(|d| d.type_name) == Some(type_name::<T>())
+        }));
+        if ids.is_empty() {
+            panic!("Bean not found: {}", Identifier::type_spec::<T>());
+        }
+        let path = |id: &Identifier| {
+            self.beans
+                .read()
+                .unwrap()
+                .get(id)
+                .and_then(|d| d.creation_path.clone())
+                .unwrap_or_else(|| id.to_string())
+        };
+
+        let mut resolved: Vec<(Identifier, Arc<dyn Any + Send + Sync>)> = Vec::new();
+        for id in ids {
+            let instance = self
+                .resolve(&id, &mut self.new_context())
+                .unwrap_or_else(|e| panic!("Failed to resolve {}: {}", id, e));
+            // Resolve twice, prototypes and inactive scopes yield distinct instances
+            let again = self.resolve(&id, &mut self.new_context()).unwrap();
+            if !Arc::ptr_eq(&instance, &again) {
+                panic!(
+                    "{} is not a singleton, two resolutions yield distinct instances of {}, \
+                     the cached one created via `{}`",
+                    id,
+                    type_name::<T>(),
+                    path(&self.instance_id(&id))
+                );
+            }
+            resolved.push((id, instance));
+        }
+
+        // The by-type lookup of a named bean yields the named instance
+        let Some(alias) = by_type else {
+            return;
+        };
+        let named = self.instance_id(&alias);
+        let instance = |id: &Identifier| resolved.iter().find(|(r, _)| r == id).map(|(_, i)| i);
+        if let (Some(by_type), Some(by_name)) = (instance(&alias), instance(&named))
+            && !Arc::ptr_eq(by_type, by_name)
+        {
+            panic!(
+                "{} and {} are distinct instances of {}, created via `{}` and `{}`",
+                alias,
+                named,
+                type_name::<T>(),
+                path(&alias),
+                path(&named)
+            );
+        }
+    }
+
+    /// Drop the cached singletons of the bean and its dependents, see [`refresh`](Self::refresh)
+    pub(crate) fn invalidate(&self, id: Identifier) {
+        self.invalidate_from(id, false);
+    }
+
+    /// Identifiers of the definition registered under `id`, see [`definition_ids`]
+    #[cfg(feature = "testing")]
+    pub(crate) fn definition_ids(&self, id: &Identifier) -> Vec<Identifier> {
+        definition_ids(&self.beans.read().unwrap(), id)
+    }
+
+    /// Drop the cached singletons of the bean's dependents, keeping the bean's own
+    #[cfg(feature = "testing")]
+    pub(crate) fn invalidate_dependents(&self, id: Identifier) {
+        self.invalidate_from(id, true);
+    }
+
+    fn invalidate_from(&self, id: Identifier, keep_own: bool) {
+        let mut invalidated = vec![id.clone()];
+        {
+            let mut beans = self.beans.write().unwrap();
+            // A named bean and its by-type alias share the instance, and their dependents
+            let aliases = definition_ids(&beans, &id);
+            invalidated.extend(aliases.into_iter().filter(|alias| *alias != id));
+            let own = invalidated.len();
+            let mut next = 0;
+            while next < invalidated.len() {
+                let dependents: Vec<Identifier> = beans
+                    .iter()
+                    .filter(|(_, definition)| definition.dependencies.contains(&invalidated[next]))
+                    .map(|(dependent, _)| dependent.clone())
+                    .filter(|dependent| !invalidated.contains(dependent))
+                    .collect();
+                invalidated.extend(dependents);
+                next += 1;
+            }
+            for id in invalidated.iter().skip(if keep_own { own } else { 0 }) {
+                if let Some(definition) = beans.get_mut(id) {
+                    // Checked out instances of a pool stay with their holders
+                    #[cfg(feature = "scopes")]
+                    let drained = !std::mem::take(&mut definition.pooled).is_empty();
+                    #[cfg(not(feature = "scopes"))]
+                    let drained = false;
+                    if definition.instance.take().is_some() || drained {
+                        definition.state = BeanState::Disposed;
+                        definition.creation_path = None;
+                    }
+                }
+                #[cfg(feature = "lifecycle")]
+                for id in definition_ids(&beans, id) {
+                    if let Some(definition) = beans.get_mut(&id) {
+                        definition.unavailable = None;
+                    }
+                }
+            }
+        }
+        #[cfg(feature = "reload")]
+        self.watches.bump(&invalidated);
+        #[cfg_attr(
+            not(any(feature = "observers", feature = "introspection")),
+            allow(unused_variables)
+        )]
+        let invalidated: Vec<BeanId> = invalidated.iter().map(BeanId::new).collect();
+        #[cfg(feature = "observers")]
+        self.notify(|o| o.on_refreshed(invalidated[0], &invalidated));
+        #[cfg(feature = "introspection")]
+        self.record(RecordPoint::Refreshes, || {
+            HistoryEvent::Refreshed(invalidated[0])
+        });
+    }
+
+    /// Extract `T` and its transitive dependencies into a new, minimal container
+    ///
+    /// Definitions are copied without their instances, so the slice creates its own.
+    /// Declared dependencies are known up front, the others once observed, i.e. after the bean
+    /// has been created at least once.
+    pub fn slice<T: Any + Send + Sync + 'static>(&self) -> Result<BeanContainer, BeanError> {
+        let root = self
+            .find_id::<T>(None)
+            .ok_or_else(|| BeanError::NotFound(BeanId::new(&Identifier::type_spec::<T>())))?;
+
+        let slice = BeanContainer::new();
+        slice.set_max_depth(self.max_depth.load(Ordering::Relaxed));
+        #[cfg(feature = "naming")]
+        slice.set_name_policy(self.name_policy());
+        {
+            let beans = self.beans.read().unwrap();
+            let mut sliced = slice.beans.write().unwrap();
+            let mut pending = vec![root];
+            while let Some(id) = pending.pop() {
+                if sliced.contains_key(&id) {
+                    continue;
+                }
+                if let Some(definition) = beans.get(&id) {
+                    pending.extend(definition.dependency_keys(&beans));
+                    sliced.insert(id, definition.detached());
+                }
+            }
+        }
+        slice
+            .next_order
+            .store(self.next_order.load(Ordering::Relaxed), Ordering::Relaxed);
+        Ok(slice)
+    }
+
+    /// Walk what resolving `T` would do, without running any factory
+    ///
+    /// Dependencies of a bean to create are known once observed, i.e. after the bean
+    /// has been created at least once.
+    #[cfg(feature = "introspection")]
+    pub fn explain<T: Any + Send + Sync + 'static>(&self) -> ResolutionPlanReport {
+        let id = self
+            .find_id::<T>(None)
+            .unwrap_or_else(Identifier::type_spec::<T>);
+        self.explain_id(id)
+    }
+
+    /// Walk what resolving a named bean would do, see [`explain`](Self::explain)
+    #[cfg(feature = "introspection")]
+    pub fn explain_named(&self, name: &str) -> ResolutionPlanReport {
+        self.explain_id(self.named_id(name))
+    }
+
+    #[cfg(feature = "introspection")]
+    fn explain_id(&self, root: Identifier) -> ResolutionPlanReport {
+        #[cfg(feature = "scopes")]
+        let context = self.new_context();
+        // Dry run, the clock isn't created
+        #[cfg(any(feature = "scopes", feature = "lifecycle", feature = "resilience"))]
+        let now = Some(self.ready_clock_now());
+        let beans = self.beans.read().unwrap();
+        let mut steps = Vec::new();
+        let mut path = Vec::new();
+        // Beans to visit with their depth, popped depth-first
+        let mut pending = vec![(root, 0)];
+        while let Some((id, depth)) = pending.pop() {
+            path.truncate(depth);
+            let rule = match &id {
+                Identifier::Named(_) => MatchRule::Name,
+                Identifier::TypeSpec(..) => MatchRule::Type,
+                Identifier::Unnamed(..) => MatchRule::UnnamedAlias,
+                Identifier::Anonymous(..) => MatchRule::Handle,
+            };
+            let Some(definition) = beans.get(&id) else {
+                steps.push(ResolutionStep {
+                    bean: BeanId::new(&id),
+                    depth,
+                    rule: None,
+                    scope: None,
+                    profile: None,
+                    decision: ResolutionDecision::NotFound,
+                    dependencies_known: true,
+                });
+                continue;
+            };
+            // The by-type alias of a named bean resolves to it
+            #[cfg_attr(not(feature = "scopes"), allow(unused_variables))]
+            let (held, definition) = match &definition.alias_of {
+                Some(named) => (named, beans.get(named).unwrap_or(definition)),
+                None => (&id, definition),
+            };
+
+            #[cfg(any(feature = "lifecycle", feature = "resilience"))]
+            let cached_failure = definition.cached_failure(now);
+            #[cfg(not(any(feature = "lifecycle", feature = "resilience")))]
+            let cached_failure: Option<&BeanError> = None;
+            let decision = if path.contains(&id) {
+                ResolutionDecision::Circular
+            } else if let Some(err) = cached_failure {
+                ResolutionDecision::CachedFailure(err.clone())
+            } else {
+                match definition.scope {
+                    Scope::Singleton if definition.instance.is_some() => {
+                        ResolutionDecision::ReuseSingleton
+                    }
+                    Scope::Singleton => ResolutionDecision::CreateSingleton,
+                    #[cfg(feature = "scopes")]
+                    Scope::Cached(_) if definition.reusable_instance(now).is_some() => {
+                        ResolutionDecision::ReuseCached
+                    }
+                    #[cfg(feature = "scopes")]
+                    Scope::Cached(_) => ResolutionDecision::CreateCached,
+                    #[cfg(feature = "scopes")]
+                    Scope::Pooled(_) if definition.idle_instance().is_some() => {
+                        ResolutionDecision::ReusePooled
+                    }
+                    #[cfg(feature = "scopes")]
+                    Scope::Pooled(size)
+                        if definition.pooled.len() + definition.pool_creating >= size =>
+                    {
+                        ResolutionDecision::PoolExhausted(size)
+                    }
+                    #[cfg(feature = "scopes")]
+                    Scope::Pooled(_) => ResolutionDecision::CreatePooled,
+                    Scope::Prototype => ResolutionDecision::CreatePrototype,
+                    #[cfg(feature = "scopes")]
+                    Scope::Scoped(name) => match context.active_scope(name) {
+                        None => ResolutionDecision::ScopeInactive(name),
+                        Some(active) if active.get(held).is_some() => {
+                            ResolutionDecision::ReuseScoped(name)
+                        }
+                        Some(_) => ResolutionDecision::CreateScoped(name),
+                    },
+                }
+            };
+            let creates = matches!(
+                decision,
+                ResolutionDecision::CreateSingleton
+                    | ResolutionDecision::CreateCached
+                    | ResolutionDecision::CreatePooled
+                    | ResolutionDecision::CreatePrototype
+                    | ResolutionDecision::CreateScoped(_)
+            );
+            let dependencies_known = !creates || definition.state != BeanState::Registered;
+            steps.push(ResolutionStep {
+                bean: BeanId::new(&id),
+                depth,
+                rule: Some(rule),
+                scope: Some(definition.scope),
+                profile: definition.profile.clone(),
+                decision,
+                dependencies_known,
+            });
+
+            if creates {
+                let dependencies = definition.dependencies.iter().rev();
+                pending.extend(dependencies.map(|d| (d.clone(), depth + 1)));
+                path.push(id);
+            }
+        }
+        ResolutionPlanReport { steps }
+    }
+
+    /// Instance already created for the bean's singleton or scope
+    ///
+    /// Fails if the bean is scoped but its scope is not active
+    fn existing_instance(
+        &self,
+        id: &Identifier,
+        #[cfg_attr(not(feature = "scopes"), allow(unused_variables))] context: &CreationContext,
+    ) -> Result<Option<Arc<dyn Any + Send + Sync>>, BeanError> {
+        let now = self.clock_time(id);
+        let beans = self.beans.read().unwrap();
+        let Some(definition) = beans.get(id) else {
+            return Ok(None);
+        };
+        match definition.scope {
+            Scope::Singleton => Ok(definition.reusable_instance(now).cloned()),
+            #[cfg(feature = "scopes")]
+            Scope::Cached(_) => Ok(definition.reusable_instance(now).cloned()),
+            // Checked out under the write lock
+            #[cfg(feature = "scopes")]
+            Scope::Pooled(_) => Ok(None),
+            Scope::Prototype => Ok(None),
+            #[cfg(feature = "scopes")]
+            Scope::Scoped(name) => context
+                .active_scope(name)
+                .map(|active| active.get(id))
+                .ok_or_else(|| BeanError::ScopeInactive {
+                    scope: name,
+                    bean: BeanId::new(id),
+                }),
+        }
+    }
+
+    /// Cached instance of a singleton, or of a `Cached` bean not expired, if already created
+    pub(crate) fn cached_instance(&self, id: &Identifier) -> Option<Arc<dyn Any + Send + Sync>> {
+        let id = &self.instance_id(id);
+        let now = self.clock_time(id);
+        let beans = self.beans.read().unwrap();
+        beans
+            .get(id)
+            .and_then(|definition| definition.reusable_instance(now).cloned())
+    }
+
+    /// Identifier of the definition holding the bean's instance, the named bean for its
+    /// by-type alias
+    pub(crate) fn instance_id(&self, id: &Identifier) -> Identifier {
+        let beans = self.beans.read().unwrap();
+        beans
+            .get(id)
+            .and_then(|definition| definition.alias_of.clone())
+            .unwrap_or_else(|| id.clone())
+    }
+
+    /// Time of the container's clock if the bean follows it, see
+    /// [`BeanDefinition::follows_clock`]
+    fn clock_time(&self, id: &Identifier) -> Option<Instant> {
+        let (follows, type_id) = {
+            let beans = self.beans.read().unwrap();
+            let definition = beans.get(id)?;
+            (definition.follows_clock(), definition.type_id)
+        };
+        follows.then(|| self.clock_now(type_id))
+    }
+
+    /// Time of the [`Clock`] bean, for a bean of type `type_id`
+    #[cfg(feature = "config")]
+    fn clock_now(&self, type_id: TypeId) -> Instant {
+        // The clock can't follow itself
+        if type_id == TypeId::of::<Clock>() {
+            return Instant::now();
+        }
+        self.clock().now()
+    }
+
+    /// System time, there is no clock bean without the `config` feature
+    #[cfg(not(feature = "config"))]
+    fn clock_now(&self, _type_id: TypeId) -> Instant {
+        Instant::now()
+    }
+
+    /// Time of the [`Clock`] bean if already created, read without resolving it
+    #[cfg(all(
+        feature = "introspection",
+        any(feature = "scopes", feature = "lifecycle", feature = "resilience")
+    ))]
+    fn ready_clock_now(&self) -> Instant {
+        #[cfg(feature = "config")]
+        if let Some(clock) = self.clock_if_ready() {
+            return clock.now();
+        }
+        Instant::now()
+    }
+
+    /// The [`Clock`] bean, the system clock if it can't be resolved
+    #[cfg(feature = "config")]
+    fn clock(&self) -> Clock {
+        self.clock_if_ready().unwrap_or_else(|| {
+            self.try_get::<Clock>(None)
+                .map_or_else(|_| Clock::system(), |clock| Clock::clone(&clock))
+        })
+    }
+
+    /// The [`Clock`] bean if already created, read without resolving it
+    #[cfg(feature = "config")]
+    fn clock_if_ready(&self) -> Option<Clock> {
+        let id = self.instance_id(&self.find_id::<Clock>(None)?);
+        let beans = self.beans.read().unwrap();
+        let instance = beans.get(&id)?.instance.clone()?;
+        instance.downcast_ref::<Clock>().cloned()
+    }
+
+    /// Unique id of the container, binding thread-local state to it
+    #[cfg(any(feature = "scopes", feature = "modules"))]
+    pub(crate) fn container_id(&self) -> u64 {
+        self.id
+    }
+
+    /// Get the lifecycle state of a bean, `None` if not registered
+    pub fn state_of<T: Any + Send + Sync + 'static>(&self) -> Option<BeanState> {
+        let id = self.instance_id(&self.find_id::<T>(None)?);
+        self.beans.read().unwrap().get(&id).map(|d| d.state)
+    }
+
+    /// Get the lifecycle state of a named bean, `None` if not registered
+    pub fn state_of_named(&self, name: &str) -> Option<BeanState> {
+        let beans = self.beans.read().unwrap();
+        beans.get(&self.named_id(name)).map(|d| d.state)
+    }
+
+    /// Get bean by type only if it is already created, never triggers creation
+    ///
+    /// Prototype beans are never cached, so this always returns `None` for them
+    pub fn get_if_ready<T: Any + Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        let id = self.find_id::<T>(None)?;
+        self.cached_instance(&id)?.downcast::<T>().ok()
+    }
+
+    /// Get named bean by type only if it is already created, never triggers creation
+    pub fn get_named_if_ready<T: Any + Send + Sync + 'static>(&self, name: &str) -> Option<Arc<T>> {
+        self.cached_instance(&self.named_id(name))?
+            .downcast::<T>()
+            .ok()
+    }
+
     /// Check if the container contains the specified bean
     pub fn contains<T: Any + Send + Sync + 'static>(&self, name: Option<&str>) -> bool {
         let beans = self.beans.read().unwrap();
 
         if let Some(n) = name {
-            beans.contains_key(&Identifier::Named(n.to_string()))
+            beans.contains_key(&self.named_id(n))
         } else {
             beans.contains_key(&Identifier::type_spec::<T>())
                 || beans.contains_key(&Identifier::unnamed::<T>())
         }
     }
 
+    /// Whether registering `def` fails because of a registered definition
+    #[cfg(feature = "reload")]
+    pub(crate) fn conflicts_with(&self, def: &BeanDef) -> bool {
+        let beans = self.beans.read().unwrap();
+        #[cfg_attr(not(feature = "modules"), allow(unused_variables))]
+        let Some(existing) = beans.get(&def.key()) else {
+            return false;
+        };
+        // A definition of another priority replaces or yields to the registered one
+        #[cfg(feature = "modules")]
+        if existing.priority != def.priority {
+            return false;
+        }
+        !def.is_replacing()
+    }
+
+    /// Set the profiles of the current environment
+    ///
+    /// Definitions bound to another profile with [`BeanDef::profile`] are skipped
+    /// when registered afterwards
+    pub fn set_active_profiles(&self, profiles: &[&str]) {
+        *self.active_profiles.write().unwrap() = profiles.iter().map(|p| p.to_string()).collect();
+    }
+
+    pub fn active_profiles(&self) -> Vec<String> {
+        self.active_profiles.read().unwrap().clone()
+    }
+
+    /// Make the container behave reproducibly, for tests
+    ///
+    /// Prefetch hints no longer create beans in background threads, and features making
+    /// random choices draw them from `seed`. Iteration over beans always follows
+    /// registration order, whether this mode is enabled or not.
+    pub fn enable_deterministic_mode(&self, seed: u64) {
+        *self.deterministic_seed.write().unwrap() = Some(seed);
+    }
+
+    /// Seed of the deterministic mode, `None` if not enabled
+    pub fn deterministic_seed(&self) -> Option<u64> {
+        *self.deterministic_seed.read().unwrap()
+    }
+
+    /// Identifiers of registered beans, in registration order
+    pub(crate) fn ids_in_order(&self) -> Vec<Identifier> {
+        ordered_ids(&self.beans.read().unwrap())
+    }
+
+    /// Introspection of registered beans, in registration order
+    #[cfg(feature = "introspection")]
+    pub fn beans(&self) -> Vec<BeanInfo> {
+        let ids = self.ids_in_order();
+        let beans = self.beans.read().unwrap();
+        ids.iter()
+            .filter_map(|id| {
+                let definition = beans.get(id)?;
+                let mut info = definition.info(id);
+                // The by-type alias of a named bean follows its state
+                if let Some(named) = definition.alias_of.as_ref().and_then(|n| beans.get(n)) {
+                    info.state = named.state;
+                }
+                Some(info)
+            })
+            .collect()
+    }
+
+    /// Definitions with their identifier, priority and declared dependencies, see
+    /// [`wiring_hash`](Self::wiring_hash)
+    #[cfg(feature = "introspection")]
+    pub(crate) fn wiring_definitions(&self) -> Vec<(Identifier, BeanInfo, i32, Vec<Identifier>)> {
+        let beans = self.beans.read().unwrap();
+        beans
+            .iter()
+            .map(|(id, definition)| {
+                #[cfg(feature = "wiring")]
+                let requires = definition
+                    .requires
+                    .iter()
+                    .map(|dependency| dependency.key(&beans))
+                    .collect();
+                #[cfg(not(feature = "wiring"))]
+                let requires = Vec::new();
+                #[cfg(feature = "modules")]
+                let priority = definition.priority;
+                #[cfg(not(feature = "modules"))]
+                let priority = 0;
+                (id.clone(), definition.info(id), priority, requires)
+            })
+            .collect()
+    }
+
+    /// Release every cached singleton, most recently created first, with its disposal hook
+    /// and shutdown phase
+    #[cfg(feature = "lifecycle")]
+    pub(crate) fn take_singletons(&self) -> Vec<DisposableInstance> {
+        let mut beans = self.beans.write().unwrap();
+        let mut taken: Vec<(u64, DisposableInstance)> = beans
+            .iter_mut()
+            .filter_map(|(id, definition)| {
+                let instance = definition.instance.take()?;
+                definition.state = BeanState::Disposed;
+                let seq = definition.created_seq.take().unwrap_or_default();
+                let disposable = DisposableInstance {
+                    id: BeanId::new(id),
+                    instance,
+                    dispose: definition.dispose.clone(),
+                    phase: definition.shutdown_phase.clone(),
+                };
+                Some((seq, disposable))
+            })
+            .collect();
+        taken.sort_by_key(|(seq, _)| std::cmp::Reverse(*seq));
+        taken.into_iter().map(|(_, d)| d).collect()
+    }
+
+    /// Singletons already created, in registration order
+    ///
+    /// Lets cross-cutting passes run over live instances without knowing their types,
+    /// never triggers a creation
+    pub fn instances(&self) -> Vec<(BeanId, Arc<dyn Any + Send + Sync>)> {
+        let ids = self.ids_in_order();
+        let beans = self.beans.read().unwrap();
+        ids.into_iter()
+            .filter_map(|id| {
+                let definition = beans.get(&id)?;
+                let instance = definition.instance.clone()?;
+                Some((BeanId::new(&id), instance))
+            })
+            .collect()
+    }
+
+    /// Singletons already created and exposed as the trait object `U`, in registration order
+    ///
+    /// See [`Registration::upcast`]
+    #[cfg(feature = "wiring")]
+    pub fn instances_as<U: ?Sized + 'static>(&self) -> Vec<Arc<U>> {
+        self.identified_instances_as::<U>()
+            .into_iter()
+            .map(|(_, view)| view)
+            .collect()
+    }
+
+    /// Same as [`instances_as`](Self::instances_as), with the bean of each instance
+    #[cfg(feature = "wiring")]
+    pub(crate) fn identified_instances_as<U: ?Sized + 'static>(&self) -> Vec<(BeanId, Arc<U>)> {
+        let ids = self.ids_in_order();
+        let beans = self.beans.read().unwrap();
+        ids.iter()
+            .filter_map(|id| {
+                let definition = beans.get(id)?;
+                let instance = definition.instance.clone()?;
+                let (_, upcast) = definition
+                    .upcasts
+                    .iter()
+                    .find(|(target, _)| *target == TypeId::of::<U>())?;
+                let view = upcast(instance)?.downcast::<Arc<U>>().ok()?;
+                Some((BeanId::new(id), *view))
+            })
+            .collect()
+    }
+
+    /// Beans exposed as the trait object `U`, created or not, in registration order,
+    /// aliases and internal beans excluded
+    #[cfg(all(feature = "wiring", any(feature = "scopes", feature = "lifecycle")))]
+    pub(crate) fn upcast_candidates<U: ?Sized + 'static>(&self) -> Vec<BeanId> {
+        let ids = self.ids_in_order();
+        let beans = self.beans.read().unwrap();
+        ids.iter()
+            .filter(|id| !matches!(id, Identifier::Unnamed(..)))
+            .filter(|id| {
+                beans.get(id).is_some_and(|definition| {
+                    #[cfg(feature = "modules")]
+                    if definition.internal {
+                        return false;
+                    }
+                    definition
+                        .upcasts
+                        .iter()
+                        .any(|(target, _)| *target == TypeId::of::<U>())
+                })
+            })
+            .map(BeanId::new)
+            .collect()
+    }
+
+    /// Resolve a bean exposed as the trait object `U`, see [`Registration::upcast`]
+    #[cfg(all(feature = "wiring", any(feature = "scopes", feature = "lifecycle")))]
+    pub(crate) fn resolve_as<U: ?Sized + 'static>(&self, id: BeanId) -> Result<Arc<U>, BeanError> {
+        let instance = self.resolve_id(&id)?;
+        let upcast = self
+            .beans
+            .read()
+            .unwrap()
+            .get(id.identifier())
+            .and_then(|definition| {
+                definition
+                    .upcasts
+                    .iter()
+                    .find(|(target, _)| *target == TypeId::of::<U>())
+                    .map(|(_, upcast)| upcast.clone())
+            })
+            .ok_or(BeanError::NotFound(id))?;
+        upcast(instance)
+            .and_then(|view| view.downcast::<Arc<U>>().ok())
+            .map(|view| *view)
+            .ok_or(BeanError::DowncastFailed(type_name::<U>()))
+    }
+
+    /// Dependency edges `(dependent, dependency)` captured while creating beans,
+    /// in registration order of the dependents
+    ///
+    /// Every `Dependencies::get*` call made by a factory is recorded, so the graph needs no
+    /// declaration. Edges of a bean are known once it has been created.
+    pub fn dependency_edges(&self) -> Vec<(BeanId, BeanId)> {
+        let ids = self.ids_in_order();
+        let beans = self.beans.read().unwrap();
+        ids.iter()
+            .filter_map(|id| beans.get(id).map(|definition| (id, definition)))
+            .flat_map(|(id, definition)| {
+                definition
+                    .dependencies
+                    .iter()
+                    .map(|dependency| (BeanId::new(id), BeanId::new(dependency)))
+            })
+            .collect()
+    }
+
+    /// Declared and observed dependencies `(bean, dependency, registered)`, in registration
+    /// order, `registered` telling whether the dependency is registered here or in a parent
+    #[cfg(feature = "introspection")]
+    pub(crate) fn wiring_edges(&self) -> Vec<(BeanId, BeanId, bool)> {
+        let declared: Vec<(Identifier, Vec<Identifier>)> = {
+            let ids = self.ids_in_order();
+            let beans = self.beans.read().unwrap();
+            ids.into_iter()
+                .filter_map(|id| {
+                    let dependencies = beans.get(&id)?.dependency_keys(&beans);
+                    Some((id, dependencies))
+                })
+                .collect()
+        };
+        let mut edges = Vec::new();
+        for (id, dependencies) in declared {
+            for dependency in dependencies {
+                let registered = self.is_registered(&dependency);
+                edges.push((BeanId::new(&id), BeanId::new(&dependency), registered));
+            }
+        }
+        edges
+    }
+
+    /// Registered beans and their declared and observed dependencies, in registration order,
+    /// read from one snapshot of the definitions
+    #[cfg(feature = "graph")]
+    pub(crate) fn wiring_graph(&self) -> (Vec<BeanId>, Vec<crate::graph::DependencyEdge>) {
+        use crate::graph::{DependencyEdge, EdgeSource};
+
+        let (nodes, mut edges) = {
+            let beans = self.beans.read().unwrap();
+            let ids = ordered_ids(&beans);
+            let mut edges = Vec::new();
+            for id in &ids {
+                let Some(definition) = beans.get(id) else {
+                    continue;
+                };
+                #[cfg(feature = "wiring")]
+                let declared: Vec<Identifier> = definition
+                    .requires
+                    .iter()
+                    .map(|dependency| dependency.key(&beans))
+                    .collect();
+                #[cfg(not(feature = "wiring"))]
+                let declared: Vec<Identifier> = Vec::new();
+                for dependency in definition.dependency_keys(&beans) {
+                    let source = match (
+                        declared.contains(&dependency),
+                        definition.dependencies.contains(&dependency),
+                    ) {
+                        (true, true) => EdgeSource::Both,
+                        (true, false) => EdgeSource::Declared,
+                        _ => EdgeSource::Observed,
+                    };
+                    edges.push(DependencyEdge {
+                        dependent: BeanId::new(id),
+                        dependency: BeanId::new(&dependency),
+                        source,
+                        registered: registered_in(&beans, &dependency),
+                    });
+                }
+            }
+            let nodes: Vec<BeanId> = ids.iter().map(BeanId::new).collect();
+            (nodes, edges)
+        };
+        // Parents have their own lock
+        for edge in edges.iter_mut().filter(|edge| !edge.registered) {
+            edge.registered = self
+                .parent
+                .as_ref()
+                .is_some_and(|p| p.is_registered(edge.dependency.identifier()));
+        }
+        (nodes, edges)
+    }
+
+    #[cfg(feature = "introspection")]
+    pub(crate) fn is_registered(&self, id: &Identifier) -> bool {
+        let registered = registered_in(&self.beans.read().unwrap(), id);
+        registered || self.parent.as_ref().is_some_and(|p| p.is_registered(id))
+    }
+
+    /// Edges `(bean, target)` from the beans resolving their target on demand, e.g. providers
+    #[cfg(feature = "introspection")]
+    pub(crate) fn deferred_edges(&self) -> Vec<(BeanId, BeanId)> {
+        let deferred: Vec<(Identifier, DeferredTarget)> = {
+            let ids = self.ids_in_order();
+            let beans = self.beans.read().unwrap();
+            ids.into_iter()
+                .filter_map(|id| {
+                    let target = beans.get(&id)?.defers_to?;
+                    Some((id, target))
+                })
+                .collect()
+        };
+        deferred
+            .into_iter()
+            .filter_map(|(id, target)| Some((BeanId::new(&id), BeanId::new(&target(self)?))))
+            .collect()
+    }
+
+    /// Shortest path from `start` to one of `targets` through declared, observed and deferred
+    /// dependencies, both ends included
+    #[cfg(any(feature = "testing", feature = "lifecycle"))]
+    pub(crate) fn dependency_path(&self, start: BeanId, targets: &[BeanId]) -> Option<Vec<BeanId>> {
+        let edges: Vec<(BeanId, BeanId)> = self
+            .wiring_edges()
+            .into_iter()
+            .map(|(from, to, _)| (from, to))
+            .chain(self.deferred_edges())
+            .collect();
+        shortest_path(
+            start,
+            |id| {
+                edges
+                    .iter()
+                    .filter(|(dependent, _)| dependent == id)
+                    .map(|(_, dependency)| *dependency)
+                    .collect::<Vec<_>>()
+            },
+            |id| targets.contains(id),
+        )
+    }
+
+    /// Introspection of the bean resolved by type, `None` if not registered
+    #[cfg(feature = "introspection")]
+    pub fn bean_info<T: Any + Send + Sync + 'static>(&self) -> Option<BeanInfo> {
+        let id = self.find_id::<T>(None)?;
+        self.beans.read().unwrap().get(&id).map(|d| d.info(&id))
+    }
+
+    /// Introspection of a named bean, `None` if not registered
+    #[cfg(feature = "introspection")]
+    pub fn bean_info_named(&self, name: &str) -> Option<BeanInfo> {
+        let id = self.named_id(name);
+        self.beans.read().unwrap().get(&id).map(|d| d.info(&id))
+    }
+
+    /// Names of registered beans, in registration order
+    pub fn bean_names(&self) -> Vec<String> {
+        self.ids_in_order()
+            .iter()
+            .map(|id| id.to_string())
+            .collect()
+    }
+
     /// Get the number of registered beans
     pub fn len(&self) -> usize {
         self.beans.read().unwrap().len()
@@ -345,11 +4481,23 @@ impl Default for BeanContainer {
     }
 }
 
+/// Wrap a typed factory into a type-erased bean factory
+fn into_bean_factory<T, F>(factory: F) -> Arc<dyn BeanFactory>
+where
+    T: Any + Send + Sync + 'static,
+    F: Fn(&mut Dependencies) -> Result<T, BeanError> + Send + Sync + 'static,
+{
+    Arc::new(move |deps: &mut Dependencies| {
+        let instance = factory(deps)?;
+        Ok(Arc::new(instance) as Arc<dyn Any + Send + Sync>)
+    })
+}
+
 impl<F> BeanFactory for F
 where
-    F: Fn(&mut Dependencies) -> Result<Arc<dyn Any + Send + Sync>, String> + Send + Sync,
+    F: Fn(&mut Dependencies) -> Result<Arc<dyn Any + Send + Sync>, BeanError> + Send + Sync,
 {
-    fn create(&self, deps: &mut Dependencies) -> Result<Arc<dyn Any + Send + Sync>, String> {
+    fn create(&self, deps: &mut Dependencies) -> Result<Arc<dyn Any + Send + Sync>, BeanError> {
         self(deps)
     }
 }
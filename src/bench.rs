@@ -0,0 +1,79 @@
+use std::any::Any;
+use std::time::{Duration, Instant};
+
+use crate::bean::BeanContainer;
+use crate::error::BeanError;
+
+/// Latency distribution of repeated resolutions
+#[derive(Debug, Clone)]
+pub struct LatencyStats {
+    pub samples: usize,
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+}
+
+impl LatencyStats {
+    fn from_samples(mut samples: Vec<Duration>) -> Self {
+        samples.sort();
+        let percentile = |p: usize| {
+            let index = (samples.len() * p / 100).min(samples.len().saturating_sub(1));
+            samples.get(index).copied().unwrap_or_default()
+        };
+        let total: Duration = samples.iter().sum();
+        LatencyStats {
+            samples: samples.len(),
+            min: samples.first().copied().unwrap_or_default(),
+            max: samples.last().copied().unwrap_or_default(),
+            mean: total.checked_div(samples.len() as u32).unwrap_or_default(),
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+        }
+    }
+}
+
+/// Latencies of resolving one bean, see [`measure_resolution`]
+#[derive(Debug, Clone)]
+pub struct ResolutionBench {
+    /// Resolutions through the container cache, like `get`
+    pub cached: LatencyStats,
+    /// Resolutions running the factory, like `create`
+    pub cold: LatencyStats,
+}
+
+/// Measure `iters` cached and `iters` cold resolutions of `T`
+///
+/// Meant for regression tests of the user's own bean graphs, results depend on the machine.
+/// Dependencies of `T` are resolved through the cache in both cases.
+pub fn measure_resolution<T: Any + Send + Sync + 'static>(
+    container: &BeanContainer,
+    iters: usize,
+) -> Result<ResolutionBench, BeanError> {
+    // Warm up, so cached resolutions don't include the first creation
+    container.try_get::<T>(None)?;
+
+    let mut cached = Vec::with_capacity(iters);
+    for _ in 0..iters {
+        let start = Instant::now();
+        let instance = container.try_get::<T>(None)?;
+        cached.push(start.elapsed());
+        drop(instance);
+    }
+
+    let mut cold = Vec::with_capacity(iters);
+    for _ in 0..iters {
+        let start = Instant::now();
+        let instance = container.try_create::<T>()?;
+        cold.push(start.elapsed());
+        drop(instance);
+    }
+
+    Ok(ResolutionBench {
+        cached: LatencyStats::from_samples(cached),
+        cold: LatencyStats::from_samples(cold),
+    })
+}
@@ -0,0 +1,39 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use crate::bean::{BeanContainer, Dependencies, Registration, Scope};
+use crate::error::BeanError;
+
+impl BeanContainer {
+    /// Bind the trait object `U` to the bean `T`, e.g.
+    /// `bind::<dyn Repository, SqlRepository>(|r| r)`, so consumers depend on the abstraction
+    ///
+    /// Each resolution of `U` resolves `T` with its own scope, a singleton `T` is shared
+    #[track_caller]
+    pub fn bind<U, T>(&self, cast: fn(Arc<T>) -> Arc<U>) -> Result<Registration<'_>, BeanError>
+    where
+        U: ?Sized + Send + Sync + 'static,
+        T: Any + Send + Sync + 'static,
+    {
+        self.register::<Arc<U>, _>(Scope::Prototype, move |deps| Ok(cast(deps.get::<T>()?)))
+    }
+
+    /// Get the bean bound to the trait object `U` with [`bind`](Self::bind)
+    /// **NOTE**: panics if no bean is bound or creation fails
+    pub fn get_dyn<U: ?Sized + Send + Sync + 'static>(&self) -> Arc<U> {
+        self.try_get_dyn::<U>().unwrap()
+    }
+
+    /// Get the bean bound to the trait object `U` with [`bind`](Self::bind)
+    pub fn try_get_dyn<U: ?Sized + Send + Sync + 'static>(&self) -> Result<Arc<U>, BeanError> {
+        self.try_get::<Arc<U>>(None)
+            .map(|bound| Arc::clone(&*bound))
+    }
+}
+
+impl Dependencies<'_> {
+    /// Get the bean bound to the trait object `U`, see [`BeanContainer::bind`]
+    pub fn get_dyn<U: ?Sized + Send + Sync + 'static>(&mut self) -> Result<Arc<U>, BeanError> {
+        self.get::<Arc<U>>().map(|bound| Arc::clone(&*bound))
+    }
+}
@@ -0,0 +1,135 @@
+use crate::bean::{BeanContainer, BeanDef};
+use crate::error::BeanError;
+
+/// What [`ContainerBuilder`] does when two definitions share the same type or name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictPolicy {
+    /// Fail the build
+    #[default]
+    Error,
+    /// Keep the definition added first, ignore the later one
+    KeepFirst,
+    /// The definition added last wins
+    Overwrite,
+    /// Keep both, registering the later one under a fresh name, e.g. `db#2`
+    Rename,
+}
+
+/// How a conflict was resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConflictResolution {
+    Rejected,
+    KeptFirst,
+    Overwritten,
+    Renamed(String),
+}
+
+/// Two definitions registering the same bean
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    /// Type or name both definitions register
+    pub bean: String,
+    /// Module of the definition added first, if any
+    pub first_module: Option<String>,
+    /// Module of the conflicting definition, if any
+    pub second_module: Option<String>,
+    pub resolution: ConflictResolution,
+}
+
+/// Collects definitions from several modules, resolving conflicts between them
+/// before building the container
+#[derive(Default)]
+pub struct ContainerBuilder {
+    policy: ConflictPolicy,
+    // Definitions with the module which added them
+    defs: Vec<(Option<String>, BeanDef)>,
+}
+
+impl ContainerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Policy applied when two definitions register the same type or name, defaults to `Error`
+    pub fn conflict_policy(mut self, policy: ConflictPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Add one definition
+    pub fn bean(mut self, def: BeanDef) -> Self {
+        self.defs.push((None, def));
+        self
+    }
+
+    /// Add the definitions of a module, its name is reported in conflicts
+    ///
+    /// Definitions without a module are put in this one, see [`BeanDef::in_module`]
+    pub fn module(mut self, name: &str, defs: impl IntoIterator<Item = BeanDef>) -> Self {
+        self.defs.extend(defs.into_iter().map(|d| {
+            let d = if d.module_name().is_none() {
+                d.in_module(name)
+            } else {
+                d
+            };
+            (Some(name.to_string()), d)
+        }));
+        self
+    }
+
+    /// Build the container, returning the conflicts found along the way
+    ///
+    /// Fails if any conflict is found with the `Error` policy, or if registration fails
+    pub fn build(self) -> Result<(BeanContainer, Vec<Conflict>), BeanError> {
+        let mut accepted: Vec<(Option<String>, BeanDef)> = Vec::new();
+        let mut conflicts = Vec::new();
+
+        for (module, def) in self.defs {
+            let Some(index) = accepted.iter().position(|(_, d)| def.conflicts_with(d)) else {
+                accepted.push((module, def));
+                continue;
+            };
+
+            let bean = def.display_name().to_string();
+            let first_module = accepted[index].0.clone();
+            let resolution = match self.policy {
+                ConflictPolicy::Error => ConflictResolution::Rejected,
+                ConflictPolicy::KeepFirst => ConflictResolution::KeptFirst,
+                ConflictPolicy::Overwrite => {
+                    accepted.remove(index);
+                    accepted.push((module.clone(), def));
+                    ConflictResolution::Overwritten
+                }
+                ConflictPolicy::Rename => {
+                    let name = (2..)
+                        .map(|n| format!("{}#{}", bean, n))
+                        .find(|name| accepted.iter().all(|(_, d)| d.display_name() != name))
+                        .unwrap();
+                    accepted.push((module.clone(), def.named(&name)));
+                    ConflictResolution::Renamed(name)
+                }
+            };
+            conflicts.push(Conflict {
+                bean,
+                first_module,
+                second_module: module,
+                resolution,
+            });
+        }
+
+        if self.policy == ConflictPolicy::Error && !conflicts.is_empty() {
+            let beans = conflicts.iter().map(|c| c.bean.clone()).collect();
+            return Err(BeanError::Conflicts(beans));
+        }
+
+        let container = BeanContainer::new();
+        container.register_all(accepted.into_iter().map(|(_, d)| d))?;
+        Ok((container, conflicts))
+    }
+}
+
+impl BeanContainer {
+    pub fn builder() -> ContainerBuilder {
+        ContainerBuilder::new()
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::bean::{BeanContainer, Registration, Scope};
+use crate::error::BeanError;
+
+/// Implementation of a [`Clock`]
+pub trait TimeSource: Send + Sync {
+    fn now(&self) -> Instant;
+    fn system_time(&self) -> SystemTime;
+    fn sleep(&self, duration: Duration);
+}
+
+/// Time source of the operating system
+struct SystemTimeSource;
+
+impl TimeSource for SystemTimeSource {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// Source of time for beans, e.g. TTLs, schedulers and retries
+///
+/// Resolving it registers the system clock unless one is registered, tests can register
+/// a [`TestClock`](crate::testing::TestClock) advanced manually instead. The container follows
+/// it too, for the TTLs of [`Scope::Cached`] beans, failure backoffs and creation rate limits.
+#[derive(Clone)]
+pub struct Clock {
+    source: Arc<dyn TimeSource>,
+}
+
+impl Clock {
+    pub fn new(source: impl TimeSource + 'static) -> Self {
+        Clock {
+            source: Arc::new(source),
+        }
+    }
+
+    /// Clock of the operating system
+    pub fn system() -> Self {
+        Self::new(SystemTimeSource)
+    }
+
+    pub fn now(&self) -> Instant {
+        self.source.now()
+    }
+
+    pub fn system_time(&self) -> SystemTime {
+        self.source.system_time()
+    }
+
+    /// Block the current thread for `duration` of this clock's time
+    pub fn sleep(&self, duration: Duration) {
+        self.source.sleep(duration)
+    }
+}
+
+impl BeanContainer {
+    /// Register the clock of the beans
+    #[track_caller]
+    pub fn register_clock(&self, clock: Clock) -> Result<Registration<'_>, BeanError> {
+        self.register::<Clock, _>(Scope::Singleton, move |_deps| Ok(clock.clone()))
+    }
+
+    /// Register the system [`Clock`], unless one is already registered
+    pub(crate) fn provide_clock(&self) -> Result<(), BeanError> {
+        self.register_clock(Clock::system())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,476 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+use crate::bean::{BeanContainer, Registration, Scope};
+use crate::environment::Environment;
+use crate::error::BeanError;
+
+/// Source of configuration properties, e.g. environment variables or a file
+pub trait PropertySource: Send + Sync {
+    /// Name shown in diagnostics
+    fn name(&self) -> &str;
+
+    /// Value of the property, keys are dotted paths like `db.url`
+    fn get(&self, key: &str) -> Option<String>;
+}
+
+/// Struct bound from configuration properties, see [`BeanContainer::register_config`]
+pub trait FromConfig: Sized {
+    /// Bind from the properties under `prefix`, e.g. `db.url` and `db.pool_size` for `db`
+    fn from_config(config: &Config, prefix: &str) -> Result<Self, BeanError>;
+}
+
+/// Properties read from environment variables, `db.url` is read from `DB_URL`
+pub struct EnvSource {
+    prefix: Option<String>,
+}
+
+impl EnvSource {
+    pub fn new() -> Self {
+        EnvSource { prefix: None }
+    }
+
+    /// Only read variables starting with the prefix, `db.url` is read from `<PREFIX>_DB_URL`
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = Some(prefix.to_uppercase());
+        self
+    }
+
+    fn variable(&self, key: &str) -> String {
+        let name = key.to_uppercase().replace(['.', '-'], "_");
+        match &self.prefix {
+            Some(prefix) => format!("{}_{}", prefix, name),
+            None => name,
+        }
+    }
+}
+
+impl Default for EnvSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PropertySource for EnvSource {
+    fn name(&self) -> &str {
+        "env"
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        std::env::var(self.variable(key)).ok()
+    }
+}
+
+/// In-memory properties, for defaults and tests
+pub struct MapSource {
+    name: String,
+    values: HashMap<String, String>,
+}
+
+impl MapSource {
+    pub fn new(name: &str) -> Self {
+        MapSource {
+            name: name.to_string(),
+            values: HashMap::new(),
+        }
+    }
+
+    pub fn with(mut self, key: &str, value: &str) -> Self {
+        self.values.insert(key.to_string(), value.to_string());
+        self
+    }
+}
+
+impl PropertySource for MapSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        self.values.get(key).cloned()
+    }
+}
+
+/// Properties read from a file of `key = value` lines, re-read with [`reload`](Self::reload)
+///
+/// `[section]` headers prefix the keys below them, so TOML files with flat tables work:
+/// `url` under `[db]` is read as `db.url`. Clones share the same properties.
+#[derive(Clone)]
+pub struct FileSource {
+    path: PathBuf,
+    name: String,
+    values: Arc<RwLock<HashMap<String, String>>>,
+}
+
+impl FileSource {
+    /// Read the file, failing if it can't be read
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, BeanError> {
+        let path = path.as_ref().to_path_buf();
+        let source = FileSource {
+            name: path.display().to_string(),
+            path,
+            values: Arc::default(),
+        };
+        source.reload()?;
+        Ok(source)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the file again, keeping the previous properties if it can't be read
+    pub fn reload(&self) -> Result<(), BeanError> {
+        let content = std::fs::read_to_string(&self.path)
+            .map_err(|e| BeanError::Config(format!("Failed to read {}: {}", self.name, e)))?;
+        *self.values.write().unwrap() = parse_properties(&content);
+        Ok(())
+    }
+}
+
+impl PropertySource for FileSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        self.values.read().unwrap().get(key).cloned()
+    }
+}
+
+fn parse_properties(content: &str) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    let mut section = String::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_string();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        let key = if section.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", section, key)
+        };
+        values.insert(key, value.to_string());
+    }
+    values
+}
+
+/// Property sources consulted in order of precedence
+#[derive(Clone, Default)]
+pub struct Config {
+    // Highest precedence first
+    sources: Vec<Arc<dyn PropertySource>>,
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a source, consulted after the ones already added
+    pub fn with_source<S: PropertySource + 'static>(mut self, source: S) -> Self {
+        self.sources.push(Arc::new(source));
+        self
+    }
+
+    /// Value of the property from the first source defining it
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.sources.iter().find_map(|source| source.get(key))
+    }
+
+    /// Value of a property parsed as `T`, `None` if not defined
+    pub fn get_parsed<T: FromStr>(&self, key: &str) -> Result<Option<T>, BeanError>
+    where
+        T::Err: Display,
+    {
+        self.get(key)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|e| BeanError::Config(format!("Invalid value of {}: {}", key, e)))
+            })
+            .transpose()
+    }
+
+    /// Where the value of a property comes from: each source consulted, what it offered
+    /// and which one won
+    pub fn explain(&self, key: &str) -> ValueProvenance {
+        let consulted: Vec<SourceValue> = self
+            .sources
+            .iter()
+            .map(|source| SourceValue {
+                source: source.name().to_string(),
+                value: source.get(key),
+            })
+            .collect();
+        let winner = consulted.iter().position(|s| s.value.is_some());
+        ValueProvenance {
+            key: key.to_string(),
+            consulted,
+            winner,
+        }
+    }
+
+    /// Names of the sources, in order of precedence
+    pub fn source_names(&self) -> Vec<String> {
+        self.sources.iter().map(|s| s.name().to_string()).collect()
+    }
+}
+
+/// Value offered by one source, see [`Config::explain`]
+#[derive(Debug, Clone)]
+pub struct SourceValue {
+    pub source: String,
+    pub value: Option<String>,
+}
+
+/// Provenance of a property value, see [`Config::explain`]
+#[derive(Debug, Clone)]
+pub struct ValueProvenance {
+    pub key: String,
+    /// Every source, in order of precedence
+    pub consulted: Vec<SourceValue>,
+    /// Index in `consulted` of the source the value comes from
+    pub winner: Option<usize>,
+}
+
+impl ValueProvenance {
+    /// The winning value, as returned by [`Config::get`]
+    pub fn value(&self) -> Option<&str> {
+        self.winning_source()?.value.as_deref()
+    }
+
+    pub fn winning_source(&self) -> Option<&SourceValue> {
+        self.consulted.get(self.winner?)
+    }
+}
+
+impl Display for ValueProvenance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.key)?;
+        for (index, consulted) in self.consulted.iter().enumerate() {
+            let marker = if Some(index) == self.winner { "*" } else { " " };
+            match &consulted.value {
+                Some(value) => writeln!(f, "{} {}: {}", marker, consulted.source, value)?,
+                None => writeln!(f, "{} {}: (unset)", marker, consulted.source)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BeanContainer {
+    /// Register a singleton `T` bound from the properties of the [`Environment`] under `prefix`
+    ///
+    /// The bean depends on `Environment`, so it is rebound when the environment is refreshed
+    #[track_caller]
+    pub fn register_config<T>(&self, prefix: &str) -> Result<Registration<'_>, BeanError>
+    where
+        T: FromConfig + Any + Send + Sync + 'static,
+    {
+        let prefix = prefix.to_string();
+        self.register::<T, _>(Scope::Singleton, move |deps| {
+            let environment = deps.get::<Environment>()?;
+            T::from_config(environment.config(), &prefix)
+        })
+    }
+
+    /// Register a config bean like [`register_config`](Self::register_config),
+    /// adding its JSON Schema to [`config_schema`](Self::config_schema)
+    #[cfg(feature = "schema")]
+    #[track_caller]
+    pub fn register_config_with_schema<T>(
+        &self,
+        prefix: &str,
+    ) -> Result<Registration<'_>, BeanError>
+    where
+        T: FromConfig + schemars::JsonSchema + Any + Send + Sync + 'static,
+    {
+        let registration = self.register_config::<T>(prefix)?;
+        let schema = schemars::schema_for!(T).to_value();
+        let mut schemas = self.config_schemas.write().unwrap();
+        schemas.push((prefix.to_string(), schema));
+        Ok(registration)
+    }
+
+    /// JSON Schema of all the configuration consumed by config beans registered with
+    /// [`register_config_with_schema`](Self::register_config_with_schema)
+    ///
+    /// Each bean's schema is nested under its dotted prefix, shared definitions are merged
+    #[cfg(feature = "schema")]
+    pub fn config_schema(&self) -> serde_json::Value {
+        use serde_json::{Map, Value, json};
+
+        let mut root = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": {},
+        });
+        let mut definitions = Map::new();
+        for (prefix, schema) in self.config_schemas.read().unwrap().iter() {
+            let mut schema = schema.clone();
+            if let Some(object) = schema.as_object_mut() {
+                object.remove("$schema");
+                if let Some(Value::Object(defs)) = object.remove("$defs") {
+                    definitions.extend(defs);
+                }
+            }
+            // Nest `a.b` as properties.a.properties.b
+            let mut node = &mut root;
+            for part in prefix.split('.') {
+                if !node["properties"].is_object() {
+                    node["properties"] = json!({});
+                }
+                node = node["properties"]
+                    .as_object_mut()
+                    .unwrap()
+                    .entry(part)
+                    .or_insert_with(|| json!({ "type": "object", "properties": {} }));
+                if !node.is_object() {
+                    *node = json!({});
+                }
+            }
+            // Keep the beans nested under this prefix
+            let nested = std::mem::replace(node, schema);
+            if let Some(Value::Object(nested)) = nested.get("properties").cloned()
+                && node.is_object()
+            {
+                if !node["properties"].is_object() {
+                    node["properties"] = json!({});
+                }
+                let properties = node["properties"].as_object_mut().unwrap();
+                for (key, value) in nested {
+                    properties.entry(key).or_insert(value);
+                }
+            }
+        }
+        if !definitions.is_empty() {
+            root["$defs"] = Value::Object(definitions);
+        }
+        root
+    }
+}
+
+#[cfg(feature = "watch")]
+pub use file_watcher::ConfigWatcher;
+
+#[cfg(feature = "watch")]
+mod file_watcher {
+    use std::path::PathBuf;
+    use std::sync::mpsc::{self, RecvTimeoutError};
+    use std::sync::{Arc, Weak};
+    use std::thread;
+    use std::time::Duration;
+
+    use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+
+    use super::FileSource;
+    use crate::bean::BeanContainer;
+    use crate::environment::Environment;
+    use crate::error::BeanError;
+
+    /// Watcher of file property sources, stops when dropped
+    ///
+    /// See [`BeanContainer::watch_config_files`]
+    pub struct ConfigWatcher {
+        _watcher: RecommendedWatcher,
+    }
+
+    impl BeanContainer {
+        /// Reload the file sources when they change, then refresh the `Environment` bean
+        /// and the beans depending on it
+        ///
+        /// Changes are debounced: the refresh happens once no change was seen for `debounce`
+        pub fn watch_config_files(
+            self: &Arc<Self>,
+            sources: &[FileSource],
+            debounce: Duration,
+        ) -> Result<ConfigWatcher, BeanError> {
+            let (tx, rx) = mpsc::channel::<Vec<PathBuf>>();
+            let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+                // Reading the files on reload must not trigger another one
+                if let Ok(event) = event
+                    && (event.kind.is_modify() || event.kind.is_create())
+                {
+                    let _ = tx.send(event.paths);
+                }
+            })
+            .map_err(|e| BeanError::Io(format!("Failed to watch config files: {}", e)))?;
+
+            // Watch directories, editors often replace files instead of writing them
+            for source in sources {
+                let dir = source
+                    .path()
+                    .parent()
+                    .filter(|d| !d.as_os_str().is_empty())
+                    .unwrap_or(".".as_ref());
+                watcher
+                    .watch(dir, RecursiveMode::NonRecursive)
+                    .map_err(|e| {
+                        BeanError::Io(format!("Failed to watch {}: {}", source.name, e))
+                    })?;
+            }
+
+            let container = Arc::downgrade(self);
+            let sources = sources.to_vec();
+            thread::spawn(move || debounce_loop(container, sources, rx, debounce));
+            Ok(ConfigWatcher { _watcher: watcher })
+        }
+    }
+
+    fn debounce_loop(
+        container: Weak<BeanContainer>,
+        sources: Vec<FileSource>,
+        rx: mpsc::Receiver<Vec<PathBuf>>,
+        debounce: Duration,
+    ) {
+        // Ends once the watcher is dropped
+        while let Ok(paths) = rx.recv() {
+            let mut changed = paths;
+            loop {
+                match rx.recv_timeout(debounce) {
+                    Ok(paths) => changed.extend(paths),
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+
+            let touched: Vec<&FileSource> = sources
+                .iter()
+                .filter(|s| {
+                    changed
+                        .iter()
+                        .any(|p| p.file_name() == s.path().file_name())
+                })
+                .collect();
+            if touched.is_empty() {
+                continue;
+            }
+            let Some(container) = container.upgrade() else {
+                return;
+            };
+            // A file being rewritten may not be readable yet, keep the previous properties
+            if touched.iter().any(|s| s.reload().is_ok()) {
+                let _ = container.refresh::<Environment>();
+            }
+        }
+    }
+}
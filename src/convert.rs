@@ -0,0 +1,49 @@
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+use crate::bean::{BeanContainer, BeanFactory, Dependencies};
+#[cfg(feature = "config")]
+use crate::environment::Environment;
+use crate::error::BeanError;
+
+impl BeanContainer {
+    /// Satisfy requests for `T` by converting the bean `S`, when no `T` bean is registered
+    ///
+    /// Avoids glue beans which only adapt types, e.g. a `DbUrl` newtype from a `Settings` bean.
+    /// The conversion runs on each injection, registering a `T` bean takes precedence. A chain
+    /// of converters leading back to `T` fails with `CircularDependency`.
+    pub fn register_converter<S, T, F>(&self, convert: F)
+    where
+        S: Any + Send + Sync + 'static,
+        T: Any + Send + Sync + 'static,
+        F: Fn(Arc<S>) -> Result<T, BeanError> + Send + Sync + 'static,
+    {
+        self.add_converter::<T>(Arc::new(move |deps: &mut Dependencies| {
+            let source = deps.get::<S>()?;
+            Ok(Arc::new(convert(source)?) as Arc<dyn Any + Send + Sync>)
+        }));
+    }
+
+    /// Satisfy requests for `T` by converting the property `key` of the [`Environment`],
+    /// when no `T` bean is registered
+    #[cfg(feature = "config")]
+    pub fn register_property_converter<T, F>(&self, key: &str, convert: F)
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(&str) -> Result<T, BeanError> + Send + Sync + 'static,
+    {
+        let key = key.to_string();
+        self.add_converter::<T>(Arc::new(move |deps: &mut Dependencies| {
+            let environment = deps.get::<Environment>()?;
+            let value = environment
+                .property(&key)
+                .ok_or_else(|| BeanError::Config(format!("Property not found: {}", key)))?;
+            Ok(Arc::new(convert(&value)?) as Arc<dyn Any + Send + Sync>)
+        }));
+    }
+
+    fn add_converter<T: 'static>(&self, converter: Arc<dyn BeanFactory>) {
+        let mut converters = self.converters().write().unwrap();
+        converters.insert(TypeId::of::<T>(), converter);
+    }
+}
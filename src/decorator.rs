@@ -0,0 +1,104 @@
+use std::any::{Any, type_name};
+use std::sync::Arc;
+
+use crate::bean::{BeanContainer, BeanFactory, Dependencies, Registration};
+use crate::error::BeanError;
+
+// Wraps a bean bound to a trait object, `Fn(Arc<U>, deps) -> Arc<U>` once typed
+type Layer<U> = dyn Fn(Arc<U>, &mut Dependencies) -> Result<Arc<U>, BeanError> + Send + Sync;
+
+// Applies a whole stack to a type-erased `Arc<U>` bean
+pub(crate) type StackFn = dyn Fn(
+        Arc<dyn Any + Send + Sync>,
+        &mut Dependencies,
+    ) -> Result<Arc<dyn Any + Send + Sync>, BeanError>
+    + Send
+    + Sync;
+
+/// Wrappers of the trait object `U` applied together, e.g. `auth -> retry -> metrics`
+///
+/// Define it with [`BeanContainer::define_stack`] and apply it to beans bound to `U`
+/// with [`Registration::with_stack`]. The first layer is the outermost one.
+pub struct DecoratorStack<U: ?Sized> {
+    layers: Vec<(String, Arc<Layer<U>>)>,
+}
+
+impl<U: ?Sized + Send + Sync + 'static> DecoratorStack<U> {
+    pub fn new() -> Self {
+        DecoratorStack { layers: Vec::new() }
+    }
+
+    /// Add a layer wrapping the layers added after it
+    pub fn layer<F>(mut self, name: &str, wrap: F) -> Self
+    where
+        F: Fn(Arc<U>, &mut Dependencies) -> Result<Arc<U>, BeanError> + Send + Sync + 'static,
+    {
+        self.layers.push((name.to_string(), Arc::new(wrap)));
+        self
+    }
+
+    /// Names of the layers, outermost first
+    pub fn layer_names(&self) -> Vec<&str> {
+        self.layers.iter().map(|(name, _)| name.as_str()).collect()
+    }
+}
+
+impl<U: ?Sized + Send + Sync + 'static> Default for DecoratorStack<U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BeanContainer {
+    /// Define a decorator stack, replacing the one with the same name if any
+    pub fn define_stack<U: ?Sized + Send + Sync + 'static>(
+        &self,
+        name: &str,
+        stack: DecoratorStack<U>,
+    ) {
+        let apply: Arc<StackFn> = Arc::new(move |instance, deps| {
+            let bound = instance
+                .downcast::<Arc<U>>()
+                .map_err(|_| BeanError::DowncastFailed(type_name::<Arc<U>>()))?;
+            // Innermost layer first
+            let mut decorated = Arc::clone(&*bound);
+            for (_, wrap) in stack.layers.iter().rev() {
+                decorated = wrap(decorated, deps)?;
+            }
+            Ok(Arc::new(decorated) as Arc<dyn Any + Send + Sync>)
+        });
+        self.decorator_stacks
+            .write()
+            .unwrap()
+            .insert(name.to_string(), apply);
+    }
+}
+
+impl Registration<'_> {
+    /// Wrap the bean in the decorator stack defined with [`BeanContainer::define_stack`]
+    ///
+    /// The bean must be bound to the stack's trait object, see [`BeanContainer::bind`].
+    /// The stack is looked up when the bean is created, creating it fails if the stack
+    /// is not defined.
+    pub fn with_stack(self, name: &str) -> Self {
+        let name = name.to_string();
+        self.wrap_factory(|factory| {
+            let name = name.clone();
+            Arc::new(move |deps: &mut Dependencies| {
+                let instance = factory.create(deps)?;
+                let stack = deps
+                    .container()
+                    .decorator_stacks
+                    .read()
+                    .unwrap()
+                    .get(&name)
+                    .cloned()
+                    .ok_or_else(|| {
+                        BeanError::Config(format!("Decorator stack '{}' is not defined", name))
+                    })?;
+                stack(instance, deps)
+            }) as Arc<dyn BeanFactory>
+        });
+        self
+    }
+}
@@ -0,0 +1,143 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use crate::bean::{
+    BeanContainer, BeanDef, BeanFactory, Dependencies, Identifier, Registration, Scope,
+};
+use crate::error::BeanError;
+
+thread_local! {
+    // Lookups delegated to other containers on this thread, by container id, innermost last
+    static BRIDGING: RefCell<Vec<(u64, Identifier)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Pops the delegated lookup, even if a factory panics
+struct Bridging;
+
+impl Drop for Bridging {
+    fn drop(&mut self) {
+        BRIDGING.with(|bridging| bridging.borrow_mut().pop());
+    }
+}
+
+/// Container embedded in another one, see [`BeanContainer::embed`]
+///
+/// Its beans stay private to it unless exported. An exported bean is resolved by the
+/// embedded container, which owns its instance and lifecycle.
+pub struct Embedding<'a> {
+    host: &'a BeanContainer,
+    embedded: Arc<BeanContainer>,
+}
+
+impl Embedding<'_> {
+    /// Expose the embedded bean `T` in the host container, by type
+    #[track_caller]
+    pub fn export<T: Any + Send + Sync + 'static>(self) -> Result<Self, BeanError> {
+        self.host.bridge::<T>(&self.embedded)?;
+        Ok(self)
+    }
+
+    /// Expose the embedded bean `T` named `name` in the host container, under the same name
+    ///
+    /// Exports leading back to a lookup in progress fail with `CircularDependency`.
+    #[track_caller]
+    pub fn export_named<T: Any + Send + Sync + 'static>(
+        self,
+        name: &str,
+    ) -> Result<Self, BeanError> {
+        let embedded = self.embedded.clone();
+        let target = name.to_string();
+        let factory: Arc<dyn BeanFactory> = Arc::new(move |_deps: &mut Dependencies| {
+            let instance = embedded.resolve_bridged::<T>(Some(&target))?;
+            Ok(instance as Arc<dyn Any + Send + Sync>)
+        });
+        self.host
+            .register_def(BeanDef::from_factory::<T>(Scope::Prototype, factory).named(name))?;
+        Ok(self)
+    }
+
+    /// The embedded container
+    pub fn container(&self) -> &Arc<BeanContainer> {
+        &self.embedded
+    }
+}
+
+impl BeanContainer {
+    /// Register a whole container as the bean `name` of type `Arc<BeanContainer>`, e.g. the
+    /// pre-wired container of a vendored library
+    ///
+    /// Select the beans resolvable from this container with [`Embedding::export`]
+    #[track_caller]
+    pub fn embed(
+        &self,
+        name: &str,
+        embedded: Arc<BeanContainer>,
+    ) -> Result<Embedding<'_>, BeanError> {
+        let instance = embedded.clone();
+        self.register_named::<Arc<BeanContainer>, _>(name, Scope::Singleton, move |_deps| {
+            Ok(instance.clone())
+        })?;
+        Ok(Embedding {
+            host: self,
+            embedded,
+        })
+    }
+
+    /// Register in this container a bean `T` whose resolution delegates to `other`
+    ///
+    /// The instance is owned by `other`, so its singleton is shared by both containers.
+    /// Bridges leading back to a lookup in progress, e.g. the same type bridged back and forth
+    /// between two containers, fail with `CircularDependency`.
+    #[track_caller]
+    pub fn bridge<T: Any + Send + Sync + 'static>(
+        &self,
+        other: &Arc<BeanContainer>,
+    ) -> Result<Registration<'_>, BeanError> {
+        let other = other.clone();
+        let factory: Arc<dyn BeanFactory> = Arc::new(move |_deps: &mut Dependencies| {
+            let instance = other.resolve_bridged::<T>(None)?;
+            Ok(instance as Arc<dyn Any + Send + Sync>)
+        });
+        // Nothing cached on this side, `other` decides the lifecycle
+        self.register_def(BeanDef::from_factory::<T>(Scope::Prototype, factory))
+    }
+
+    /// Get a bean on behalf of a bridge from another container, see [`bridge`](Self::bridge)
+    ///
+    /// Fails with `CircularDependency` if the lookup is already in progress on this thread,
+    /// i.e. bridges lead back to it
+    pub(crate) fn resolve_bridged<T: Any + Send + Sync + 'static>(
+        &self,
+        name: Option<&str>,
+    ) -> Result<Arc<T>, BeanError> {
+        let id = match name {
+            Some(name) => self.named_id(name),
+            None => Identifier::type_spec::<T>(),
+        };
+        let entry = (self.container_id(), id);
+        let cycle = BRIDGING.with(|bridging| {
+            let bridging = bridging.borrow();
+            let start = bridging.iter().position(|e| *e == entry)?;
+            let path: Vec<String> = bridging[start..]
+                .iter()
+                .chain([&entry])
+                .map(|(container, id)| format!("{} in container {}", id, container))
+                .collect();
+            Some(path.join(" -> "))
+        });
+        if let Some(path) = cycle {
+            return Err(BeanError::CircularDependency(path));
+        }
+        BRIDGING.with(|bridging| bridging.borrow_mut().push(entry));
+        let _bridging = Bridging;
+        self.try_get::<T>(name)
+    }
+
+    /// Container embedded as the bean `name`, see [`embed`](Self::embed)
+    pub fn embedded(&self, name: &str) -> Option<Arc<BeanContainer>> {
+        self.try_get::<Arc<BeanContainer>>(Some(name))
+            .ok()
+            .map(|embedded| Arc::clone(&*embedded))
+    }
+}
@@ -0,0 +1,195 @@
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::bean::{BeanContainer, Registration, Scope};
+use crate::config::{Config, EnvSource};
+use crate::error::BeanError;
+
+/// Facts about the environment the container runs in
+///
+/// Resolving `Environment` from a container which has none registers one describing the
+/// current process, with the container's active profiles and properties read from
+/// environment variables. Register another one, e.g. in tests, to override it.
+#[derive(Clone)]
+pub struct Environment {
+    profiles: Vec<String>,
+    config: Config,
+    hostname: String,
+    pid: u32,
+    started_at: SystemTime,
+}
+
+impl Environment {
+    /// Environment of the current process, without active profiles
+    ///
+    /// The start time is the time the environment is created
+    pub fn system() -> Self {
+        Environment {
+            profiles: Vec::new(),
+            config: Config::new().with_source(EnvSource::new()),
+            hostname: system_hostname(),
+            pid: std::process::id(),
+            started_at: SystemTime::now(),
+        }
+    }
+
+    pub fn with_profiles(mut self, profiles: &[&str]) -> Self {
+        self.profiles = profiles.iter().map(|p| p.to_string()).collect();
+        self
+    }
+
+    /// Replace the sources of properties
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_hostname(mut self, hostname: &str) -> Self {
+        self.hostname = hostname.to_string();
+        self
+    }
+
+    pub fn with_pid(mut self, pid: u32) -> Self {
+        self.pid = pid;
+        self
+    }
+
+    pub fn with_started_at(mut self, started_at: SystemTime) -> Self {
+        self.started_at = started_at;
+        self
+    }
+
+    pub fn active_profiles(&self) -> &[String] {
+        &self.profiles
+    }
+
+    pub fn is_profile_active(&self, profile: &str) -> bool {
+        self.profiles.iter().any(|p| p == profile)
+    }
+
+    /// Value of a property, e.g. `db.url`
+    pub fn property(&self, key: &str) -> Option<String> {
+        self.config.get(key)
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn hostname(&self) -> &str {
+        &self.hostname
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub fn started_at(&self) -> SystemTime {
+        self.started_at
+    }
+}
+
+/// Build and runtime metadata of the application, e.g. for health endpoints and logs
+///
+/// Build it with [`app_info!`](crate::app_info) in the application crate and register it with
+/// [`BeanContainer::register_app_info`]. Resolving `AppInfo` from a container which has none
+/// registers one with unknown name and version.
+#[derive(Debug, Clone)]
+pub struct AppInfo {
+    name: String,
+    version: String,
+    git_sha: Option<String>,
+    built_at: SystemTime,
+    started_at: Instant,
+}
+
+impl AppInfo {
+    pub fn new(name: &str, version: &str) -> Self {
+        AppInfo {
+            name: name.to_string(),
+            version: version.to_string(),
+            git_sha: None,
+            built_at: SystemTime::now(),
+            started_at: Instant::now(),
+        }
+    }
+
+    pub fn with_git_sha(mut self, git_sha: &str) -> Self {
+        self.git_sha = Some(git_sha.to_string());
+        self
+    }
+
+    /// Crate name, `CARGO_PKG_NAME` of the application crate
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Crate version, `CARGO_PKG_VERSION` of the application crate
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Git sha the application was compiled from, if `GIT_SHA` was set at compile time
+    pub fn git_sha(&self) -> Option<&str> {
+        self.git_sha.as_deref()
+    }
+
+    /// Time the container was built
+    pub fn built_at(&self) -> SystemTime {
+        self.built_at
+    }
+
+    /// Instant the container was built
+    pub fn started_at(&self) -> Instant {
+        self.started_at
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+}
+
+fn system_hostname() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
+impl BeanContainer {
+    /// Register the default [`Environment`], unless one is already registered
+    pub(crate) fn provide_environment(&self) -> Result<(), BeanError> {
+        let profiles = self.active_profiles();
+        self.register::<Environment, _>(Scope::Singleton, move |_deps| {
+            let profiles: Vec<&str> = profiles.iter().map(String::as_str).collect();
+            Ok(Environment::system().with_profiles(&profiles))
+        })?;
+        Ok(())
+    }
+}
+
+impl BeanContainer {
+    /// Register the application metadata, timestamped with the time the container was built
+    ///
+    /// ```text
+    /// container.register_app_info(rs_bean::app_info!())?;
+    /// ```
+    #[track_caller]
+    pub fn register_app_info(&self, info: AppInfo) -> Result<Registration<'_>, BeanError> {
+        let (built_at, started_at) = self.created_at();
+        let info = AppInfo {
+            built_at,
+            started_at,
+            ..info
+        };
+        self.register::<AppInfo, _>(Scope::Singleton, move |_deps| Ok(info.clone()))
+    }
+
+    /// Register an [`AppInfo`] with unknown name and version, unless one is already registered
+    pub(crate) fn provide_app_info(&self) -> Result<(), BeanError> {
+        self.register_app_info(AppInfo::new("unknown", "unknown"))?;
+        Ok(())
+    }
+}
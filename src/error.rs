@@ -0,0 +1,217 @@
+#[cfg(feature = "lifecycle")]
+use std::any::Any;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::bean::BeanId;
+
+/// Error of the container APIs and factories
+///
+/// Factories can return their own failures with `?` or `.into()` from a `String`, a `&str` or a
+/// boxed error, which become [`BeanError::Factory`]; [`BeanError::factory`] keeps any other error
+/// as the source. Errors of dependencies propagate unchanged, so callers can match on the kind of
+/// the root cause.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BeanError {
+    /// No bean registered for the type or name
+    NotFound(BeanId),
+    /// A bean is already registered for the type or name
+    AlreadyRegistered(BeanId),
+    /// Dependency path closing the cycle, e.g. `Bean(a) -> Bean(b) -> Bean(a)`
+    CircularDependency(String),
+    /// Dependency chain longer than the limit
+    TooDeep(usize),
+    /// The instance is not of the requested type
+    DowncastFailed(&'static str),
+    /// The bean is scoped and its scope is not active on the current thread
+    ScopeInactive { scope: &'static str, bean: BeanId },
+    /// The scope must be entered inside its declared parent scope, which is not active
+    ScopeNotNested {
+        scope: &'static str,
+        parent: &'static str,
+    },
+    /// The bean is internal to a module the requester doesn't belong to, or is registered
+    /// internal without a module
+    Internal {
+        bean: BeanId,
+        module: Option<String>,
+    },
+    /// A guard of the bean denied the resolution
+    Denied { bean: BeanId, reason: String },
+    /// Conflicting definitions found by [`ContainerBuilder`](crate::builder::ContainerBuilder)
+    Conflicts(Vec<String>),
+    /// Missing or invalid configuration
+    Config(String),
+    /// The bean's factory reached its invocation rate limit, see
+    /// [`Registration::creation_rate_limit`](crate::bean::Registration::creation_rate_limit), or
+    /// no creation permit freed up in time, see
+    /// [`Registration::max_concurrent_creations_within`](crate::bean::Registration::max_concurrent_creations_within)
+    RateLimited { bean: BeanId, retry_after: Duration },
+    /// Every instance of the bean's pool is checked out, see
+    /// [`Scope::Pooled`](crate::bean::Scope::Pooled)
+    PoolExhausted { bean: BeanId, size: usize },
+    /// The container was dropped
+    ContainerDropped,
+    /// The container is sealed, no bean can be registered anymore
+    Sealed,
+    /// Failure of the operating system, e.g. spawning a thread or watching a file
+    Io(String),
+    /// Failure returned by a factory, with the error it was built from if any
+    Factory {
+        message: String,
+        source: Option<ErrorSource>,
+    },
+}
+
+/// Error a [`BeanError::Factory`] was built from, shared by the clones of the failure
+///
+/// Two sources are equal if they are the same error.
+#[derive(Clone)]
+pub struct ErrorSource(Arc<dyn Error + Send + Sync>);
+
+impl Deref for ErrorSource {
+    type Target = dyn Error + Send + Sync;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+impl Debug for ErrorSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&*self.0, f)
+    }
+}
+
+impl PartialEq for ErrorSource {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for ErrorSource {}
+
+impl Display for BeanError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BeanError::NotFound(bean) => write!(f, "Bean not found: {}", bean),
+            BeanError::AlreadyRegistered(bean) => write!(f, "Bean already registered: {}", bean),
+            BeanError::CircularDependency(path) => {
+                write!(f, "Circular dependency detected: {}", path)
+            }
+            BeanError::TooDeep(max) => write!(f, "Dependency chain too deep (>{})", max),
+            BeanError::DowncastFailed(type_name) => {
+                write!(f, "Type downcast failed: {}", type_name)
+            }
+            BeanError::ScopeInactive { scope, bean } => {
+                write!(f, "Scope '{}' is not active for {}", scope, bean)
+            }
+            BeanError::ScopeNotNested { scope, parent } => write!(
+                f,
+                "Scope '{}' must be entered inside scope '{}'",
+                scope, parent
+            ),
+            BeanError::Internal {
+                bean,
+                module: Some(module),
+            } => write!(f, "Bean {} is internal to module {}", bean, module),
+            BeanError::Internal { bean, module: None } => {
+                write!(f, "Bean {} is internal but belongs to no module", bean)
+            }
+            BeanError::Denied { bean, reason } => {
+                write!(f, "Access to {} denied: {}", bean, reason)
+            }
+            BeanError::RateLimited { bean, retry_after } => write!(
+                f,
+                "Creation rate limit of {} reached, retry after {:?}",
+                bean, retry_after
+            ),
+            BeanError::PoolExhausted { bean, size } => {
+                write!(f, "All {} instances of {} are checked out", size, bean)
+            }
+            BeanError::Conflicts(beans) => {
+                write!(f, "Conflicting bean definitions: {}", beans.join(", "))
+            }
+            BeanError::Config(message)
+            | BeanError::Io(message)
+            | BeanError::Factory { message, .. } => f.write_str(message),
+            BeanError::ContainerDropped => f.write_str("Container was dropped"),
+            BeanError::Sealed => f.write_str("Container is sealed"),
+        }
+    }
+}
+
+impl BeanError {
+    /// Failure of a factory caused by `error`, kept as its [`source`](Error::source)
+    pub fn factory<E: Error + Send + Sync + 'static>(error: E) -> Self {
+        BeanError::Factory {
+            message: error.to_string(),
+            source: Some(ErrorSource(Arc::new(error))),
+        }
+    }
+
+    /// Failure of `what` which panicked with `payload`
+    #[cfg(feature = "lifecycle")]
+    pub(crate) fn panicked(what: &str, payload: &(dyn Any + Send)) -> Self {
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("non-string payload");
+        BeanError::from(format!("{} panicked: {}", what, message))
+    }
+
+    /// Bean the error is about, if it is about a single one
+    pub fn bean(&self) -> Option<BeanId> {
+        match self {
+            BeanError::NotFound(bean)
+            | BeanError::AlreadyRegistered(bean)
+            | BeanError::ScopeInactive { bean, .. }
+            | BeanError::Internal { bean, .. }
+            | BeanError::Denied { bean, .. }
+            | BeanError::RateLimited { bean, .. }
+            | BeanError::PoolExhausted { bean, .. } => Some(*bean),
+            _ => None,
+        }
+    }
+}
+
+impl Error for BeanError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BeanError::Factory {
+                source: Some(source),
+                ..
+            } => Some(&*source.0),
+            _ => None,
+        }
+    }
+}
+
+impl From<String> for BeanError {
+    fn from(message: String) -> Self {
+        BeanError::Factory {
+            message,
+            source: None,
+        }
+    }
+}
+
+impl From<&str> for BeanError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl From<Box<dyn Error + Send + Sync>> for BeanError {
+    fn from(error: Box<dyn Error + Send + Sync>) -> Self {
+        BeanError::Factory {
+            message: error.to_string(),
+            source: Some(ErrorSource(Arc::from(error))),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_core::Stream;
+use tokio::sync::mpsc;
+
+use crate::bean::{BeanContainer, BeanId, Scope};
+use crate::observer::BeanObserver;
+#[cfg(feature = "lifecycle")]
+use crate::shutdown::BeanShutdown;
+
+/// Events buffered per stream, later events are dropped until the stream catches up
+pub const EVENT_BUFFER: usize = 1024;
+
+/// Container activity yielded by [`BeanContainer::events`]
+#[derive(Debug, Clone)]
+pub enum ContainerEvent {
+    Registered {
+        bean: BeanId,
+        scope: Scope,
+    },
+    Created {
+        bean: BeanId,
+        scope: Scope,
+        elapsed: Duration,
+    },
+    Failed {
+        bean: BeanId,
+        path: String,
+        error: String,
+    },
+    /// The bean and its dependents in `invalidated` dropped their instance
+    Refreshed {
+        bean: BeanId,
+        invalidated: Vec<BeanId>,
+    },
+    /// Progress of the shutdown, one event per disposed bean
+    #[cfg(feature = "lifecycle")]
+    Disposed(BeanShutdown),
+}
+
+/// Observer forwarding events to the open streams
+#[derive(Clone, Default)]
+pub(crate) struct EventHub {
+    senders: Arc<Mutex<Vec<mpsc::Sender<ContainerEvent>>>>,
+}
+
+impl EventHub {
+    fn subscribe(&self) -> ContainerEvents {
+        let (sender, receiver) = mpsc::channel(EVENT_BUFFER);
+        self.senders.lock().unwrap().push(sender);
+        ContainerEvents { receiver }
+    }
+
+    fn publish(&self, event: impl FnOnce() -> ContainerEvent) {
+        let mut senders = self.senders.lock().unwrap();
+        senders.retain(|sender| !sender.is_closed());
+        if senders.is_empty() {
+            return;
+        }
+        let event = event();
+        for sender in senders.iter() {
+            // Full: the stream lags behind, drop the event
+            let _ = sender.try_send(event.clone());
+        }
+    }
+}
+
+impl BeanObserver for EventHub {
+    fn on_registered(&self, bean: BeanId, scope: Scope) {
+        self.publish(|| ContainerEvent::Registered { bean, scope });
+    }
+
+    fn on_created(&self, bean: BeanId, scope: Scope, elapsed: Duration) {
+        self.publish(|| ContainerEvent::Created {
+            bean,
+            scope,
+            elapsed,
+        });
+    }
+
+    fn on_failed(&self, bean: BeanId, path: &str, error: &str) {
+        self.publish(|| ContainerEvent::Failed {
+            bean,
+            path: path.to_string(),
+            error: error.to_string(),
+        });
+    }
+
+    fn on_refreshed(&self, bean: BeanId, invalidated: &[BeanId]) {
+        self.publish(|| ContainerEvent::Refreshed {
+            bean,
+            invalidated: invalidated.to_vec(),
+        });
+    }
+
+    #[cfg(feature = "lifecycle")]
+    fn on_disposed(&self, shutdown: &BeanShutdown) {
+        self.publish(|| ContainerEvent::Disposed(shutdown.clone()));
+    }
+}
+
+/// Stream of container activity, see [`BeanContainer::events`]
+pub struct ContainerEvents {
+    receiver: mpsc::Receiver<ContainerEvent>,
+}
+
+impl ContainerEvents {
+    /// Next event, `None` once the container is dropped
+    pub async fn recv(&mut self) -> Option<ContainerEvent> {
+        self.receiver.recv().await
+    }
+}
+
+impl Stream for ContainerEvents {
+    type Item = ContainerEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<ContainerEvent>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl BeanContainer {
+    /// Stream of registrations, creations, failures, refreshes and shutdown progress from now on,
+    /// e.g. for an admin dashboard
+    ///
+    /// Each stream buffers up to [`EVENT_BUFFER`] events, later events are dropped for a stream
+    /// lagging behind. Events are published synchronously, like observers.
+    pub fn events(&self) -> ContainerEvents {
+        self.event_hub
+            .get_or_init(|| {
+                let hub = EventHub::default();
+                self.add_observer(hub.clone());
+                hub
+            })
+            .subscribe()
+    }
+}
@@ -0,0 +1,117 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use crate::bean::{BeanContainer, BeanDef, Dependencies, Dependency, Registration, Scope};
+use crate::error::BeanError;
+
+#[cfg(feature = "derive")]
+pub use rs_bean_derive::Injectable;
+
+type BuildFn<T, P> = dyn Fn(&mut Dependencies, P) -> Result<T, BeanError> + Send + Sync;
+
+/// Injectable builder of `T` taking caller-supplied parameters `P`
+///
+/// Registered with [`BeanContainer::register_factory`] and resolved like any other bean,
+/// e.g. `deps.get::<Factory<Report>>()`. This is the assisted-injection pattern:
+/// dependencies come from the container, parameters from the caller.
+pub struct Factory<T, P = ()> {
+    build: Arc<BuildFn<T, P>>,
+}
+
+impl<T, P> Factory<T, P> {
+    pub fn new<F>(build: F) -> Self
+    where
+        F: Fn(&mut Dependencies, P) -> Result<T, BeanError> + Send + Sync + 'static,
+    {
+        Factory {
+            build: Arc::new(build),
+        }
+    }
+
+    /// Build a new product
+    pub fn build(&self, deps: &mut Dependencies, params: P) -> Result<T, BeanError> {
+        (self.build)(deps, params)
+    }
+}
+
+impl<T, P> Clone for Factory<T, P> {
+    fn clone(&self) -> Self {
+        Factory {
+            build: self.build.clone(),
+        }
+    }
+}
+
+/// Bean built from its dependencies alone, see [`BeanContainer::register_injectable`]
+///
+/// With the `derive` feature, `#[derive(Injectable)]` implements it from the field types:
+/// `Arc<T>`, `Option<Arc<T>>` and `Vec<Arc<T>>` fields are resolved like `get`, `get_opt`
+/// and `get_all`, `#[inject(name = "...")]` resolves by name and `#[inject(default)]` uses
+/// `Default`.
+pub trait Injectable: Sized {
+    fn inject(deps: &mut Dependencies) -> Result<Self, BeanError>;
+
+    /// Dependencies `inject` requires, declared at registration, see [`BeanDef::depends_on`]
+    fn dependencies() -> Vec<Dependency> {
+        Vec::new()
+    }
+}
+
+impl BeanContainer {
+    /// Register `T` built by its [`Injectable`] implementation, declaring its dependencies
+    #[track_caller]
+    pub fn register_injectable<T>(&self, scope: Scope) -> Result<Registration<'_>, BeanError>
+    where
+        T: Injectable + Any + Send + Sync + 'static,
+    {
+        self.register_with_deps::<T, _>(scope, &T::dependencies(), T::inject)
+    }
+
+    /// Register a bean declaring the dependencies its factory resolves, e.g.
+    /// `&[Dependency::on::<Database>()]`
+    ///
+    /// Fails with `CircularDependency`, registering nothing, if the declared dependencies lead
+    /// back to the bean. Missing ones may be registered later, [`validate`](Self::validate)
+    /// reports those still missing.
+    #[track_caller]
+    pub fn register_with_deps<T, F>(
+        &self,
+        scope: Scope,
+        dependencies: &[Dependency],
+        factory: F,
+    ) -> Result<Registration<'_>, BeanError>
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(&mut Dependencies) -> Result<T, BeanError> + Send + Sync + 'static,
+    {
+        self.register_def(
+            BeanDef::new::<T, F>(scope, factory).with_dependencies(dependencies.iter().cloned()),
+        )
+    }
+
+    /// Register a singleton `Factory<T, P>` building products from caller-supplied parameters
+    #[track_caller]
+    pub fn register_factory<T, P, F>(&self, build: F) -> Result<Registration<'_>, BeanError>
+    where
+        T: 'static,
+        P: 'static,
+        F: Fn(&mut Dependencies, P) -> Result<T, BeanError> + Send + Sync + 'static,
+    {
+        let factory = Factory::new(build);
+        self.register::<Factory<T, P>, _>(Scope::Singleton, move |_deps| Ok(factory.clone()))
+    }
+
+    /// Register `T` as a prototype derived from its registered `Factory<T, P>`,
+    /// built with default parameters
+    #[track_caller]
+    pub fn register_product<T, P>(&self) -> Result<Registration<'_>, BeanError>
+    where
+        T: Any + Send + Sync + 'static,
+        P: Default + 'static,
+    {
+        self.register::<T, _>(Scope::Prototype, |deps| {
+            let factory = deps.get::<Factory<T, P>>()?;
+            factory.build(deps, P::default())
+        })
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::bean::{BeanContainer, BeanId, shortest_path};
+
+/// How an edge of the dependency graph is known
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeSource {
+    /// Declared at registration, e.g. with
+    /// [`Registration::depends_on`](crate::bean::Registration::depends_on)
+    Declared,
+    /// Captured while creating the dependent bean
+    Observed,
+    /// Declared and observed
+    Both,
+}
+
+/// Edge of the dependency graph, from a bean to one of its dependencies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DependencyEdge {
+    pub dependent: BeanId,
+    pub dependency: BeanId,
+    pub source: EdgeSource,
+    /// Whether the dependency is registered, here or in a parent container
+    pub registered: bool,
+}
+
+/// Snapshot of the dependency graph, see [`BeanContainer::dependency_graph`]
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph {
+    nodes: Vec<BeanId>,
+    edges: Vec<DependencyEdge>,
+    successors: HashMap<BeanId, Vec<BeanId>>,
+    predecessors: HashMap<BeanId, Vec<BeanId>>,
+}
+
+impl DependencyGraph {
+    /// Registered beans in registration order, then the dependencies which are not registered
+    /// here, in order of appearance
+    pub fn nodes(&self) -> &[BeanId] {
+        &self.nodes
+    }
+
+    /// Edges in registration order of the dependent beans
+    pub fn edges(&self) -> &[DependencyEdge] {
+        &self.edges
+    }
+
+    /// Direct dependencies of `bean`
+    pub fn dependencies(&self, bean: BeanId) -> &[BeanId] {
+        self.successors.get(&bean).map_or(&[], Vec::as_slice)
+    }
+
+    /// Beans depending directly on `bean`
+    pub fn dependents(&self, bean: BeanId) -> &[BeanId] {
+        self.predecessors.get(&bean).map_or(&[], Vec::as_slice)
+    }
+
+    /// Dependencies of `bean`, direct or not, nearest first
+    pub fn transitive_dependencies(&self, bean: BeanId) -> Vec<BeanId> {
+        reachable(bean, &self.successors)
+    }
+
+    /// Beans depending on `bean`, directly or not, nearest first, e.g. the beans affected by
+    /// its refresh
+    pub fn transitive_dependents(&self, bean: BeanId) -> Vec<BeanId> {
+        reachable(bean, &self.predecessors)
+    }
+
+    /// Beans no other bean depends on, in node order
+    pub fn roots(&self) -> Vec<BeanId> {
+        self.nodes
+            .iter()
+            .filter(|id| self.dependents(**id).is_empty())
+            .copied()
+            .collect()
+    }
+
+    /// Shortest dependency path from `from` to `to`, both included, if `from` depends on `to`
+    ///
+    /// With `from` as `to`, the shortest cycle through the bean.
+    pub fn path(&self, from: BeanId, to: BeanId) -> Option<Vec<BeanId>> {
+        shortest_path(
+            from,
+            |bean| self.dependencies(*bean).to_vec(),
+            |bean| *bean == to,
+        )
+    }
+}
+
+/// Nodes reachable from `start` through `edges`, breadth first, without `start`
+fn reachable(start: BeanId, edges: &HashMap<BeanId, Vec<BeanId>>) -> Vec<BeanId> {
+    let mut seen = HashSet::from([start]);
+    let mut found = Vec::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(bean) = queue.pop_front() {
+        for next in edges.get(&bean).into_iter().flatten() {
+            if seen.insert(*next) {
+                found.push(*next);
+                queue.push_back(*next);
+            }
+        }
+    }
+    found
+}
+
+impl BeanContainer {
+    /// Dependency graph of the registered beans, for tooling to traverse
+    ///
+    /// Nodes are beans, edges are the dependencies declared at registration and the ones
+    /// observed while creating beans, so the graph fills in as beans are created. It is a
+    /// snapshot, later registrations and creations don't change it.
+    pub fn dependency_graph(&self) -> DependencyGraph {
+        let (nodes, edges) = self.wiring_graph();
+        let mut graph = DependencyGraph {
+            nodes,
+            ..Default::default()
+        };
+        let mut nodes: HashSet<BeanId> = graph.nodes.iter().copied().collect();
+        for edge in edges {
+            let DependencyEdge {
+                dependent,
+                dependency,
+                ..
+            } = edge;
+            if nodes.insert(dependency) {
+                graph.nodes.push(dependency);
+            }
+            graph.edges.push(edge);
+            graph
+                .successors
+                .entry(dependent)
+                .or_default()
+                .push(dependency);
+            graph
+                .predecessors
+                .entry(dependency)
+                .or_default()
+                .push(dependent);
+        }
+        graph
+    }
+}
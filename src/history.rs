@@ -0,0 +1,208 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::bean::{BeanContainer, BeanId, BeanState};
+use crate::introspect::BeanInfo;
+
+/// Point at which the history recorder snapshots the container, see [`BeanContainer::record_history`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordPoint {
+    /// After each registration batch, e.g. one `register` or `register_all` call
+    Registrations,
+    /// After each `refresh`
+    Refreshes,
+    /// When a scope is entered
+    ScopeOpens,
+}
+
+/// What a snapshot was taken after
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HistoryEvent {
+    /// Beans registered by one batch
+    Registered(Vec<BeanId>),
+    /// Refreshed bean, its dependents were refreshed too
+    Refreshed(BeanId),
+    ScopeEntered(&'static str),
+    /// Taken with [`BeanContainer::snapshot`]
+    Manual(String),
+}
+
+impl Display for HistoryEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HistoryEvent::Registered(beans) => {
+                let beans: Vec<String> = beans.iter().map(|id| id.to_string()).collect();
+                write!(f, "registered {}", beans.join(", "))
+            }
+            HistoryEvent::Refreshed(bean) => write!(f, "refreshed {}", bean),
+            HistoryEvent::ScopeEntered(scope) => write!(f, "entered scope {}", scope),
+            HistoryEvent::Manual(label) => write!(f, "{}", label),
+        }
+    }
+}
+
+/// State of the container at one point of its history
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /// Position in the history, starting at 0 and counting dropped snapshots
+    pub seq: u64,
+    pub at: SystemTime,
+    pub event: HistoryEvent,
+    /// Registered beans, in registration order
+    pub beans: Vec<BeanInfo>,
+    /// Beans holding a cached instance
+    pub instances: Vec<BeanId>,
+}
+
+impl Snapshot {
+    /// Changes from an `earlier` snapshot to this one
+    pub fn changes_since(&self, earlier: &Snapshot) -> SnapshotDiff {
+        let find = |beans: &[BeanInfo], id: BeanId| beans.iter().find(|b| b.id == id).cloned();
+        let mut diff = SnapshotDiff::default();
+        for bean in &self.beans {
+            match find(&earlier.beans, bean.id) {
+                None => diff.registered.push(bean.id),
+                Some(before)
+                    if before.type_name != bean.type_name || before.scope != bean.scope =>
+                {
+                    diff.replaced.push(bean.id)
+                }
+                Some(before) if before.state != bean.state => {
+                    diff.state_changes.push((bean.id, before.state, bean.state))
+                }
+                Some(_) => {}
+            }
+        }
+        diff.removed = earlier
+            .beans
+            .iter()
+            .filter(|b| find(&self.beans, b.id).is_none())
+            .map(|b| b.id)
+            .collect();
+        diff.created = self
+            .instances
+            .iter()
+            .filter(|id| !earlier.instances.contains(id))
+            .copied()
+            .collect();
+        diff.released = earlier
+            .instances
+            .iter()
+            .filter(|id| !self.instances.contains(id))
+            .copied()
+            .collect();
+        diff
+    }
+}
+
+/// Difference between two snapshots, see [`Snapshot::changes_since`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    pub registered: Vec<BeanId>,
+    pub removed: Vec<BeanId>,
+    /// Registered again with another type or scope
+    pub replaced: Vec<BeanId>,
+    /// Bean with its state before and after
+    pub state_changes: Vec<(BeanId, BeanState, BeanState)>,
+    /// Instances cached since
+    pub created: Vec<BeanId>,
+    /// Instances dropped since, e.g. by a refresh
+    pub released: Vec<BeanId>,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == SnapshotDiff::default()
+    }
+}
+
+impl Display for SnapshotDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let list = |f: &mut Formatter<'_>, label: &str, beans: &[BeanId]| {
+            if beans.is_empty() {
+                return Ok(());
+            }
+            let beans: Vec<String> = beans.iter().map(|id| id.to_string()).collect();
+            writeln!(f, "{}: {}", label, beans.join(", "))
+        };
+        list(f, "registered", &self.registered)?;
+        list(f, "removed", &self.removed)?;
+        list(f, "replaced", &self.replaced)?;
+        for (bean, before, after) in &self.state_changes {
+            writeln!(f, "{}: {:?} -> {:?}", bean, before, after)?;
+        }
+        list(f, "created", &self.created)?;
+        list(f, "released", &self.released)
+    }
+}
+
+/// Bounded history of snapshots, oldest dropped first
+pub(crate) struct Recorder {
+    capacity: usize,
+    points: Vec<RecordPoint>,
+    snapshots: Mutex<(u64, VecDeque<Snapshot>)>,
+}
+
+impl BeanContainer {
+    /// Record a snapshot of the registry and the cached instances at the given points,
+    /// keeping the last `capacity` ones, see [`history`](Self::history)
+    ///
+    /// Meant for development, each snapshot copies the whole registry.
+    /// Only the first call has an effect.
+    pub fn record_history(&self, capacity: usize, points: &[RecordPoint]) {
+        self.recorder.get_or_init(|| Recorder {
+            capacity: capacity.max(1),
+            points: points.to_vec(),
+            snapshots: Mutex::new((0, VecDeque::new())),
+        });
+    }
+
+    /// Recorded snapshots, oldest first, empty unless [`record_history`](Self::record_history)
+    /// was called
+    ///
+    /// Step back through them with [`Snapshot::changes_since`], e.g. to find which
+    /// registration or refresh broke a resolution
+    pub fn history(&self) -> Vec<Snapshot> {
+        self.recorder
+            .get()
+            .map(|r| r.snapshots.lock().unwrap().1.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Record a snapshot now, whatever the configured points, if recording is enabled
+    pub fn snapshot(&self, label: &str) {
+        self.take_snapshot(HistoryEvent::Manual(label.to_string()));
+    }
+
+    /// Record a snapshot if `point` is configured
+    pub(crate) fn record(&self, point: RecordPoint, event: impl FnOnce() -> HistoryEvent) {
+        if let Some(recorder) = self.recorder.get()
+            && recorder.points.contains(&point)
+        {
+            self.take_snapshot(event());
+        }
+    }
+
+    fn take_snapshot(&self, event: HistoryEvent) {
+        let Some(recorder) = self.recorder.get() else {
+            return;
+        };
+        let beans = self.beans();
+        let instances = self.instances().into_iter().map(|(id, _)| id).collect();
+        let mut snapshots = recorder.snapshots.lock().unwrap();
+        let (next_seq, snapshots) = &mut *snapshots;
+        if snapshots.len() == recorder.capacity {
+            snapshots.pop_front();
+        }
+        snapshots.push_back(Snapshot {
+            seq: *next_seq,
+            at: SystemTime::now(),
+            event,
+            beans,
+            instances,
+        });
+        *next_seq += 1;
+    }
+}
@@ -0,0 +1,387 @@
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, HashSet};
+use std::hash::BuildHasher;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::bean::{BeanContainer, BeanId};
+use crate::error::BeanError;
+use crate::introspect::BeanInfo;
+
+// Interval at which the idle server checks whether it was stopped
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+// Largest request head read, bodies are ignored
+const MAX_REQUEST: usize = 8 * 1024;
+// Header carrying the token of the inspector, required by requests changing the container
+const TOKEN_HEADER: &str = "x-inspector-token";
+
+/// HTTP server exposing the container's introspection as JSON, see
+/// [`BeanContainer::serve_inspector`]
+///
+/// Stops when dropped.
+pub struct Inspector {
+    addr: SocketAddr,
+    token: Arc<str>,
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Inspector {
+    /// Address the server listens on, e.g. to find the port picked for `127.0.0.1:0`
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Random token to send in the `X-Inspector-Token` header of `POST` requests
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Stop serving and wait for the server thread to end
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stopped.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Inspector {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl BeanContainer {
+    /// Serve the introspection APIs as JSON over HTTP, for development environments
+    ///
+    /// - `GET /beans`: registered beans
+    /// - `GET /beans/{index}`: one bean, by [`BeanId::index`]
+    /// - `GET /beans/{index}/tree`: the bean's dependency tree, as observed during creation
+    /// - `POST /beans/{index}/refresh`: drop the bean's instance and its dependents', see
+    ///   [`refresh`](Self::refresh); requires the [`token`](Inspector::token) in the
+    ///   `X-Inspector-Token` header
+    /// - `GET /graph`: captured dependency edges, `GET /graph.dot` in Graphviz DOT with the
+    ///   `graph` feature
+    /// - `GET /summary`: bean counts, active profiles and startup warnings
+    ///
+    /// Reads are not authenticated: bind it to a loopback address. Requests from a browser page
+    /// of another origin are rejected. Connections are accepted on a dedicated thread and served
+    /// each on its own, these threads hold the container weakly.
+    pub fn serve_inspector(
+        self: &Arc<Self>,
+        addr: impl ToSocketAddrs,
+    ) -> Result<Inspector, BeanError> {
+        let io = |e: std::io::Error| BeanError::Io(format!("Failed to start inspector: {}", e));
+        let listener = TcpListener::bind(addr).map_err(io)?;
+        listener.set_nonblocking(true).map_err(io)?;
+        let addr = listener.local_addr().map_err(io)?;
+
+        let token: Arc<str> = random_token().into();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let container = Arc::downgrade(self);
+        let thread = {
+            let token = token.clone();
+            let stopped = stopped.clone();
+            thread::Builder::new()
+                .name("bean inspector".to_string())
+                .spawn(move || serve(listener, container, token, stopped))
+                .map_err(io)?
+        };
+        Ok(Inspector {
+            addr,
+            token,
+            stopped,
+            thread: Some(thread),
+        })
+    }
+}
+
+fn random_token() -> String {
+    // Each `RandomState` is seeded with random keys
+    let half = || RandomState::new().hash_one(0u8);
+    format!("{:016x}{:016x}", half(), half())
+}
+
+fn serve(
+    listener: TcpListener,
+    container: Weak<BeanContainer>,
+    token: Arc<str>,
+    stopped: Arc<AtomicBool>,
+) {
+    while !stopped.load(Ordering::Acquire) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let container = container.clone();
+                let token = token.clone();
+                // A slow or failed connection only affects its client
+                let _ = thread::Builder::new()
+                    .name("bean inspector client".to_string())
+                    .spawn(move || handle(stream, &container, &token));
+            }
+            // No pending connection, or a transient accept failure
+            Err(_) => thread::sleep(POLL_INTERVAL),
+        }
+    }
+}
+
+fn handle(
+    mut stream: TcpStream,
+    container: &Weak<BeanContainer>,
+    token: &str,
+) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") && head.len() < MAX_REQUEST {
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default();
+    let path = request_line.next().unwrap_or_default();
+    let headers: HashMap<String, &str> = lines
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim()))
+        .collect();
+
+    let response = if !same_origin(&headers) {
+        Response::error(403, "Cross-origin requests are not allowed")
+    } else if method != "GET" && headers.get(TOKEN_HEADER) != Some(&token) {
+        Response::error(403, "Missing or invalid X-Inspector-Token header")
+    } else {
+        match container.upgrade() {
+            Some(container) => route(&container, method, path),
+            None => Response::error(503, "Container dropped"),
+        }
+    };
+    let reason = match response.status {
+        200 => "OK",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Service Unavailable",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason,
+        response.content_type,
+        response.body.len()
+    )?;
+    stream.write_all(response.body.as_bytes())?;
+    stream.flush()
+}
+
+/// Whether the request comes from a page served by the inspector itself, or not from a browser
+///
+/// Browsers send the origin of the page along cross-origin requests, and of every `POST`.
+fn same_origin(headers: &HashMap<String, &str>) -> bool {
+    match headers.get("origin") {
+        Some(origin) => {
+            let host = origin
+                .strip_prefix("http://")
+                .or_else(|| origin.strip_prefix("https://"));
+            host.is_some() && host == headers.get("host").copied()
+        }
+        None => true,
+    }
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn json(body: String) -> Self {
+        Response {
+            status: 200,
+            content_type: "application/json",
+            body,
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Response {
+            status,
+            content_type: "application/json",
+            body: format!("{{\"error\":{}}}", quote(message)),
+        }
+    }
+}
+
+fn route(container: &BeanContainer, method: &str, path: &str) -> Response {
+    let segments: Vec<&str> = path
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+    let beans = container.beans();
+    let find = |index: &str| {
+        let index: u32 = index.parse().ok()?;
+        beans.iter().find(|b| b.id.index() == index)
+    };
+    match (method, segments.as_slice()) {
+        ("GET", ["beans"]) => Response::json(array(beans.iter().map(bean_json))),
+        ("GET", ["beans", index]) => match find(index) {
+            Some(bean) => Response::json(bean_json(bean)),
+            None => Response::error(404, "Bean not found"),
+        },
+        ("GET", ["beans", index, "tree"]) => match find(index) {
+            Some(bean) => {
+                let mut trees = HashMap::new();
+                Response::json(tree_json(bean.id, &beans, &mut HashSet::new(), &mut trees).0)
+            }
+            None => Response::error(404, "Bean not found"),
+        },
+        ("POST", ["beans", index, "refresh"]) => match find(index) {
+            Some(bean) => {
+                container.invalidate(bean.id.identifier().clone());
+                Response::json(format!("{{\"refreshed\":{}}}", id_json(bean.id)))
+            }
+            None => Response::error(404, "Bean not found"),
+        },
+        ("GET", ["graph"]) => Response::json(array(container.dependency_edges().into_iter().map(
+            |(dependent, dependency)| {
+                format!(
+                    "{{\"dependent\":{},\"dependency\":{}}}",
+                    id_json(dependent),
+                    id_json(dependency)
+                )
+            },
+        ))),
+        #[cfg(feature = "graph")]
+        ("GET", ["graph.dot"]) => Response {
+            status: 200,
+            content_type: "text/vnd.graphviz",
+            body: container.render_dot(),
+        },
+        #[cfg(feature = "lifecycle")]
+        ("GET", ["summary"]) => {
+            let summary = container.startup_summary();
+            Response::json(format!(
+                "{{\"beans\":{},\"singletons\":{},\"prototypes\":{},\"cached\":{},\"active_profiles\":{},\"warnings\":{}}}",
+                summary.beans,
+                summary.singletons,
+                summary.prototypes,
+                summary.cached,
+                array(summary.active_profiles.iter().map(|p| quote(p))),
+                array(summary.warnings.iter().map(|w| quote(w))),
+            ))
+        }
+        (_, ["beans" | "graph" | "graph.dot" | "summary", ..]) => {
+            Response::error(405, "Method not allowed")
+        }
+        _ => Response::error(404, "Not found"),
+    }
+}
+
+fn bean_json(bean: &BeanInfo) -> String {
+    let optional = |value: &Option<String>| value.as_deref().map(quote).unwrap_or("null".into());
+    format!(
+        "{{\"index\":{},\"name\":{},\"type\":{},\"scope\":{},\"state\":{},\"module\":{},\"profile\":{},\"description\":{},\"location\":{},\"tags\":{},\"dependencies\":{}}}",
+        bean.id.index(),
+        quote(&bean.name),
+        quote(bean.type_name),
+        quote(&format!("{:?}", bean.scope)),
+        quote(&format!("{:?}", bean.state)),
+        optional(&bean.module),
+        optional(&bean.profile),
+        optional(&bean.description),
+        optional(&bean.location),
+        array(bean.tags.iter().map(|t| quote(t))),
+        array(bean.dependencies.iter().map(|id| id_json(*id))),
+    )
+}
+
+/// Dependencies of `bean`, recursively, a bean already on the path is not expanded again
+///
+/// Returns whether the subtree was cut short by the path. Subtrees which weren't are the same
+/// wherever the bean appears, they are rendered once into `trees`.
+fn tree_json(
+    bean: BeanId,
+    beans: &[BeanInfo],
+    path: &mut HashSet<BeanId>,
+    trees: &mut HashMap<BeanId, String>,
+) -> (String, bool) {
+    if let Some(tree) = trees.get(&bean) {
+        return (tree.clone(), false);
+    }
+    let dependencies = beans
+        .iter()
+        .find(|b| b.id == bean)
+        .map(|b| b.dependencies.clone())
+        .unwrap_or_default();
+    let (children, cut) = if path.insert(bean) {
+        let mut cut = false;
+        let children = array(dependencies.into_iter().map(|dependency| {
+            let (tree, cut_short) = tree_json(dependency, beans, path, trees);
+            cut |= cut_short;
+            tree
+        }));
+        path.remove(&bean);
+        (children, cut)
+    } else {
+        ("[]".to_string(), true)
+    };
+    let tree = format!(
+        "{{\"index\":{},\"name\":{},\"dependencies\":{}}}",
+        bean.index(),
+        quote(&bean.to_string()),
+        children
+    );
+    if !cut {
+        trees.insert(bean, tree.clone());
+    }
+    (tree, cut)
+}
+
+fn id_json(id: BeanId) -> String {
+    format!(
+        "{{\"index\":{},\"name\":{}}}",
+        id.index(),
+        quote(&id.to_string())
+    )
+}
+
+fn array(items: impl Iterator<Item = String>) -> String {
+    format!("[{}]", items.collect::<Vec<_>>().join(","))
+}
+
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,333 @@
+use std::fmt::{Display, Formatter};
+
+use crate::bean::{BeanContainer, BeanId, BeanState, Scope};
+use crate::error::BeanError;
+#[cfg(all(feature = "introspection", feature = "lifecycle"))]
+use crate::once::InitializerInfo;
+
+/// Snapshot of a registered bean, see [`BeanContainer::beans`]
+#[derive(Debug, Clone)]
+pub struct BeanInfo {
+    pub id: BeanId,
+    pub name: String,
+    pub type_name: &'static str,
+    pub scope: Scope,
+    pub state: BeanState,
+    /// Set with [`Registration::describe`](crate::bean::Registration::describe)
+    pub description: Option<String>,
+    /// Dependencies observed while creating the bean
+    pub dependencies: Vec<BeanId>,
+    /// Set with [`BeanDef::profile`](crate::bean::BeanDef::profile)
+    pub profile: Option<String>,
+    /// Cargo cfg predicate the registration is compiled under, see [`register_cfg!`](crate::register_cfg)
+    pub gate: Option<String>,
+    /// Source location of the registration, as `file:line`
+    pub location: Option<String>,
+    /// Created when the container starts
+    pub eager: bool,
+    /// Set with [`Registration::phase`](crate::bean::Registration::phase)
+    pub phase: Option<String>,
+    /// Deprecation note
+    pub deprecated: Option<String>,
+    /// Failure tolerated by `initialize_all`
+    pub optional: bool,
+    /// Set with [`BeanDef::in_module`](crate::bean::BeanDef::in_module)
+    pub module: Option<String>,
+    /// Only resolvable from beans of its module
+    pub internal: bool,
+    /// Set with [`Registration::tag`](crate::bean::Registration::tag)
+    pub tags: Vec<String>,
+}
+
+/// Beans registered for the active profiles, see [`BeanContainer::manifest`]
+#[cfg(feature = "introspection")]
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    pub active_profiles: Vec<String>,
+    /// Beans in registration order
+    pub beans: Vec<BeanInfo>,
+    /// Beans left out of the build by their cfg predicate, with the predicate
+    pub gated_out: Vec<(String, String)>,
+    /// One-time initializers, in registration order
+    #[cfg(feature = "lifecycle")]
+    pub initializers: Vec<InitializerInfo>,
+}
+
+#[cfg(feature = "introspection")]
+impl Manifest {
+    /// Render the manifest as a markdown table, one row per bean
+    pub fn render_markdown(&self) -> String {
+        let mut out = String::new();
+        if !self.active_profiles.is_empty() {
+            out.push_str(&format!(
+                "Active profiles: {}\n\n",
+                self.active_profiles.join(", ")
+            ));
+        }
+        out.push_str("| Bean | Type | Scope | Profile | Cfg | Source | Description |\n");
+        out.push_str("| --- | --- | --- | --- | --- | --- | --- |\n");
+        for bean in &self.beans {
+            let cells = [
+                bean.name.clone(),
+                bean.type_name.to_string(),
+                format!("{:?}", bean.scope),
+                bean.profile.clone().unwrap_or_default(),
+                bean.gate.clone().unwrap_or_default(),
+                bean.location.clone().unwrap_or_default(),
+                bean.description.clone().unwrap_or_default(),
+            ];
+            let cells: Vec<String> = cells.iter().map(|c| escape_cell(c)).collect();
+            out.push_str(&format!("| {} |\n", cells.join(" | ")));
+        }
+        #[cfg(feature = "lifecycle")]
+        if !self.initializers.is_empty() {
+            out.push_str("\nOne-time initializers:\n\n");
+            for initializer in &self.initializers {
+                out.push_str(&format!("- {}", initializer.name));
+                if let Some(phase) = &initializer.phase {
+                    out.push_str(&format!(" (phase {})", phase));
+                }
+                out.push_str(&format!(
+                    ": {:?}, {}\n",
+                    initializer.state, initializer.location
+                ));
+            }
+        }
+        if !self.gated_out.is_empty() {
+            out.push_str("\nLeft out of this build:\n\n");
+            for (bean, cfg) in &self.gated_out {
+                out.push_str(&format!("- {} (requires `cfg({})`)\n", bean, cfg));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(feature = "introspection")]
+fn escape_cell(cell: &str) -> String {
+    cell.replace('|', "\\|").replace('\n', " ")
+}
+
+impl BeanContainer {
+    /// Graphviz DOT export of the beans and their captured dependencies,
+    /// descriptions are shown as tooltips
+    #[cfg(feature = "graph")]
+    pub fn render_dot(&self) -> String {
+        let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut out = String::from("digraph beans {\n");
+        for bean in self.beans() {
+            out.push_str(&format!(
+                "    {} [label={}",
+                quote(&bean.name),
+                quote(bean.id.name())
+            ));
+            if let Some(description) = &bean.description {
+                out.push_str(&format!(", tooltip={}", quote(description)));
+            }
+            out.push_str("];\n");
+        }
+        for (dependent, dependency) in self.dependency_edges() {
+            out.push_str(&format!(
+                "    {} -> {};\n",
+                quote(&dependent.to_string()),
+                quote(&dependency.to_string())
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Manifest of the beans registered for the active profiles, e.g. to document an environment
+    #[cfg(feature = "introspection")]
+    pub fn manifest(&self) -> Manifest {
+        Manifest {
+            active_profiles: self.active_profiles(),
+            beans: self.beans(),
+            gated_out: self.gated_out(),
+            #[cfg(feature = "lifecycle")]
+            initializers: self.initializers(),
+        }
+    }
+
+    /// Hash of the registered definitions: their ids and kind of id, types, scopes, tags,
+    /// profiles, modules, internal flags, priorities and declared dependencies
+    ///
+    /// Independent of the registration order, of instances and of captured dependencies, so
+    /// replicas of one build can cheaply assert identical wiring, and tests can snapshot it to
+    /// catch accidental registration changes. Type names are part of it, and those may change
+    /// between compiler versions.
+    pub fn wiring_hash(&self) -> u64 {
+        let mut definitions: Vec<Vec<u8>> = self
+            .wiring_definitions()
+            .into_iter()
+            .map(|(id, bean, priority, requires)| {
+                let mut bytes = WiringBytes::default();
+                let (kind, key) = id.wiring_key();
+                bytes.write(kind.as_bytes());
+                bytes.write(key.as_bytes());
+                bytes.write(bean.type_name.as_bytes());
+                match bean.scope {
+                    Scope::Singleton => bytes.write(b"singleton"),
+                    Scope::Prototype => bytes.write(b"prototype"),
+                    #[cfg(feature = "scopes")]
+                    Scope::Scoped(name) => {
+                        bytes.write(b"scoped");
+                        bytes.write(name.as_bytes());
+                    }
+                    #[cfg(feature = "scopes")]
+                    Scope::Cached(ttl) => {
+                        bytes.write(b"cached");
+                        bytes.write(&ttl.as_nanos().to_le_bytes());
+                    }
+                    #[cfg(feature = "scopes")]
+                    Scope::Pooled(size) => {
+                        bytes.write(b"pooled");
+                        bytes.write(&(size as u64).to_le_bytes());
+                    }
+                }
+                let mut tags: Vec<&str> = bean.tags.iter().map(String::as_str).collect();
+                tags.sort_unstable();
+                bytes.write(&(tags.len() as u64).to_le_bytes());
+                for tag in tags {
+                    bytes.write(tag.as_bytes());
+                }
+                bytes.write(bean.profile.as_deref().unwrap_or_default().as_bytes());
+                bytes.write(bean.module.as_deref().unwrap_or_default().as_bytes());
+                bytes.write(&[u8::from(bean.internal)]);
+                bytes.write(&priority.to_le_bytes());
+                let mut requires: Vec<(&str, &str)> = requires
+                    .iter()
+                    .map(|dependency| dependency.wiring_key())
+                    .collect();
+                requires.sort_unstable();
+                bytes.write(&(requires.len() as u64).to_le_bytes());
+                for (kind, key) in requires {
+                    bytes.write(kind.as_bytes());
+                    bytes.write(key.as_bytes());
+                }
+                bytes.0
+            })
+            .collect();
+        // Sorted on their whole encoding, so ties can't depend on the registration order
+        definitions.sort_unstable();
+        let mut hash = WiringHash::default();
+        for definition in &definitions {
+            hash.write(definition);
+        }
+        hash.0
+    }
+}
+
+/// Fields of a definition, each prefixed with its length so consecutive ones can't run into
+/// each other
+#[derive(Default)]
+struct WiringBytes(Vec<u8>);
+
+impl WiringBytes {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0
+            .extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+        self.0.extend_from_slice(bytes);
+    }
+}
+
+/// 64-bit FNV-1a, unlike `DefaultHasher` its output doesn't change between Rust releases
+struct WiringHash(u64);
+
+impl Default for WiringHash {
+    fn default() -> Self {
+        WiringHash(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl WiringHash {
+    /// Hash `bytes` prefixed with their length
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in (bytes.len() as u64).to_le_bytes().iter().chain(bytes) {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// Rule which matched a bean during resolution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchRule {
+    /// Requested by name
+    Name,
+    /// Registered by type
+    Type,
+    /// Requested by type, matched the unnamed alias of a named bean
+    UnnamedAlias,
+    /// Requested through the handle of an anonymous bean
+    Handle,
+}
+
+/// What resolving a bean would do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolutionDecision {
+    NotFound,
+    ReuseSingleton,
+    CreateSingleton,
+    /// Instance of a `Cached` bean not expired yet
+    ReuseCached,
+    /// Instance of a `Cached` bean missing or expired
+    CreateCached,
+    /// Idle instance of a `Pooled` bean
+    ReusePooled,
+    /// New instance of a `Pooled` bean, none is idle
+    CreatePooled,
+    /// Fails, every instance of the pool of the given size is checked out
+    PoolExhausted(usize),
+    CreatePrototype,
+    ReuseScoped(&'static str),
+    CreateScoped(&'static str),
+    /// Fails, the scope is not active on the current thread
+    ScopeInactive(&'static str),
+    /// Fails with the failure cached by the backoff
+    CachedFailure(BeanError),
+    /// Fails, the bean depends on itself
+    Circular,
+}
+
+/// One bean visited by [`BeanContainer::explain`]
+#[derive(Debug, Clone)]
+pub struct ResolutionStep {
+    pub bean: BeanId,
+    /// Distance from the requested bean
+    pub depth: usize,
+    /// `None` if the bean is not registered
+    pub rule: Option<MatchRule>,
+    pub scope: Option<Scope>,
+    pub profile: Option<String>,
+    pub decision: ResolutionDecision,
+    /// Whether the dependencies of a bean to create are known, i.e. were observed in a
+    /// previous creation
+    pub dependencies_known: bool,
+}
+
+/// Dry run of a resolution, in depth-first order
+#[derive(Debug, Clone)]
+pub struct ResolutionPlanReport {
+    pub steps: Vec<ResolutionStep>,
+}
+
+impl Display for ResolutionPlanReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for step in &self.steps {
+            write!(f, "{}{}", "  ".repeat(step.depth), step.bean)?;
+            if let Some(rule) = step.rule {
+                write!(f, " [{:?}]", rule)?;
+            }
+            if let Some(profile) = &step.profile {
+                write!(f, " (profile {})", profile)?;
+            }
+            write!(f, ": {:?}", step.decision)?;
+            if !step.dependencies_known {
+                write!(f, ", dependencies unknown")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
@@ -1 +1,78 @@
+#[cfg(feature = "modules")]
+pub mod access;
+#[cfg(feature = "async")]
+pub mod async_container;
 pub mod bean;
+#[cfg(feature = "introspection")]
+pub mod bench;
+#[cfg(feature = "wiring")]
+mod binding;
+#[cfg(feature = "modules")]
+pub mod builder;
+#[cfg(feature = "config")]
+pub mod clock;
+#[cfg(feature = "config")]
+pub mod config;
+#[cfg(feature = "wiring")]
+mod convert;
+#[cfg(feature = "modules")]
+pub mod decorator;
+#[cfg(feature = "modules")]
+pub mod embed;
+#[cfg(feature = "config")]
+pub mod environment;
+pub mod error;
+#[cfg(feature = "tokio")]
+pub mod events;
+#[cfg(feature = "wiring")]
+pub mod factory;
+#[cfg(feature = "graph")]
+pub mod graph;
+#[cfg(feature = "introspection")]
+pub mod history;
+#[cfg(feature = "inspector")]
+pub mod inspector;
+#[cfg(feature = "introspection")]
+pub mod introspect;
+#[cfg(feature = "scopes")]
+pub mod local;
+#[cfg(feature = "macros")]
+mod macros;
+#[cfg(feature = "modules")]
+pub mod module;
+#[cfg(feature = "naming")]
+pub mod name_key;
+#[cfg(feature = "observers")]
+pub mod observer;
+#[cfg(feature = "lifecycle")]
+pub mod once;
+#[cfg(feature = "lifecycle")]
+pub mod panic;
+#[cfg(feature = "scopes")]
+pub mod provider;
+#[cfg(feature = "scan")]
+pub mod scan;
+#[cfg(feature = "scopes")]
+pub mod scope;
+#[cfg(feature = "introspection")]
+pub mod seal;
+#[cfg(feature = "lifecycle")]
+pub mod service;
+#[cfg(feature = "lifecycle")]
+pub mod shutdown;
+#[cfg(feature = "lifecycle")]
+pub mod startup;
+#[cfg(feature = "tokio")]
+pub mod task_scope;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "introspection")]
+pub mod trace;
+#[cfg(feature = "reload")]
+pub mod transaction;
+#[cfg(feature = "macros")]
+pub mod tuple;
+#[cfg(feature = "introspection")]
+pub mod validate;
+#[cfg(feature = "reload")]
+pub mod watch;
@@ -0,0 +1,96 @@
+use std::any::{Any, TypeId};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use crate::bean::BeanContainer;
+use crate::error::BeanError;
+
+/// Beans resolved once and cached in a list owned by the view, see [`BeanContainer::enter_local`]
+///
+/// Lookups are a scan of the cached beans: no locks, no atomics, no reference counting.
+/// Bound to the thread which created it, so scoped beans stay those of its active scopes.
+pub struct LocalView<'a> {
+    container: &'a BeanContainer,
+    cache: Vec<(TypeId, Option<String>, Arc<dyn Any + Send + Sync>)>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<'a> LocalView<'a> {
+    /// Resolve `T` now and cache it for the lifetime of the view
+    pub fn with<T: Any + Send + Sync + 'static>(self) -> Result<Self, BeanError> {
+        self.resolve::<T>(None)
+    }
+
+    /// Resolve the named bean now and cache it for the lifetime of the view
+    pub fn with_named<T: Any + Send + Sync + 'static>(self, name: &str) -> Result<Self, BeanError> {
+        self.resolve::<T>(Some(name))
+    }
+
+    /// Get a cached bean by type
+    /// **NOTE**: panics if not resolved with [`with`](Self::with)
+    pub fn get<T: Any + Send + Sync + 'static>(&self) -> &T {
+        self.lookup(None).unwrap_or_else(|| {
+            panic!(
+                "Bean not cached in local view: {}",
+                std::any::type_name::<T>()
+            )
+        })
+    }
+
+    /// Get a cached named bean
+    /// **NOTE**: panics if not resolved with [`with_named`](Self::with_named)
+    pub fn get_named<T: Any + Send + Sync + 'static>(&self, name: &str) -> &T {
+        self.lookup(Some(name))
+            .unwrap_or_else(|| panic!("Bean not cached in local view: {}", name))
+    }
+
+    /// Get a cached bean by type, if resolved
+    pub fn try_get<T: Any + Send + Sync + 'static>(&self) -> Option<&T> {
+        self.lookup(None)
+    }
+
+    pub fn container(&self) -> &'a BeanContainer {
+        self.container
+    }
+
+    fn resolve<T: Any + Send + Sync + 'static>(
+        mut self,
+        name: Option<&str>,
+    ) -> Result<Self, BeanError> {
+        if self.lookup::<T>(name).is_none() {
+            let instance = self.container.try_get::<T>(name)?;
+            self.cache
+                .push((TypeId::of::<T>(), name.map(str::to_string), instance));
+        }
+        Ok(self)
+    }
+
+    fn lookup<T: Any + Send + Sync + 'static>(&self, name: Option<&str>) -> Option<&T> {
+        self.cache
+            .iter()
+            .find(|(type_id, n, _)| *type_id == TypeId::of::<T>() && n.as_deref() == name)
+            .and_then(|(_, _, instance)| instance.downcast_ref::<T>())
+    }
+}
+
+impl BeanContainer {
+    /// Borrow the container for a single-threaded hot loop, e.g. a game tick or a batch
+    ///
+    /// Beans are resolved once when added to the view, then looked up without touching the
+    /// container. Instances are frozen for the lifetime of the view: prototypes are created once,
+    /// and refreshes are not observed.
+    ///
+    /// ```text
+    /// let view = container.enter_local().with::<Physics>()?.with::<Renderer>()?;
+    /// for entity in &mut entities {
+    ///     view.get::<Physics>().step(entity);
+    /// }
+    /// ```
+    pub fn enter_local(&self) -> LocalView<'_> {
+        LocalView {
+            container: self,
+            cache: Vec::new(),
+            _not_send: PhantomData,
+        }
+    }
+}
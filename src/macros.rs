@@ -0,0 +1,131 @@
+/// Register a bean only if a cargo cfg predicate holds in the calling crate
+///
+/// The gating is recorded in the manifest either way: as the bean's `gate` when compiled in,
+/// or in [`Manifest::gated_out`](crate::introspect::Manifest::gated_out) when left out.
+/// The factory is not compiled when the predicate doesn't hold.
+///
+/// ```text
+/// register_cfg!(container, cfg(feature = "payments"), PaymentGateway, Scope::Singleton, |deps| {
+///     Ok(PaymentGateway::new(deps.get()?))
+/// })?;
+/// ```
+#[macro_export]
+macro_rules! register_cfg {
+    ($container:expr, cfg($($cfg:tt)*), $ty:ty, $scope:expr, $factory:expr $(,)?) => {{
+        #[cfg($($cfg)*)]
+        let registration = $container.register_def(
+            $crate::bean::BeanDef::new::<$ty, _>($scope, $factory).gated_by(stringify!($($cfg)*)),
+        );
+        #[cfg(not($($cfg)*))]
+        let registration = $container.register_gated_out(stringify!($ty), stringify!($($cfg)*));
+        registration
+    }};
+}
+
+/// [`AppInfo`](crate::environment::AppInfo) of the calling crate
+///
+/// Name and version come from its `CARGO_PKG_NAME` and `CARGO_PKG_VERSION`, the git sha from
+/// the `GIT_SHA` environment variable at compile time, if set (e.g. by a build script).
+///
+/// ```text
+/// container.register_app_info(rs_bean::app_info!())?;
+/// ```
+#[cfg(feature = "config")]
+#[macro_export]
+macro_rules! app_info {
+    () => {{
+        let info =
+            $crate::environment::AppInfo::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+        match option_env!("GIT_SHA") {
+            Some(sha) => info.with_git_sha(sha),
+            None => info,
+        }
+    }};
+}
+
+/// Resolve several beans from a container, binding each to a variable, with one
+/// error-propagation point: the first failure is returned with `?`
+///
+/// Used as a statement, in a function returning a `Result` whose error converts from
+/// [`BeanError`](crate::error::BeanError). Named beans take a string literal.
+///
+/// ```text
+/// resolve!(container => db: Database, users: UserService, orders named "eu": OrderService);
+/// ```
+#[macro_export]
+macro_rules! resolve {
+    ($container:expr => $($var:ident $(named $name:literal)? : $ty:ty),+ $(,)?) => {
+        let container = &$container;
+        let ($($var,)+) = (|| {
+            Ok::<_, $crate::error::BeanError>(($($crate::resolve!(@get container, $ty $(, $name)?),)+))
+        })()?;
+    };
+    (@get $container:ident, $ty:ty) => {
+        $container.try_get::<$ty>(None)?
+    };
+    (@get $container:ident, $ty:ty, $name:literal) => {
+        $container.try_get::<$ty>(Some($name))?
+    };
+}
+
+/// Build a container from a list of beans in one expression, evaluating to
+/// `Result<BeanContainer, BeanError>`
+///
+/// Each entry is a type, optionally `as` a name, then its scope and factory closure. Scopes are
+/// `singleton`, `prototype`, `scoped(name)`, `cached(ttl)` and `pooled(size)`. Factories capture by move, and
+/// beans are registered atomically, see [`register_all`](crate::bean::BeanContainer::register_all).
+///
+/// ```text
+/// let container = container! {
+///     Database => singleton |_| Ok(Database::new(URL)),
+///     Database as "replica" => singleton |_| Ok(Database::new(REPLICA_URL)),
+///     UserService => prototype |deps| Ok(UserService::new(deps.get()?)),
+///     Session => scoped("request") |deps| Ok(Session::new(deps.get()?)),
+/// }?;
+/// ```
+#[macro_export]
+macro_rules! container {
+    (@scope singleton) => {
+        $crate::bean::Scope::Singleton
+    };
+    (@scope prototype) => {
+        $crate::bean::Scope::Prototype
+    };
+    (@scope scoped($name:expr)) => {
+        $crate::bean::Scope::Scoped($name)
+    };
+    (@scope cached($ttl:expr)) => {
+        $crate::bean::Scope::Cached($ttl)
+    };
+    (@scope pooled($size:expr)) => {
+        $crate::bean::Scope::Pooled($size)
+    };
+    (@scope $($scope:tt)*) => {
+        compile_error!(concat!(
+            "unknown scope `",
+            stringify!($($scope)*),
+            "`, expected `singleton`, `prototype`, `scoped(name)`, `cached(ttl)` or `pooled(size)`"
+        ))
+    };
+    ($(
+        $ty:ty $(as $name:literal)? => $scope:ident $(($($arg:tt)*))?
+        |$($param:pat_param),*| $body:expr
+    ),* $(,)?) => {{
+        let container = $crate::bean::BeanContainer::new();
+        let registered = container
+            .register_all([$(
+                $crate::bean::BeanDef::new::<$ty, _>(
+                    $crate::container!(@scope $scope $(($($arg)*))?),
+                    move |$($param),*| $body,
+                )
+                $(.named($name))?
+            ),*])
+            .map(|_| ());
+        registered.map(|()| container)
+    }};
+    ($($tokens:tt)*) => {
+        compile_error!(
+            "expected entries like `Type => singleton |deps| ...` or `Type as \"name\" => prototype |deps| ...`, separated by commas"
+        )
+    };
+}
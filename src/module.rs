@@ -0,0 +1,128 @@
+use std::any::type_name;
+use std::cell::RefCell;
+
+use crate::access::ModuleToken;
+use crate::bean::{BeanContainer, BeanDef};
+use crate::error::BeanError;
+
+/// Reusable bundle of registrations, e.g. a database or a metrics module shipped by a library
+///
+/// Applications add it to their container with [`BeanContainer::install`]
+pub trait BeanModule {
+    fn configure(&self, container: &BeanContainer) -> Result<(), BeanError>;
+
+    /// Module the beans are put in, see [`BeanDef::in_module`](crate::bean::BeanDef::in_module),
+    /// defaults to the type name
+    fn name(&self) -> String {
+        type_name::<Self>().to_string()
+    }
+}
+
+/// Module being installed
+#[derive(Clone)]
+pub(crate) struct ModuleInstall {
+    container: u64,
+    pub(crate) name: String,
+    /// Its registrations replace existing definitions, see
+    /// [`BeanContainer::install_overriding`]
+    pub(crate) overriding: bool,
+}
+
+impl ModuleInstall {
+    /// Put a definition registered while installing in the module
+    pub(crate) fn adopt(&self, def: BeanDef) -> BeanDef {
+        let def = match def.module_name() {
+            Some(_) => def,
+            None => def.in_module(&self.name),
+        };
+        if self.overriding {
+            def.replacing()
+        } else {
+            def
+        }
+    }
+}
+
+thread_local! {
+    // Modules being installed on this thread, innermost last
+    static INSTALLING: RefCell<Vec<ModuleInstall>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Module being installed into the container on the current thread, if any
+pub(crate) fn installing(container: u64) -> Option<ModuleInstall> {
+    INSTALLING.with(|installing| {
+        installing
+            .borrow()
+            .iter()
+            .rev()
+            .find(|install| install.container == container)
+            .cloned()
+    })
+}
+
+/// Pops the module being installed, even if `configure` panics
+struct Installing;
+
+impl Installing {
+    /// A module installed by an overriding module overrides too
+    fn enter(container: u64, name: String, overriding: bool) -> Self {
+        let overriding = overriding || installing(container).is_some_and(|m| m.overriding);
+        INSTALLING.with(|installing| {
+            installing.borrow_mut().push(ModuleInstall {
+                container,
+                name,
+                overriding,
+            })
+        });
+        Installing
+    }
+}
+
+impl Drop for Installing {
+    fn drop(&mut self) {
+        INSTALLING.with(|installing| installing.borrow_mut().pop());
+    }
+}
+
+impl BeanContainer {
+    /// Run the module's registrations, putting the beans registered by type or name in the
+    /// module unless they declare one
+    ///
+    /// Not atomic: if `configure` fails, the beans it already registered stay registered.
+    /// Modules can install other modules.
+    pub fn install<M: BeanModule>(&self, module: M) -> Result<(), BeanError> {
+        self.install_module(module, false)
+    }
+
+    /// Same as [`install`](Self::install), except that the module's registrations replace the
+    /// existing definitions of the same type or name instead of failing
+    ///
+    /// Typically used in integration tests to swap parts of production modules for fakes,
+    /// installed after them. Modules installed by this one override too.
+    pub fn install_overriding<M: BeanModule>(&self, module: M) -> Result<(), BeanError> {
+        self.install_module(module, true)
+    }
+
+    fn install_module<M: BeanModule>(&self, module: M, overriding: bool) -> Result<(), BeanError> {
+        let name = module.name();
+        {
+            let _installing = Installing::enter(self.container_id(), name.clone(), overriding);
+            module.configure(self)?;
+        }
+        self.installed_modules.write().unwrap().push(name);
+        Ok(())
+    }
+
+    /// Token granting access to the internal beans of the module being installed on the current
+    /// thread, `None` outside of [`BeanModule::configure`]
+    ///
+    /// See [`get_internal`](Self::get_internal)
+    pub fn module_token(&self) -> Option<ModuleToken> {
+        installing(self.container_id()).map(|module| ModuleToken::new(&module.name))
+    }
+
+    /// Names of the modules installed successfully, a module installed by another one first
+    pub fn installed_modules(&self) -> Vec<String> {
+        self.installed_modules.read().unwrap().clone()
+    }
+}
@@ -0,0 +1,59 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use crate::bean::{BeanContainer, BeanDef, Dependencies, Registration, Scope};
+use crate::error::BeanError;
+
+/// Typed key of a bean name, e.g. `struct PrimaryDb;` with `NAME = "db.primary"`
+///
+/// Using a key instead of a string name at both registration and resolution sites
+/// lets the compiler catch typos
+pub trait BeanName {
+    const NAME: &'static str;
+}
+
+impl BeanDef {
+    /// Register with the name of key `K` instead of by type
+    pub fn named_key<K: BeanName>(self) -> Self {
+        self.named(K::NAME)
+    }
+}
+
+impl BeanContainer {
+    /// Register a bean with the name of key `K`, see [`BeanName`]
+    #[track_caller]
+    pub fn register_named_key<T, K, F>(
+        &self,
+        scope: Scope,
+        factory: F,
+    ) -> Result<Registration<'_>, BeanError>
+    where
+        T: Any + Send + Sync + 'static,
+        K: BeanName,
+        F: Fn(&mut Dependencies) -> Result<T, BeanError> + Send + Sync + 'static,
+    {
+        self.register_named::<T, F>(K::NAME, scope, factory)
+    }
+
+    /// Get the bean named by key `K`
+    /// **NOTE**: panics if bean not found
+    pub fn get_named_key<T: Any + Send + Sync + 'static, K: BeanName>(&self) -> Arc<T> {
+        self.get_named::<T>(K::NAME)
+    }
+
+    /// Get the bean named by key `K` without panicking
+    pub fn try_get_named_key<T: Any + Send + Sync + 'static, K: BeanName>(
+        &self,
+    ) -> Result<Arc<T>, BeanError> {
+        self.try_get::<T>(Some(K::NAME))
+    }
+}
+
+impl Dependencies<'_> {
+    /// Get the bean named by key `K`, see [`BeanName`]
+    pub fn get_named_key<T: Any + Send + Sync + 'static, K: BeanName>(
+        &mut self,
+    ) -> Result<Arc<T>, BeanError> {
+        self.get_named::<T>(Some(K::NAME))
+    }
+}
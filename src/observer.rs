@@ -0,0 +1,226 @@
+use std::time::Duration;
+
+use crate::bean::{BeanId, Scope};
+#[cfg(feature = "lifecycle")]
+use crate::shutdown::BeanShutdown;
+#[cfg(feature = "lifecycle")]
+use crate::startup::StartupSummary;
+
+/// Observer of container activity
+///
+/// All methods default to no-op, implement only the events of interest.
+/// Observers are invoked synchronously without holding container locks.
+/// Beans are passed as [`BeanId`], cheap to store and compare to correlate events.
+pub trait BeanObserver: Send + Sync {
+    /// A bean was registered
+    fn on_registered(&self, _bean: BeanId, _scope: Scope) {}
+
+    /// A bean instance was created by its factory
+    fn on_created(&self, _bean: BeanId, _scope: Scope, _elapsed: Duration) {}
+
+    /// A factory failed, `path` is the dependency path which led to the bean
+    fn on_failed(&self, _bean: BeanId, _path: &str, _error: &str) {}
+
+    /// A bean was refreshed, `invalidated` lists it and its dependents which dropped their
+    /// instance, see [`BeanContainer::refresh`](crate::bean::BeanContainer::refresh)
+    fn on_refreshed(&self, _bean: BeanId, _invalidated: &[BeanId]) {}
+
+    /// The disposal of a bean ended or timed out while shutting down, see
+    /// [`BeanContainer::shutdown_with`](crate::bean::BeanContainer::shutdown_with)
+    #[cfg(feature = "lifecycle")]
+    fn on_disposed(&self, _shutdown: &BeanShutdown) {}
+
+    /// The beans of a startup phase were created, see
+    /// [`BeanContainer::set_startup_phases`](crate::bean::BeanContainer::set_startup_phases)
+    #[cfg(feature = "lifecycle")]
+    fn on_phase_completed(&self, _phase: &str, _beans: &[BeanId], _elapsed: Duration) {}
+
+    /// A bean was used against its declared access pattern, see
+    /// [`Registration::single_consumer`](crate::bean::Registration::single_consumer) and
+    /// [`Registration::assert_sync_access`](crate::bean::Registration::assert_sync_access)
+    #[cfg(feature = "modules")]
+    fn on_access_violation(&self, _bean: BeanId, _violation: &str) {}
+
+    /// The container started, see [`BeanContainer::start`](crate::bean::BeanContainer::start)
+    #[cfg(feature = "lifecycle")]
+    fn on_started(&self, _summary: &StartupSummary) {}
+
+    /// A background service failed and is restarted, `attempt` counts from 1
+    #[cfg(feature = "lifecycle")]
+    fn on_service_restarted(&self, _bean: BeanId, _attempt: u32, _error: &str) {}
+
+    /// A background service failed and shuts down its group
+    #[cfg(feature = "lifecycle")]
+    fn on_service_escalated(&self, _bean: BeanId, _error: &str) {}
+}
+
+#[cfg(feature = "log")]
+pub use log_observer::LogObserver;
+
+#[cfg(feature = "log")]
+mod log_observer {
+    use std::time::Duration;
+
+    use log::{Level, log};
+
+    use super::BeanObserver;
+    use crate::bean::{BeanId, Scope};
+    #[cfg(feature = "lifecycle")]
+    use crate::shutdown::{BeanShutdown, DisposeOutcome};
+    #[cfg(feature = "lifecycle")]
+    use crate::startup::StartupSummary;
+
+    const TARGET: &str = "rs_bean";
+
+    /// Observer emitting container activity through the `log` crate
+    ///
+    /// By default registrations are logged at `debug`, singleton creations and the startup
+    /// summary at `info`, and failures at `error` with the dependency path.
+    pub struct LogObserver {
+        registration: Option<Level>,
+        creation: Option<Level>,
+        failure: Option<Level>,
+        prefix: Option<String>,
+    }
+
+    impl LogObserver {
+        pub fn new() -> Self {
+            LogObserver {
+                registration: Some(Level::Debug),
+                creation: Some(Level::Info),
+                failure: Some(Level::Error),
+                prefix: None,
+            }
+        }
+
+        /// Level of registration events, `None` to mute them
+        pub fn registration_level(mut self, level: Option<Level>) -> Self {
+            self.registration = level;
+            self
+        }
+
+        /// Level of singleton creation and startup events, `None` to mute them
+        ///
+        /// Creations of other scopes are logged at `trace`
+        pub fn creation_level(mut self, level: Option<Level>) -> Self {
+            self.creation = level;
+            self
+        }
+
+        /// Level of failure events, `None` to mute them
+        pub fn failure_level(mut self, level: Option<Level>) -> Self {
+            self.failure = level;
+            self
+        }
+
+        /// Only log beans whose name starts with the given prefix, e.g. `"myapp::"`
+        pub fn with_prefix(mut self, prefix: &str) -> Self {
+            self.prefix = Some(prefix.to_string());
+            self
+        }
+
+        fn accepts(&self, bean: BeanId) -> bool {
+            self.prefix
+                .as_ref()
+                .is_none_or(|p| bean.name().starts_with(p))
+        }
+    }
+
+    impl Default for LogObserver {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl BeanObserver for LogObserver {
+        fn on_registered(&self, bean: BeanId, scope: Scope) {
+            if let Some(level) = self.registration
+                && self.accepts(bean)
+            {
+                log!(target: TARGET, level, "Registered {} ({:?})", bean, scope);
+            }
+        }
+
+        fn on_created(&self, bean: BeanId, scope: Scope, elapsed: Duration) {
+            let level = match scope {
+                Scope::Singleton => self.creation,
+                _ => Some(Level::Trace),
+            };
+            if let Some(level) = level
+                && self.accepts(bean)
+            {
+                log!(target: TARGET, level, "Created {} ({:?}) in {:?}", bean, scope, elapsed);
+            }
+        }
+
+        fn on_failed(&self, bean: BeanId, path: &str, error: &str) {
+            if let Some(level) = self.failure
+                && self.accepts(bean)
+            {
+                log!(target: TARGET, level, "Failed to create {}: {} (path: {})", bean, error, path);
+            }
+        }
+
+        fn on_refreshed(&self, bean: BeanId, invalidated: &[BeanId]) {
+            if let Some(level) = self.registration
+                && self.accepts(bean)
+            {
+                log!(target: TARGET, level, "Refreshed {}, {} instances dropped", bean, invalidated.len());
+            }
+        }
+
+        #[cfg(feature = "lifecycle")]
+        fn on_disposed(&self, shutdown: &BeanShutdown) {
+            let level = match shutdown.outcome {
+                DisposeOutcome::Completed => Some(Level::Trace),
+                _ => self.failure,
+            };
+            if let Some(level) = level
+                && self.accepts(shutdown.bean)
+            {
+                log!(target: TARGET, level, "Disposal of {}: {:?}", shutdown.bean, shutdown.outcome);
+            }
+        }
+
+        #[cfg(feature = "lifecycle")]
+        fn on_phase_completed(&self, phase: &str, beans: &[BeanId], elapsed: Duration) {
+            if let Some(level) = self.creation {
+                log!(target: TARGET, level, "Startup phase {} completed, {} beans in {:?}", phase, beans.len(), elapsed);
+            }
+        }
+
+        #[cfg(feature = "modules")]
+        fn on_access_violation(&self, bean: BeanId, violation: &str) {
+            if let Some(level) = self.failure
+                && self.accepts(bean)
+            {
+                log!(target: TARGET, level, "Access violation on {}: {}", bean, violation);
+            }
+        }
+
+        #[cfg(feature = "lifecycle")]
+        fn on_started(&self, summary: &StartupSummary) {
+            if let Some(level) = self.creation {
+                log!(target: TARGET, level, "{}", summary);
+            }
+        }
+
+        #[cfg(feature = "lifecycle")]
+        fn on_service_restarted(&self, bean: BeanId, attempt: u32, error: &str) {
+            if let Some(level) = self.failure
+                && self.accepts(bean)
+            {
+                log!(target: TARGET, level, "Restarting service {} (attempt {}): {}", bean, attempt, error);
+            }
+        }
+
+        #[cfg(feature = "lifecycle")]
+        fn on_service_escalated(&self, bean: BeanId, error: &str) {
+            if let Some(level) = self.failure
+                && self.accepts(bean)
+            {
+                log!(target: TARGET, level, "Service {} failed, shutting down services: {}", bean, error);
+            }
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use std::panic::{self, AssertUnwindSafe, Location};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError, RwLock};
+use std::thread::{self, ThreadId};
+
+use crate::bean::BeanContainer;
+use crate::error::BeanError;
+
+type InitFn = dyn FnOnce() -> Result<(), BeanError> + Send;
+
+/// State of a one-time initializer, see [`BeanContainer::once`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InitializerState {
+    Pending,
+    Running,
+    Done,
+    /// Failed or panicked, later runs return the same error
+    Failed(BeanError),
+}
+
+/// One-time initializer registered with [`BeanContainer::once`]
+#[derive(Debug, Clone)]
+pub struct InitializerInfo {
+    pub name: String,
+    /// Startup phase it runs in, see [`OnceRegistration::phase`]
+    pub phase: Option<String>,
+    /// Source location of the registration, as `file:line`
+    pub location: String,
+    pub state: InitializerState,
+}
+
+enum Run {
+    Pending(Box<InitFn>),
+    Running(ThreadId),
+    Done(Result<(), BeanError>),
+}
+
+struct Initializer {
+    name: String,
+    phase: RwLock<Option<String>>,
+    location: &'static Location<'static>,
+    run: Mutex<Run>,
+    // Signaled when a run ends
+    ran: Condvar,
+}
+
+impl Initializer {
+    /// Run the initializer unless it already ran, waiting for a concurrent run
+    ///
+    /// It runs without the lock, so it can run other initializers; running itself fails with
+    /// `CircularDependency`.
+    fn run(&self) -> Result<(), BeanError> {
+        let mut run = self.state();
+        let init = loop {
+            match std::mem::replace(&mut *run, Run::Running(thread::current().id())) {
+                Run::Pending(init) => break init,
+                Run::Running(thread) => {
+                    *run = Run::Running(thread);
+                    if thread == thread::current().id() {
+                        return Err(BeanError::CircularDependency(format!(
+                            "Initializer({0}) -> Initializer({0})",
+                            self.name
+                        )));
+                    }
+                    run = self.ran.wait(run).unwrap_or_else(PoisonError::into_inner);
+                }
+                Run::Done(result) => {
+                    *run = Run::Done(result.clone());
+                    return result;
+                }
+            }
+        };
+        drop(run);
+        let result = panic::catch_unwind(AssertUnwindSafe(init)).unwrap_or_else(|payload| {
+            Err(BeanError::panicked(
+                &format!("Initializer {}", self.name),
+                &*payload,
+            ))
+        });
+        *self.state() = Run::Done(result.clone());
+        self.ran.notify_all();
+        result
+    }
+
+    /// Run state, the lock is never held while running so it can't be poisoned by a panic
+    /// of the initializer, recovered anyway
+    fn state(&self) -> MutexGuard<'_, Run> {
+        self.run.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn phase(&self) -> Option<String> {
+        self.phase.read().unwrap().clone()
+    }
+
+    fn info(&self) -> InitializerInfo {
+        let state = match &*self.state() {
+            Run::Pending(_) => InitializerState::Pending,
+            Run::Running(_) => InitializerState::Running,
+            Run::Done(Ok(())) => InitializerState::Done,
+            Run::Done(Err(e)) => InitializerState::Failed(e.clone()),
+        };
+        InitializerInfo {
+            name: self.name.clone(),
+            phase: self.phase(),
+            location: format!("{}:{}", self.location.file(), self.location.line()),
+            state,
+        }
+    }
+}
+
+/// One-time initializers of a container, in registration order
+#[derive(Default)]
+pub(crate) struct Initializers {
+    initializers: RwLock<Vec<Arc<Initializer>>>,
+}
+
+impl Initializers {
+    /// Initializers of the phase, `None` for those without a phase
+    pub(crate) fn run_phase(&self, phase: Option<&str>) -> Result<(), BeanError> {
+        self.run_matching(|i| i.phase().as_deref() == phase)
+    }
+
+    /// Initializers of a phase which is not in `phases`
+    pub(crate) fn run_undeclared(&self, phases: &[String]) -> Result<(), BeanError> {
+        self.run_matching(|i| i.phase().is_some_and(|p| !phases.contains(&p)))
+    }
+
+    /// Every initializer in the order of [`BeanContainer::start`]: those without a phase, of
+    /// each of `phases`, then of the undeclared phases, registration order within a phase
+    ///
+    /// Returns the failures with the name of the initializer, stops at the first one unless
+    /// `keep_going`.
+    pub(crate) fn run_all(&self, phases: &[String], keep_going: bool) -> Vec<(String, BeanError)> {
+        let mut ordered: Vec<Arc<Initializer>> = self.initializers.read().unwrap().clone();
+        ordered.sort_by_key(|i| match i.phase() {
+            None => 0,
+            Some(phase) => phases
+                .iter()
+                .position(|p| *p == phase)
+                .map_or(phases.len() + 1, |position| position + 1),
+        });
+        let mut failures = Vec::new();
+        for initializer in ordered {
+            if let Err(e) = initializer.run() {
+                failures.push((initializer.name.clone(), e));
+                if !keep_going {
+                    break;
+                }
+            }
+        }
+        failures
+    }
+
+    /// Run the matching initializers in registration order, stopping at the first failure
+    fn run_matching(&self, matches: impl Fn(&Initializer) -> bool) -> Result<(), BeanError> {
+        let matching: Vec<Arc<Initializer>> = self
+            .initializers
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|i| matches(i))
+            .cloned()
+            .collect();
+        matching.iter().try_for_each(|i| i.run())
+    }
+
+    pub(crate) fn infos(&self) -> Vec<InitializerInfo> {
+        let initializers = self.initializers.read().unwrap();
+        initializers.iter().map(|i| i.info()).collect()
+    }
+}
+
+/// Handle of a freshly registered initializer, see [`BeanContainer::once`]
+pub struct OnceRegistration<'a> {
+    container: &'a BeanContainer,
+    // `None` if an initializer of the same name was already registered
+    name: Option<String>,
+}
+
+impl OnceRegistration<'_> {
+    /// Run the initializer in the named startup phase, before the phase's beans, see
+    /// [`BeanContainer::set_startup_phases`]
+    pub fn phase(self, phase: &str) -> Self {
+        if let Some(name) = &self.name {
+            let initializers = self.container.initializers.initializers.read().unwrap();
+            if let Some(i) = initializers.iter().find(|i| i.name == *name) {
+                *i.phase.write().unwrap() = Some(phase.to_string());
+            }
+        }
+        self
+    }
+}
+
+impl BeanContainer {
+    /// Register a named one-time initializer, e.g. for a global side effect like installing a
+    /// tracing subscriber, instead of a `static INIT: Once` run in no particular order
+    ///
+    /// It runs once, at the latest when the container starts: initializers without a phase
+    /// run before any bean, the others at the beginning of their phase, see
+    /// [`start`](Self::start). [`initialize_all`](Self::initialize_all) runs them all before
+    /// the singletons. Code needing it earlier calls [`run_once`](Self::run_once). A panic
+    /// fails it like an error.
+    /// Only the first registration of a name has an effect. Listed in the manifest.
+    #[track_caller]
+    pub fn once<F>(&self, name: &str, init: F) -> OnceRegistration<'_>
+    where
+        F: FnOnce() -> Result<(), BeanError> + Send + 'static,
+    {
+        let location = Location::caller();
+        let mut initializers = self.initializers.initializers.write().unwrap();
+        let registered = !initializers.iter().any(|i| i.name == name);
+        if registered {
+            initializers.push(Arc::new(Initializer {
+                name: name.to_string(),
+                phase: RwLock::new(None),
+                location,
+                run: Mutex::new(Run::Pending(Box::new(init))),
+                ran: Condvar::new(),
+            }));
+        }
+        OnceRegistration {
+            container: self,
+            name: registered.then(|| name.to_string()),
+        }
+    }
+
+    /// Run the named initializer now unless it already ran, e.g. in a test relying on its
+    /// side effect without starting the container
+    ///
+    /// Returns its error if it failed, now or before, and waits if it is running on another
+    /// thread. An initializer running itself fails with [`BeanError::CircularDependency`].
+    pub fn run_once(&self, name: &str) -> Result<(), BeanError> {
+        let initializer = self
+            .initializers
+            .initializers
+            .read()
+            .unwrap()
+            .iter()
+            .find(|i| i.name == name)
+            .cloned()
+            .ok_or_else(|| BeanError::Config(format!("No initializer named {}", name)))?;
+        initializer.run()
+    }
+
+    /// Registered one-time initializers, in registration order
+    pub fn initializers(&self) -> Vec<InitializerInfo> {
+        self.initializers.infos()
+    }
+}
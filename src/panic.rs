@@ -0,0 +1,43 @@
+use std::any::Any;
+use std::panic::{self, PanicHookInfo};
+
+use crate::bean::{BeanContainer, Registration};
+use crate::error::BeanError;
+
+/// Bean reporting panics, e.g. a crash reporting client
+///
+/// Enabled per bean with [`Registration::panic_reporter`], called by the hook of
+/// [`BeanContainer::install_panic_hook`]
+pub trait PanicReporter: Send + Sync {
+    fn report(&self, info: &PanicHookInfo<'_>);
+}
+
+impl Registration<'_> {
+    /// Report panics through this bean once [`BeanContainer::install_panic_hook`] is called
+    pub fn panic_reporter<T: PanicReporter + Any>(self) -> Self {
+        self.upcast::<T, dyn PanicReporter>(|bean| bean)
+    }
+}
+
+impl BeanContainer {
+    /// Install a panic hook reporting panics through the first bean registered as
+    /// [`panic_reporter`](Registration::panic_reporter), then running the previous hook
+    ///
+    /// The reporter is resolved when installing, so a panic never creates beans, and the hook
+    /// keeps it alive. Fails without installing anything if it cannot be created. Without a
+    /// reporter, only the previous hook runs.
+    pub fn install_panic_hook(&self) -> Result<(), BeanError> {
+        let reporter = match self.upcast_candidates::<dyn PanicReporter>().first() {
+            Some(bean) => Some(self.resolve_as::<dyn PanicReporter>(*bean)?),
+            None => None,
+        };
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if let Some(reporter) = &reporter {
+                reporter.report(info);
+            }
+            previous(info);
+        }));
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+use std::any::Any;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::{Arc, OnceLock};
+
+use crate::bean::{BeanContainer, BeanId, BeanRef, Dependencies, Identifier, Registration, Scope};
+use crate::error::BeanError;
+
+/// Handle resolving `T` on demand, injectable into longer-lived beans
+///
+/// Registered with [`BeanContainer::register_provider`], each call to [`get`](Self::get)
+/// resolves `T` again: a prototype yields a fresh instance, a singleton the shared one.
+pub struct Provider<T> {
+    container: BeanRef,
+    // Bean the provider was injected into, resolving on its behalf
+    requester: Option<Identifier>,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Provider<T> {
+    fn clone(&self) -> Self {
+        Provider {
+            container: self.container.clone(),
+            requester: self.requester.clone(),
+            _type: PhantomData,
+        }
+    }
+}
+
+impl<T: Any + Send + Sync + 'static> Provider<T> {
+    /// Resolve `T` from the container
+    ///
+    /// Resolves on behalf of the bean the provider was injected into, so it reaches the internal
+    /// beans of its module. Called from a factory, it fails with `CircularDependency` if `T` is
+    /// being created on this thread.
+    pub fn get(&self) -> Result<Arc<T>, BeanError> {
+        let container = self
+            .container
+            .upgrade()
+            .ok_or(BeanError::ContainerDropped)?;
+        container.get_requested::<T>(None, self.requester.as_ref())
+    }
+
+    /// Defer the resolution of `T` to the first use of the returned handle
+    pub fn lazy(&self) -> Lazy<T> {
+        Lazy {
+            provider: self.clone(),
+            instance: OnceLock::new(),
+        }
+    }
+}
+
+/// Dependency resolved on first dereference, see [`Dependencies::lazy`]
+///
+/// Lets a frequently created bean declare an expensive dependency it seldom uses.
+/// Dereferencing panics if the resolution fails, [`try_get`](Self::try_get) doesn't.
+pub struct Lazy<T> {
+    provider: Provider<T>,
+    instance: OnceLock<Arc<T>>,
+}
+
+impl<T: Any + Send + Sync + 'static> Lazy<T> {
+    /// Resolve `T` unless already done, a failed resolution is retried on next use
+    pub fn try_get(&self) -> Result<&Arc<T>, BeanError> {
+        if let Some(instance) = self.instance.get() {
+            return Ok(instance);
+        }
+        let instance = self.provider.get()?;
+        Ok(self.instance.get_or_init(|| instance))
+    }
+
+    /// Whether `T` was resolved already
+    pub fn is_resolved(&self) -> bool {
+        self.instance.get().is_some()
+    }
+}
+
+impl<T: Any + Send + Sync + 'static> Deref for Lazy<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.try_get().unwrap()
+    }
+}
+
+/// Beans exposed as the trait object `U`, each resolved when iterated,
+/// see [`Dependencies::lazy_all`]
+///
+/// The candidates are the beans registered when the set was injected.
+pub struct LazySet<U: ?Sized> {
+    container: BeanRef,
+    candidates: Vec<BeanId>,
+    _type: PhantomData<fn() -> Arc<U>>,
+}
+
+impl<U: ?Sized> Clone for LazySet<U> {
+    fn clone(&self) -> Self {
+        LazySet {
+            container: self.container.clone(),
+            candidates: self.candidates.clone(),
+            _type: PhantomData,
+        }
+    }
+}
+
+impl<U: ?Sized + 'static> LazySet<U> {
+    /// Beans of the set, in registration order
+    pub fn ids(&self) -> &[BeanId] {
+        &self.candidates
+    }
+
+    pub fn len(&self) -> usize {
+        self.candidates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candidates.is_empty()
+    }
+
+    /// Resolve one bean of the set
+    pub fn get(&self, bean: BeanId) -> Result<Arc<U>, BeanError> {
+        if !self.candidates.contains(&bean) {
+            return Err(BeanError::NotFound(bean));
+        }
+        let container = self
+            .container
+            .upgrade()
+            .ok_or(BeanError::ContainerDropped)?;
+        container.resolve_as::<U>(bean)
+    }
+
+    /// Resolve the beans one by one while iterating
+    pub fn iter(&self) -> impl Iterator<Item = Result<Arc<U>, BeanError>> + '_ {
+        self.candidates.iter().map(|bean| self.get(*bean))
+    }
+}
+
+impl BeanContainer {
+    /// Register a prototype `LazySet<U>` of the beans exposed as the trait object `U` with
+    /// [`Registration::upcast`](crate::bean::Registration::upcast)
+    #[track_caller]
+    pub fn register_lazy_set<U: ?Sized + Send + Sync + 'static>(
+        self: &Arc<Self>,
+    ) -> Result<Registration<'_>, BeanError> {
+        let container = Arc::downgrade(self);
+        self.register::<LazySet<U>, _>(Scope::Prototype, move |deps: &mut Dependencies| {
+            Ok(LazySet {
+                container: container.clone(),
+                candidates: deps.container().upcast_candidates::<U>(),
+                _type: PhantomData,
+            })
+        })
+    }
+}
+
+impl Dependencies<'_> {
+    /// Get a handle resolving `T` on first dereference, `T` needs a provider registered
+    /// with [`BeanContainer::register_provider`]
+    pub fn lazy<T: Any + Send + Sync + 'static>(&mut self) -> Result<Lazy<T>, BeanError> {
+        Ok(self.get::<Provider<T>>()?.lazy())
+    }
+
+    /// Get the beans exposed as the trait object `U`, resolved when iterated instead of now,
+    /// `U` needs a set registered with [`BeanContainer::register_lazy_set`]
+    pub fn lazy_all<U: ?Sized + Send + Sync + 'static>(&mut self) -> Result<LazySet<U>, BeanError> {
+        Ok(LazySet::clone(&*self.get::<LazySet<U>>()?))
+    }
+}
+
+impl BeanContainer {
+    /// Register a prototype `Provider<T>`, letting beans pull `T` when they need it,
+    /// e.g. a singleton minting a prototype per operation
+    ///
+    /// Each bean gets its own provider, resolving on its behalf
+    #[track_caller]
+    pub fn register_provider<T: Any + Send + Sync + 'static>(
+        self: &Arc<Self>,
+    ) -> Result<Registration<'_>, BeanError> {
+        let container = Arc::downgrade(self);
+        self.register::<Provider<T>, _>(Scope::Prototype, move |deps: &mut Dependencies| {
+            Ok(Provider {
+                container: container.clone(),
+                requester: deps.requester(),
+                _type: PhantomData,
+            })
+        })
+        .map(|registration| registration.defers_to::<T>())
+    }
+}
@@ -0,0 +1,48 @@
+use crate::bean::{BeanContainer, BeanDef, Registration};
+use crate::error::BeanError;
+
+pub use rs_bean_derive::component;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use inventory;
+}
+
+/// Struct annotated with [`#[component]`](component), collected at link time
+pub struct Component {
+    type_name: &'static str,
+    definition: fn() -> BeanDef,
+}
+
+impl Component {
+    #[doc(hidden)]
+    pub const fn new(type_name: &'static str, definition: fn() -> BeanDef) -> Self {
+        Component {
+            type_name,
+            definition,
+        }
+    }
+
+    /// Path of the annotated struct
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
+inventory::collect!(Component);
+
+/// Every struct annotated with `#[component]` in the binary, in type name order
+pub fn components() -> Vec<&'static Component> {
+    let mut components: Vec<&'static Component> = inventory::iter::<Component>().collect();
+    components.sort_by_key(|c| c.type_name);
+    components
+}
+
+impl BeanContainer {
+    /// Register every struct annotated with `#[component]` in the binary, linked crates included
+    ///
+    /// Registered atomically in type name order, see [`register_all`](Self::register_all)
+    pub fn scan(&self) -> Result<Vec<Registration<'_>>, BeanError> {
+        self.register_all(components().into_iter().map(|c| (c.definition)()))
+    }
+}
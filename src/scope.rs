@@ -0,0 +1,425 @@
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::bean::{BeanContainer, BeanRef, Dependencies, Identifier, Registration, Scope};
+use crate::error::BeanError;
+#[cfg(feature = "introspection")]
+use crate::history::{HistoryEvent, RecordPoint};
+
+thread_local! {
+    // Scopes entered on this thread, innermost last
+    static ACTIVE: RefCell<Vec<Arc<ScopeState>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Instance owned by a scope
+struct ScopedInstance {
+    id: Identifier,
+    instance: Arc<dyn Any + Send + Sync>,
+    // Dependency path the instance was created on
+    path: String,
+}
+
+/// Weakly referenced instance watched for leaks
+struct Tracked {
+    bean: String,
+    path: String,
+    instance: Weak<dyn Any + Send + Sync>,
+}
+
+impl Tracked {
+    fn leaked(&self) -> Option<LeakedBean> {
+        let strong_count = self.instance.strong_count();
+        (strong_count > 0).then(|| LeakedBean {
+            bean: self.bean.clone(),
+            path: self.path.clone(),
+            strong_count,
+        })
+    }
+}
+
+/// Opt-in tracker of instances outliving the scope they were created in
+#[derive(Default)]
+pub(crate) struct LeakTracker {
+    suspects: Mutex<Vec<Tracked>>,
+}
+
+impl LeakTracker {
+    /// Suspects still alive, forgetting the ones dropped since
+    pub(crate) fn report(&self) -> Vec<LeakedBean> {
+        let mut suspects = self.suspects.lock().unwrap();
+        suspects.retain(|t| t.instance.strong_count() > 0);
+        suspects.iter().filter_map(Tracked::leaked).collect()
+    }
+}
+
+/// Instances owned by one entered scope
+pub(crate) struct ScopeState {
+    container: u64,
+    name: &'static str,
+    // Instances in creation order
+    instances: Mutex<Vec<ScopedInstance>>,
+    // Prototypes created while this scope was innermost, only when tracking leaks
+    prototypes: Mutex<Vec<Tracked>>,
+    tracker: Option<Arc<LeakTracker>>,
+    // Values attached with `ScopeHandle::put`, keyed by type
+    values: Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl ScopeState {
+    pub(crate) fn new(
+        container: u64,
+        name: &'static str,
+        tracker: Option<Arc<LeakTracker>>,
+    ) -> Arc<Self> {
+        Arc::new(ScopeState {
+            container,
+            name,
+            instances: Mutex::new(Vec::new()),
+            prototypes: Mutex::new(Vec::new()),
+            tracker,
+            values: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        self.name
+    }
+
+    #[cfg(feature = "tokio")]
+    pub(crate) fn container(&self) -> u64 {
+        self.container
+    }
+
+    pub(crate) fn value<T: Any + Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        let values = self.values.lock().unwrap();
+        values.get(&TypeId::of::<T>())?.clone().downcast::<T>().ok()
+    }
+
+    pub(crate) fn get(&self, id: &Identifier) -> Option<Arc<dyn Any + Send + Sync>> {
+        let instances = self.instances.lock().unwrap();
+        instances
+            .iter()
+            .find(|s| &s.id == id)
+            .map(|s| s.instance.clone())
+    }
+
+    /// Store a new instance, keeping the first one if created twice
+    pub(crate) fn store(
+        &self,
+        id: &Identifier,
+        instance: Arc<dyn Any + Send + Sync>,
+        path: String,
+    ) -> Arc<dyn Any + Send + Sync> {
+        let mut instances = self.instances.lock().unwrap();
+        if let Some(existing) = instances.iter().find(|s| &s.id == id) {
+            return existing.instance.clone();
+        }
+        instances.push(ScopedInstance {
+            id: id.clone(),
+            instance: instance.clone(),
+            path,
+        });
+        instance
+    }
+
+    /// Watch a prototype created inside this scope, if leak tracking is enabled
+    pub(crate) fn track(
+        &self,
+        id: &Identifier,
+        instance: &Arc<dyn Any + Send + Sync>,
+        path: String,
+    ) {
+        if self.tracker.is_some() {
+            self.prototypes.lock().unwrap().push(Tracked {
+                bean: id.to_string(),
+                path,
+                instance: Arc::downgrade(instance),
+            });
+        }
+    }
+}
+
+/// Scopes of the container entered on the current thread, innermost last
+///
+/// With the `tokio` feature, the scopes of the current task come first
+pub(crate) fn active_scopes(container: u64) -> Vec<Arc<ScopeState>> {
+    #[cfg(feature = "tokio")]
+    let mut scopes = crate::task_scope::task_scopes(container);
+    #[cfg(not(feature = "tokio"))]
+    let mut scopes = Vec::new();
+    ACTIVE.with(|active| {
+        scopes.extend(
+            active
+                .borrow()
+                .iter()
+                .filter(|s| s.container == container)
+                .cloned(),
+        )
+    });
+    scopes
+}
+
+/// Zero-cost typed key of a custom scope
+///
+/// Using a marker instead of a string name at both registration and entry sites
+/// lets the compiler catch typos, see [`Scope::of`](crate::bean::Scope::of)
+pub trait ScopeMarker {
+    const NAME: &'static str;
+}
+
+/// Marker of the `"request"` scope, see [`Scope::REQUEST`](crate::bean::Scope::REQUEST)
+pub struct RequestScope;
+
+impl ScopeMarker for RequestScope {
+    const NAME: &'static str = "request";
+}
+
+/// Stable handle to a scoped bean, injectable into longer-lived beans
+///
+/// Registered with [`BeanContainer::register_proxy`], it resolves `T` from the scope
+/// active on the calling thread each time [`current`](Self::current) is called.
+pub struct ScopedProxy<T> {
+    container: BeanRef,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T: Any + Send + Sync + 'static> ScopedProxy<T> {
+    /// Instance of `T` in the active scope, fails outside of it
+    pub fn current(&self) -> Result<Arc<T>, BeanError> {
+        let container = self
+            .container
+            .upgrade()
+            .ok_or(BeanError::ContainerDropped)?;
+        container.try_get::<T>(None)
+    }
+}
+
+impl BeanContainer {
+    /// Register a singleton `ScopedProxy<T>`, letting singletons depend on the scoped bean `T`
+    #[track_caller]
+    pub fn register_proxy<T: Any + Send + Sync + 'static>(
+        self: &Arc<Self>,
+    ) -> Result<Registration<'_>, BeanError> {
+        let container = Arc::downgrade(self);
+        self.register::<ScopedProxy<T>, _>(Scope::Singleton, move |_deps: &mut Dependencies| {
+            Ok(ScopedProxy {
+                container: container.clone(),
+                _type: PhantomData,
+            })
+        })
+        .map(|registration| registration.defers_to::<T>())
+    }
+
+    /// Enter a scope with given name on the current thread
+    ///
+    /// Beans registered with `Scope::Scoped(name)` share one instance per entered scope until
+    /// the returned handle is dropped or closed. Scopes with the same name can be nested,
+    /// the innermost one wins.
+    pub fn enter_scope(&self, name: &'static str) -> ScopeHandle {
+        let handle =
+            ScopeHandle::enter(self.container_id(), name, self.leak_tracker.get().cloned());
+        #[cfg(feature = "introspection")]
+        self.record(RecordPoint::ScopeOpens, || HistoryEvent::ScopeEntered(name));
+        handle
+    }
+
+    /// Declare that scope `name` nests inside scope `parent`, e.g. `"request"` inside `"session"`
+    ///
+    /// Enforced by [`try_enter_scope`](Self::try_enter_scope)
+    pub fn declare_scope_parent(&self, name: &'static str, parent: &'static str) {
+        self.scope_parents.write().unwrap().insert(name, parent);
+    }
+
+    /// Enter a scope, failing if its declared parent scope is not active on the current thread
+    pub fn try_enter_scope(&self, name: &'static str) -> Result<ScopeHandle, BeanError> {
+        if let Some(parent) = self.scope_parents.read().unwrap().get(name)
+            && !self.is_scope_active(parent)
+        {
+            return Err(BeanError::ScopeNotNested {
+                scope: name,
+                parent,
+            });
+        }
+        Ok(self.enter_scope(name))
+    }
+
+    /// Names of the scopes entered on the current thread, outermost first
+    ///
+    /// Scoped beans are looked up in the innermost active scope with their scope's name,
+    /// so a `"session"` bean resolved inside a nested `"request"` scope is the session's instance
+    pub fn active_scope_chain(&self) -> Vec<&'static str> {
+        active_scopes(self.container_id())
+            .iter()
+            .map(|s| s.name())
+            .collect()
+    }
+
+    pub fn is_scope_active(&self, name: &str) -> bool {
+        active_scopes(self.container_id())
+            .iter()
+            .any(|s| s.name() == name)
+    }
+
+    /// State of a new scope of this container, not entered yet
+    #[cfg(feature = "tokio")]
+    pub(crate) fn new_scope_state(&self, name: &'static str) -> Arc<ScopeState> {
+        ScopeState::new(self.container_id(), name, self.leak_tracker.get().cloned())
+    }
+
+    /// Enter the scope keyed by a typed marker on the current thread, see [`Scope::of`]
+    pub fn enter_scope_of<M: ScopeMarker>(&self) -> ScopeHandle {
+        self.enter_scope(M::NAME)
+    }
+
+    /// Enter the request scope on the current thread, e.g. for each HTTP request
+    ///
+    /// Beans registered with [`Scope::REQUEST`] share one instance until the handle is dropped
+    pub fn enter_request_scope(&self) -> ScopeHandle {
+        self.enter_scope_of::<RequestScope>()
+    }
+
+    /// Track scoped beans, and prototypes created inside a scope, which outlive their scope
+    ///
+    /// Only scopes entered after enabling are tracked, see [`BeanContainer::leak_report`]
+    pub fn enable_leak_tracking(&self) {
+        self.leak_tracker.get_or_init(Default::default);
+    }
+
+    /// Tracked instances still alive after their scope was closed
+    ///
+    /// Typically a request-scoped bean accidentally stored in a singleton
+    pub fn leak_report(&self) -> Vec<LeakedBean> {
+        self.leak_tracker
+            .get()
+            .map(|tracker| tracker.report())
+            .unwrap_or_default()
+    }
+}
+
+/// Bean still referenced after its scope was closed
+#[derive(Debug, Clone)]
+pub struct LeakedBean {
+    pub bean: String,
+    /// Dependency path the instance was created on
+    pub path: String,
+    /// Strong references left outside the scope
+    pub strong_count: usize,
+}
+
+/// Outcome of closing a scope
+#[derive(Debug, Clone, Default)]
+pub struct ScopeReport {
+    /// Beans in the order they were disposed
+    pub disposed: Vec<String>,
+    /// Beans whose `Drop` will run after the scope ended
+    pub leaked: Vec<LeakedBean>,
+}
+
+/// Guard of an entered scope
+///
+/// Beans registered with `Scope::Scoped(name)` and resolved on this thread while the guard
+/// is alive share one instance per scope. Dropping the guard disposes them in reverse creation order.
+pub struct ScopeHandle {
+    state: Arc<ScopeState>,
+    // Innermost scope of the container active when this one was entered
+    parent: Option<&'static str>,
+    closed: bool,
+    // Bound to the thread which entered the scope
+    _local: PhantomData<Rc<()>>,
+}
+
+impl ScopeHandle {
+    pub(crate) fn enter(
+        container: u64,
+        name: &'static str,
+        tracker: Option<Arc<LeakTracker>>,
+    ) -> Self {
+        let state = ScopeState::new(container, name, tracker);
+        let parent = active_scopes(container).last().map(|s| s.name);
+        ACTIVE.with(|active| active.borrow_mut().push(state.clone()));
+        ScopeHandle {
+            state,
+            parent,
+            closed: false,
+            _local: PhantomData,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.state.name
+    }
+
+    /// Scope this one was entered in, if any
+    pub fn parent(&self) -> Option<&'static str> {
+        self.parent
+    }
+
+    /// Attach a value to the scope, replacing the value of the same type, e.g. request data
+    /// such as `UserId(42)`
+    ///
+    /// Factories of beans resolved while the scope is active read it with
+    /// [`Dependencies::scope_value`]
+    pub fn put<T: Any + Send + Sync + 'static>(&self, value: T) {
+        let mut values = self.state.values.lock().unwrap();
+        values.insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// Value of type `T` attached to the scope
+    pub fn value<T: Any + Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.state.value::<T>()
+    }
+
+    /// Beans created in this scope, in the order they will be disposed
+    pub fn disposal_order(&self) -> Vec<String> {
+        let instances = self.state.instances.lock().unwrap();
+        instances.iter().rev().map(|s| s.id.to_string()).collect()
+    }
+
+    /// Close the scope, disposing its beans and auditing leaked references
+    pub fn close(mut self) -> ScopeReport {
+        self.dispose()
+    }
+
+    fn dispose(&mut self) -> ScopeReport {
+        self.closed = true;
+        ACTIVE.with(|active| active.borrow_mut().retain(|s| !Arc::ptr_eq(s, &self.state)));
+        self.state.dispose()
+    }
+}
+
+impl ScopeState {
+    /// Drop the instances and values of the scope, in reverse creation order
+    pub(crate) fn dispose(&self) -> ScopeReport {
+        let instances = std::mem::take(&mut *self.instances.lock().unwrap());
+        let mut watched = std::mem::take(&mut *self.prototypes.lock().unwrap());
+        self.values.lock().unwrap().clear();
+        let mut report = ScopeReport::default();
+        for scoped in instances.into_iter().rev() {
+            report.disposed.push(scoped.id.to_string());
+            watched.push(Tracked {
+                bean: scoped.id.to_string(),
+                path: scoped.path,
+                instance: Arc::downgrade(&scoped.instance),
+            });
+        }
+
+        watched.retain(|t| t.instance.strong_count() > 0);
+        report.leaked = watched.iter().filter_map(Tracked::leaked).collect();
+        if let Some(tracker) = &self.tracker {
+            tracker.suspects.lock().unwrap().extend(watched);
+        }
+        report
+    }
+}
+
+impl Drop for ScopeHandle {
+    fn drop(&mut self) {
+        if !self.closed {
+            self.dispose();
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::Ordering;
+
+use crate::bean::{BeanContainer, BeanId};
+use crate::error::BeanError;
+
+/// Cycle of the dependency graph found by [`BeanContainer::seal`]
+#[derive(Debug, Clone)]
+pub struct CycleFinding {
+    /// Beans of the cycle, in registration order
+    pub beans: Vec<BeanId>,
+    /// Goes through a bean resolving its target on demand, e.g. mutual `Provider`s:
+    /// it can't fail at resolution, but still couples the beans both ways
+    pub deferred: bool,
+    /// Listed with [`BeanContainer::allow_cycle`]
+    pub allowed: bool,
+}
+
+impl Display for CycleFinding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let beans: Vec<String> = self.beans.iter().map(|id| id.to_string()).collect();
+        write!(f, "{{{}}}", beans.join(", "))?;
+        if self.deferred {
+            write!(f, " (deferred)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of [`BeanContainer::seal`]
+#[derive(Debug, Clone, Default)]
+pub struct SealReport {
+    pub cycles: Vec<CycleFinding>,
+    /// Allowed cycles which were not found, and can be removed from the allowlist
+    pub stale_allowances: Vec<Vec<String>>,
+}
+
+impl SealReport {
+    /// Cycles tolerated by the allowlist
+    pub fn warnings(&self) -> impl Iterator<Item = &CycleFinding> {
+        self.cycles.iter().filter(|c| c.allowed)
+    }
+
+    /// Cycles not in the allowlist
+    pub fn errors(&self) -> impl Iterator<Item = &CycleFinding> {
+        self.cycles.iter().filter(|c| !c.allowed)
+    }
+
+    /// The report, or `CircularDependency` with the first cycle not in the allowlist
+    pub fn into_result(self) -> Result<Self, BeanError> {
+        let error = self.errors().next().map(|cycle| cycle.to_string());
+        match error {
+            Some(cycle) => Err(BeanError::CircularDependency(cycle)),
+            None => Ok(self),
+        }
+    }
+}
+
+impl BeanContainer {
+    /// Tolerate a cycle between the given beans in [`seal`](Self::seal), e.g. an existing
+    /// one to remove later
+    pub fn allow_cycle(&self, beans: &[&str]) {
+        let mut beans: Vec<String> = beans.iter().map(|b| b.to_string()).collect();
+        beans.sort();
+        self.allowed_cycles.write().unwrap().push(beans);
+    }
+
+    /// Reject any later registration, and report the cycles of the dependency graph
+    ///
+    /// The built-in beans ([`Environment`](crate::environment::Environment),
+    /// [`AppInfo`](crate::environment::AppInfo) and [`Clock`](crate::clock::Clock)) the
+    /// application didn't register are registered first, so they stay resolvable.
+    ///
+    /// The graph is made of the declared dependencies, the ones captured while creating beans,
+    /// and the edges from providers and proxies to their target, so beans should be created
+    /// first, e.g. with [`initialize_all`](Self::initialize_all). Each strongly connected group
+    /// of beans is reported once.
+    pub fn seal(&self) -> SealReport {
+        self.provide_built_ins();
+        self.sealed.store(true, Ordering::Release);
+
+        let deferred = self.deferred_edges();
+        let mut nodes: Vec<BeanId> = self.beans().into_iter().map(|info| info.id).collect();
+        let mut successors: HashMap<BeanId, Vec<BeanId>> = HashMap::new();
+        let wiring = self
+            .wiring_edges()
+            .into_iter()
+            .map(|(from, to, _)| (from, to));
+        for (from, to) in wiring.chain(deferred.clone()) {
+            for id in [from, to] {
+                if !nodes.contains(&id) {
+                    nodes.push(id);
+                }
+            }
+            successors.entry(from).or_default().push(to);
+        }
+
+        let allowed = self.allowed_cycles.read().unwrap().clone();
+        let mut matched = vec![false; allowed.len()];
+        let mut cycles = Vec::new();
+        for group in strongly_connected(&nodes, &successors) {
+            let self_loop = successors
+                .get(&group[0])
+                .is_some_and(|next| next.contains(&group[0]));
+            if group.len() == 1 && !self_loop {
+                continue;
+            }
+            let is_deferring = |id: &BeanId| deferred.iter().any(|(from, _)| from == id);
+            let beans: Vec<BeanId> = nodes
+                .iter()
+                .filter(|id| group.contains(id) && !is_deferring(id))
+                .copied()
+                .collect();
+            let mut names: Vec<String> = beans.iter().map(|id| id.name().to_string()).collect();
+            names.sort();
+            let allowance = allowed.iter().position(|a| *a == names);
+            if let Some(index) = allowance {
+                matched[index] = true;
+            }
+            cycles.push(CycleFinding {
+                beans,
+                deferred: group.iter().any(is_deferring),
+                allowed: allowance.is_some(),
+            });
+        }
+
+        SealReport {
+            cycles,
+            stale_allowances: allowed
+                .into_iter()
+                .zip(matched)
+                .filter(|(_, matched)| !matched)
+                .map(|(allowance, _)| allowance)
+                .collect(),
+        }
+    }
+
+    /// Whether [`seal`](Self::seal) was called
+    pub fn is_sealed(&self) -> bool {
+        self.sealed.load(Ordering::Acquire)
+    }
+}
+
+/// Strongly connected components, with Tarjan's algorithm
+pub(crate) fn strongly_connected(
+    nodes: &[BeanId],
+    successors: &HashMap<BeanId, Vec<BeanId>>,
+) -> Vec<Vec<BeanId>> {
+    struct Walk<'a> {
+        successors: &'a HashMap<BeanId, Vec<BeanId>>,
+        // Visit order and lowest reachable visit order of each node
+        index: HashMap<BeanId, (usize, usize)>,
+        stack: Vec<BeanId>,
+        groups: Vec<Vec<BeanId>>,
+    }
+
+    impl Walk<'_> {
+        fn visit(&mut self, id: BeanId) {
+            let order = self.index.len();
+            self.index.insert(id, (order, order));
+            self.stack.push(id);
+            for next in self.successors.get(&id).into_iter().flatten() {
+                let low = match self.index.get(next) {
+                    None => {
+                        self.visit(*next);
+                        self.index[next].1
+                    }
+                    Some(&(next_order, _)) if self.stack.contains(next) => next_order,
+                    Some(_) => continue,
+                };
+                let entry = self.index.get_mut(&id).unwrap();
+                entry.1 = entry.1.min(low);
+            }
+            let (order, low) = self.index[&id];
+            if order == low {
+                let start = self.stack.iter().position(|s| *s == id).unwrap();
+                self.groups.push(self.stack.split_off(start));
+            }
+        }
+    }
+
+    let mut walk = Walk {
+        successors,
+        index: HashMap::new(),
+        stack: Vec::new(),
+        groups: Vec::new(),
+    };
+    for id in nodes {
+        if !walk.index.contains_key(id) {
+            walk.visit(*id);
+        }
+    }
+    walk.groups
+}
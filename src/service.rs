@@ -0,0 +1,287 @@
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::bean::{BeanContainer, BeanId, Dependencies, Registration, Scope};
+use crate::error::BeanError;
+
+/// Bean running in background once the services are started, see [`BeanContainer::run_services`]
+pub trait BackgroundService: Send + Sync {
+    /// Run until done or `shutdown` is requested
+    ///
+    /// Errors and panics are handled by the [`Supervision`] policy of the bean
+    fn run(&self, shutdown: &Shutdown) -> Result<(), BeanError>;
+}
+
+/// Shutdown signal shared by the services of a [`ServiceGroup`]
+#[derive(Clone, Default)]
+pub struct Shutdown {
+    state: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl Shutdown {
+    pub fn is_requested(&self) -> bool {
+        *self.state.0.lock().unwrap()
+    }
+
+    /// Wait until shutdown is requested or `timeout` elapses, returns whether it was requested
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let (requested, cond) = &*self.state;
+        let guard = requested.lock().unwrap();
+        let (guard, _) = cond
+            .wait_timeout_while(guard, timeout, |requested| !*requested)
+            .unwrap();
+        *guard
+    }
+
+    /// Ask every service of the group to stop
+    pub fn request(&self) {
+        let (requested, cond) = &*self.state;
+        *requested.lock().unwrap() = true;
+        cond.notify_all();
+    }
+}
+
+/// What happens when a background service fails, see [`Registration::supervision`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Supervision {
+    max_restarts: u32,
+    backoff: Duration,
+    max_backoff: Duration,
+    escalate: bool,
+}
+
+impl Supervision {
+    /// Leave the service stopped, the default
+    pub fn stop() -> Self {
+        Supervision {
+            max_restarts: 0,
+            backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(30),
+            escalate: false,
+        }
+    }
+
+    /// Restart the service at most `max_restarts` times, waiting 100ms before the first
+    /// restart and doubling the wait up to 30s
+    ///
+    /// Each restart drops the failed instance and its dependents, then creates a new one.
+    pub fn restart(max_restarts: u32) -> Self {
+        Supervision {
+            max_restarts,
+            ..Self::stop()
+        }
+    }
+
+    /// Shut down every service of the group
+    pub fn escalate() -> Self {
+        Self::stop().then_escalate()
+    }
+
+    /// Wait `initial` before the first restart, doubling up to `max`
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Shut down every service of the group once restarts are exhausted
+    pub fn then_escalate(mut self) -> Self {
+        self.escalate = true;
+        self
+    }
+}
+
+impl Default for Supervision {
+    fn default() -> Self {
+        Self::stop()
+    }
+}
+
+type RunFn = dyn Fn(Arc<dyn Any + Send + Sync>, &Shutdown) -> Result<(), BeanError> + Send + Sync;
+
+/// How to run a background service bean
+#[derive(Clone)]
+pub(crate) struct ServiceSpec {
+    run: Arc<RunFn>,
+    supervision: Supervision,
+}
+
+impl ServiceSpec {
+    fn new<T: BackgroundService + 'static>() -> Self {
+        ServiceSpec {
+            run: Arc::new(|instance, shutdown| {
+                let service = instance
+                    .downcast::<T>()
+                    .map_err(|_| BeanError::DowncastFailed(std::any::type_name::<T>()))?;
+                service.run(shutdown)
+            }),
+            supervision: Supervision::default(),
+        }
+    }
+}
+
+/// How a background service ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServiceOutcome {
+    /// Returned successfully
+    Completed,
+    /// Failed and was left stopped, after the given number of restarts
+    Failed { error: BeanError, restarts: u32 },
+    /// Failed and shut down the group
+    Escalated { error: BeanError, restarts: u32 },
+}
+
+/// Outcome of every service of a [`ServiceGroup`], in registration order
+#[derive(Debug, Clone, Default)]
+pub struct ServiceReport {
+    pub services: Vec<(String, ServiceOutcome)>,
+}
+
+impl ServiceReport {
+    /// Service which shut down the group, if any
+    pub fn escalated_by(&self) -> Option<&str> {
+        self.services
+            .iter()
+            .find(|(_, outcome)| matches!(outcome, ServiceOutcome::Escalated { .. }))
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+/// Background services started by [`BeanContainer::run_services`]
+///
+/// Services never outlive their group: dropping it requests shutdown and joins every service
+pub struct ServiceGroup {
+    shutdown: Shutdown,
+    threads: Vec<(String, JoinHandle<ServiceOutcome>)>,
+}
+
+impl ServiceGroup {
+    /// Signal shared by the services, e.g. to request shutdown from another thread
+    pub fn shutdown_signal(&self) -> Shutdown {
+        self.shutdown.clone()
+    }
+
+    /// Wait for every service to end, on its own or after an escalation
+    pub fn wait(mut self) -> ServiceReport {
+        self.join()
+    }
+
+    /// Request shutdown and wait for every service to end
+    pub fn shutdown(mut self) -> ServiceReport {
+        self.shutdown.request();
+        self.join()
+    }
+
+    fn join(&mut self) -> ServiceReport {
+        let services = self
+            .threads
+            .drain(..)
+            .map(|(name, thread)| {
+                let outcome = thread.join().unwrap_or_else(|_| ServiceOutcome::Failed {
+                    error: BeanError::from("Service panicked"),
+                    restarts: 0,
+                });
+                (name, outcome)
+            })
+            .collect();
+        ServiceReport { services }
+    }
+}
+
+impl Drop for ServiceGroup {
+    fn drop(&mut self) {
+        self.shutdown.request();
+        self.join();
+    }
+}
+
+impl Registration<'_> {
+    /// How failures of a background service are handled, see [`Supervision`]
+    ///
+    /// Only applies to beans registered with [`BeanContainer::register_service`]
+    pub fn supervision(self, supervision: Supervision) -> Self {
+        self.update_service(|service| service.supervision = supervision);
+        self
+    }
+}
+
+impl BeanContainer {
+    /// Register a singleton `T` run in background by [`BeanContainer::run_services`]
+    #[track_caller]
+    pub fn register_service<T, F>(&self, factory: F) -> Result<Registration<'_>, BeanError>
+    where
+        T: BackgroundService + 'static,
+        F: Fn(&mut Dependencies) -> Result<T, BeanError> + Send + Sync + 'static,
+    {
+        Ok(self
+            .register::<T, F>(Scope::Singleton, factory)?
+            .with_service(ServiceSpec::new::<T>()))
+    }
+
+    /// Create the background services in registration order, then run each on its own
+    /// thread under its supervision policy
+    ///
+    /// Fails without starting anything if a service cannot be created.
+    /// Restarts and escalations are sent to observers.
+    pub fn run_services(self: &Arc<Self>) -> Result<ServiceGroup, BeanError> {
+        let services = self.services();
+        for (id, _) in &services {
+            self.resolve_id(id)?;
+        }
+        let mut group = ServiceGroup {
+            shutdown: Shutdown::default(),
+            threads: Vec::new(),
+        };
+        for (id, spec) in services {
+            let container = self.clone();
+            let shutdown = group.shutdown.clone();
+            let name = id.to_string();
+            let thread = thread::Builder::new()
+                .name(format!("service {}", name))
+                .spawn(move || container.supervise(&id, &spec, &shutdown))
+                .map_err(|e| BeanError::Io(format!("Failed to spawn service {}: {}", name, e)))?;
+            group.threads.push((name, thread));
+        }
+        Ok(group)
+    }
+
+    fn supervise(&self, id: &BeanId, spec: &ServiceSpec, shutdown: &Shutdown) -> ServiceOutcome {
+        let policy = spec.supervision;
+        let mut restarts = 0;
+        let mut backoff = policy.backoff;
+        loop {
+            let run = || {
+                let service = self.resolve_id(id)?;
+                panic::catch_unwind(AssertUnwindSafe(|| (spec.run)(service, shutdown)))
+                    .unwrap_or_else(|payload| Err(BeanError::panicked("Service", &*payload)))
+            };
+            let error = match run() {
+                Ok(()) => return ServiceOutcome::Completed,
+                Err(error) => error,
+            };
+            if shutdown.is_requested() {
+                return ServiceOutcome::Failed { error, restarts };
+            }
+            if restarts < policy.max_restarts {
+                restarts += 1;
+                self.notify(|o| o.on_service_restarted(*id, restarts, &error.to_string()));
+                if shutdown.wait_timeout(backoff) {
+                    return ServiceOutcome::Failed { error, restarts };
+                }
+                backoff = (backoff * 2).min(policy.max_backoff);
+                // The failed instance may be in a broken state, restart on a new one
+                self.invalidate(id.identifier().clone());
+                continue;
+            }
+            if policy.escalate {
+                self.notify(|o| o.on_service_escalated(*id, &error.to_string()));
+                shutdown.request();
+                return ServiceOutcome::Escalated { error, restarts };
+            }
+            return ServiceOutcome::Failed { error, restarts };
+        }
+    }
+}
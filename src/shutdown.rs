@@ -0,0 +1,326 @@
+use std::any::Any;
+use std::fmt::{Display, Formatter};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bean::{BeanContainer, BeanId, DisposableInstance, Registration};
+use crate::error::BeanError;
+
+/// Resource released when the container shuts down, e.g. a pool to flush or close
+///
+/// Enabled per bean with [`Registration::disposable`]
+pub trait Disposable: Send + Sync {
+    fn destroy(&self);
+}
+
+/// Bean finishing its in-flight work before shutdown, e.g. an HTTP server or a queue consumer
+///
+/// Enabled per bean with [`Registration::drainable`]
+pub trait Drainable: Send + Sync {
+    /// Reject new work, in-flight work goes on
+    fn begin_drain(&self);
+
+    /// Units of work still in flight
+    fn in_flight(&self) -> usize;
+}
+
+/// Drain progress of one bean
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrainProgress {
+    pub bean: BeanId,
+    pub in_flight: usize,
+}
+
+/// Beans draining since [`BeanContainer::begin_drain`]
+pub struct Drain {
+    beans: Vec<(BeanId, Arc<dyn Drainable>)>,
+}
+
+impl Drain {
+    /// Work in flight of each draining bean, in registration order
+    pub fn progress(&self) -> Vec<DrainProgress> {
+        self.beans
+            .iter()
+            .map(|(bean, drainable)| DrainProgress {
+                bean: *bean,
+                in_flight: drainable.in_flight(),
+            })
+            .collect()
+    }
+
+    /// Whether every bean finished its in-flight work
+    pub fn is_complete(&self) -> bool {
+        self.beans.iter().all(|(_, d)| d.in_flight() == 0)
+    }
+
+    /// Wait until every bean finished its in-flight work, polling every `interval`,
+    /// returns whether it did within `timeout`
+    pub fn wait(&self, timeout: Duration, interval: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.is_complete() {
+                return true;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            thread::sleep(interval.min(deadline - now));
+        }
+    }
+}
+
+/// Phases and deadlines of [`BeanContainer::shutdown_with`]
+///
+/// Phases are disposed in the order they are added, beans without a declared phase last.
+/// [`Default`] gives them [`DEFAULT_SHUTDOWN_DEADLINE`].
+#[derive(Debug, Clone)]
+pub struct ShutdownOptions {
+    phases: Vec<(String, Duration)>,
+    default_deadline: Duration,
+}
+
+impl ShutdownOptions {
+    /// Beans without a declared phase get `default_deadline`
+    pub fn new(default_deadline: Duration) -> Self {
+        ShutdownOptions {
+            phases: Vec::new(),
+            default_deadline,
+        }
+    }
+
+    /// Dispose the beans of the phase, see
+    /// [`Registration::shutdown_phase`](crate::bean::Registration::shutdown_phase),
+    /// abandoning those not done within `deadline`
+    pub fn phase(mut self, name: &str, deadline: Duration) -> Self {
+        self.phases.push((name.to_string(), deadline));
+        self
+    }
+}
+
+/// Deadline of the beans without a declared phase in the default [`ShutdownOptions`]
+pub const DEFAULT_SHUTDOWN_DEADLINE: Duration = Duration::from_secs(30);
+
+impl Default for ShutdownOptions {
+    fn default() -> Self {
+        Self::new(DEFAULT_SHUTDOWN_DEADLINE)
+    }
+}
+
+/// How the disposal of a bean ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisposeOutcome {
+    /// Disposed, or had nothing to dispose
+    Completed,
+    /// The disposal hook failed or panicked
+    Failed(BeanError),
+    /// The phase deadline passed before the disposal ended, or before it started
+    TimedOut,
+}
+
+/// Disposal of one bean
+#[derive(Debug, Clone)]
+pub struct BeanShutdown {
+    pub bean: BeanId,
+    pub phase: Option<String>,
+    pub outcome: DisposeOutcome,
+    /// Time spent in the disposal hook, `None` if it didn't end
+    pub elapsed: Option<Duration>,
+}
+
+/// Outcome of [`BeanContainer::shutdown_with`], beans in disposal order
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownReport {
+    pub beans: Vec<BeanShutdown>,
+}
+
+impl ShutdownReport {
+    pub fn completed(&self) -> impl Iterator<Item = &BeanShutdown> {
+        self.beans
+            .iter()
+            .filter(|b| b.outcome == DisposeOutcome::Completed)
+    }
+
+    pub fn timed_out(&self) -> impl Iterator<Item = &BeanShutdown> {
+        self.beans
+            .iter()
+            .filter(|b| b.outcome == DisposeOutcome::TimedOut)
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &BeanShutdown> {
+        self.beans
+            .iter()
+            .filter(|b| matches!(b.outcome, DisposeOutcome::Failed(_)))
+    }
+
+    /// Whether every bean was disposed
+    pub fn is_clean(&self) -> bool {
+        self.completed().count() == self.beans.len()
+    }
+}
+
+impl Display for ShutdownReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Shut down {} beans: {} completed, {} timed out, {} failed",
+            self.beans.len(),
+            self.completed().count(),
+            self.timed_out().count(),
+            self.failed().count()
+        )?;
+        for bean in self
+            .beans
+            .iter()
+            .filter(|b| b.outcome != DisposeOutcome::Completed)
+        {
+            match &bean.outcome {
+                DisposeOutcome::Failed(e) => write!(f, "\n  {} failed: {}", bean.bean, e)?,
+                _ => write!(f, "\n  {} timed out", bean.bean)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Registration<'_> {
+    /// Drain the singleton on [`BeanContainer::begin_drain`]
+    pub fn drainable<T: Drainable + Any>(self) -> Self {
+        self.upcast::<T, dyn Drainable>(|bean| bean)
+    }
+
+    /// Call [`Disposable::destroy`] on the singleton when the container shuts down
+    pub fn disposable<T: Disposable + Any>(self) -> Self {
+        self.on_dispose(|bean: &T| {
+            bean.destroy();
+            Ok(())
+        })
+    }
+}
+
+impl BeanContainer {
+    /// Make the created singletons registered as [`drainable`](Registration::drainable)
+    /// reject new work, in registration order, before shutting down
+    ///
+    /// The returned [`Drain`] reports the work they still have in flight
+    pub fn begin_drain(&self) -> Drain {
+        let beans = self.identified_instances_as::<dyn Drainable>();
+        for (_, drainable) in &beans {
+            drainable.begin_drain();
+        }
+        Drain { beans }
+    }
+
+    /// Release the cached singletons, most recently created first, running their disposal hooks
+    ///
+    /// Same as [`shutdown_with`](Self::shutdown_with) with the default options: disposals still
+    /// running after [`DEFAULT_SHUTDOWN_DEADLINE`] are abandoned
+    pub fn shutdown(&self) -> ShutdownReport {
+        self.shutdown_with(ShutdownOptions::default())
+    }
+
+    /// Release the cached singletons, running their disposal hooks phase by phase,
+    /// most recently created first within a phase
+    ///
+    /// Disposals still running when the deadline of their phase passes are abandoned on their
+    /// thread, and the next phase starts. Later resolutions create new instances.
+    pub fn shutdown_with(&self, options: ShutdownOptions) -> ShutdownReport {
+        let mut pending = self.take_singletons();
+        let mut phases: Vec<(Option<String>, Duration)> = options
+            .phases
+            .into_iter()
+            .map(|(name, deadline)| (Some(name), deadline))
+            .collect();
+        phases.push((None, options.default_deadline));
+
+        let mut report = ShutdownReport::default();
+        for (index, (phase, deadline)) in phases.iter().enumerate() {
+            let is_last = index == phases.len() - 1;
+            let (batch, rest): (Vec<_>, Vec<_>) = pending
+                .into_iter()
+                .partition(|d| is_last || d.phase == *phase);
+            pending = rest;
+            let notify = |shutdown: &BeanShutdown| self.notify(|o| o.on_disposed(shutdown));
+            report
+                .beans
+                .extend(dispose_batch(batch, *deadline, &notify));
+        }
+        report
+    }
+}
+
+/// Dispose the instances in order on a worker thread, until the deadline
+///
+/// `notify` is called as each disposal ends, then for those which timed out
+fn dispose_batch(
+    batch: Vec<DisposableInstance>,
+    deadline: Duration,
+    notify: &dyn Fn(&BeanShutdown),
+) -> Vec<BeanShutdown> {
+    let mut shutdowns: Vec<BeanShutdown> = batch
+        .iter()
+        .map(|d| BeanShutdown {
+            bean: d.id,
+            phase: d.phase.clone(),
+            outcome: DisposeOutcome::TimedOut,
+            elapsed: None,
+        })
+        .collect();
+    if batch.is_empty() {
+        return shutdowns;
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let worker = thread::Builder::new()
+        .name("bean shutdown".to_string())
+        .spawn(move || {
+            for (index, disposable) in batch.into_iter().enumerate() {
+                let started = Instant::now();
+                let result = match &disposable.dispose {
+                    // A panicking hook fails its bean only, the next ones are still disposed
+                    Some(dispose) => {
+                        panic::catch_unwind(AssertUnwindSafe(|| dispose(disposable.instance)))
+                            .unwrap_or_else(|payload| {
+                                Err(BeanError::panicked("Disposal", &*payload))
+                            })
+                    }
+                    None => Ok(()),
+                };
+                if tx.send((index, result, started.elapsed())).is_err() {
+                    return;
+                }
+            }
+        });
+    if let Err(e) = worker {
+        let error = BeanError::Io(format!("Failed to spawn shutdown thread: {}", e));
+        for shutdown in &mut shutdowns {
+            shutdown.outcome = DisposeOutcome::Failed(error.clone());
+            notify(shutdown);
+        }
+        return shutdowns;
+    }
+
+    let deadline = Instant::now() + deadline;
+    loop {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match rx.recv_timeout(timeout) {
+            Ok((index, result, elapsed)) => {
+                shutdowns[index].outcome = match result {
+                    Ok(()) => DisposeOutcome::Completed,
+                    Err(e) => DisposeOutcome::Failed(e),
+                };
+                shutdowns[index].elapsed = Some(elapsed);
+                notify(&shutdowns[index]);
+            }
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    shutdowns
+        .iter()
+        .filter(|s| s.outcome == DisposeOutcome::TimedOut)
+        .for_each(notify);
+    shutdowns
+}
@@ -0,0 +1,371 @@
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+
+use crate::bean::{BeanContainer, BeanId, BeanState, Scope};
+use crate::error::BeanError;
+use crate::once::InitializerState;
+
+/// Summary of the container, see [`BeanContainer::startup_summary`]
+#[derive(Debug, Clone)]
+pub struct StartupSummary {
+    /// Registered beans, aliases of named beans excluded
+    pub beans: usize,
+    pub singletons: usize,
+    pub prototypes: usize,
+    /// Beans with a time-to-live, see [`Scope::Cached`]
+    pub cached: usize,
+    /// Beans handed out from a bounded pool, see [`Scope::Pooled`]
+    pub pooled: usize,
+    /// Beans of custom scopes, by scope name
+    pub scoped: Vec<(&'static str, usize)>,
+    pub active_profiles: Vec<String>,
+    /// Eager and phased beans created successfully
+    pub eager_initialized: Vec<String>,
+    /// Deprecated beans, and eager or phased beans which failed
+    pub warnings: Vec<String>,
+}
+
+impl Display for StartupSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Started {} beans ({} singleton, {} prototype",
+            self.beans, self.singletons, self.prototypes
+        )?;
+        if self.cached > 0 {
+            write!(f, ", {} cached", self.cached)?;
+        }
+        if self.pooled > 0 {
+            write!(f, ", {} pooled", self.pooled)?;
+        }
+        for (scope, count) in &self.scoped {
+            write!(f, ", {} in scope {}", count, scope)?;
+        }
+        write!(f, "), {} eager initialized", self.eager_initialized.len())?;
+        if !self.active_profiles.is_empty() {
+            write!(f, ", profiles: {}", self.active_profiles.join(", "))?;
+        }
+        if !self.warnings.is_empty() {
+            write!(f, ", warnings: {}", self.warnings.join("; "))?;
+        }
+        Ok(())
+    }
+}
+
+/// How [`BeanContainer::initialize_all`] handles failures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InitMode {
+    /// Any failure aborts the initialization
+    #[default]
+    Strict,
+    /// Failures of beans marked `optional()` are collected and initialization continues,
+    /// resolving such a bean later returns its initialization failure
+    WarnAndContinue,
+    /// Every failure is collected and initialization continues, see
+    /// [`BeanContainer::init_all`]
+    Continue,
+}
+
+/// Singleton created by [`BeanContainer::initialize_all`]
+#[derive(Debug, Clone)]
+pub struct InitializedBean {
+    pub bean: BeanId,
+    /// Creation time, including the dependencies created along the way
+    pub elapsed: Duration,
+}
+
+/// Singleton which failed in [`BeanContainer::initialize_all`]
+#[derive(Debug, Clone)]
+pub struct FailedBean {
+    pub bean: BeanId,
+    /// Dependency path from the bean to the one causing the failure, the bean alone
+    /// if it failed itself
+    pub path: Vec<BeanId>,
+    pub error: BeanError,
+    /// Optional bean whose failure was tolerated by [`InitMode::WarnAndContinue`]
+    pub tolerated: bool,
+}
+
+/// One-time initializer which failed in [`BeanContainer::initialize_all`], see
+/// [`BeanContainer::once`]
+#[derive(Debug, Clone)]
+pub struct FailedInitializer {
+    pub name: String,
+    pub error: BeanError,
+}
+
+/// Bean not created by [`BeanContainer::initialize_all`], with the reason
+#[derive(Debug, Clone)]
+pub struct SkippedBean {
+    pub bean: String,
+    pub reason: String,
+}
+
+/// Outcome of [`BeanContainer::initialize_all`]
+#[derive(Debug, Clone, Default)]
+pub struct ResolutionReport {
+    /// Initializers which failed, before any singleton was created
+    pub failed_initializers: Vec<FailedInitializer>,
+    /// Singletons created, in registration order
+    pub initialized: Vec<InitializedBean>,
+    pub failed: Vec<FailedBean>,
+    /// Singletons left after an aborting failure, and beans left out of the build by their cfg predicate
+    pub skipped: Vec<SkippedBean>,
+}
+
+impl ResolutionReport {
+    /// Failure which aborted the initialization, if any
+    pub fn error(&self) -> Option<&BeanError> {
+        let initializer = self.failed_initializers.first().map(|f| &f.error);
+        initializer.or_else(|| self.failed.iter().find(|f| !f.tolerated).map(|f| &f.error))
+    }
+
+    /// Whether every singleton was created, except optional beans whose failure was tolerated
+    pub fn is_success(&self) -> bool {
+        self.error().is_none()
+    }
+
+    /// The report, or the failure which aborted the initialization
+    pub fn into_result(self) -> Result<Self, BeanError> {
+        match self.error() {
+            Some(e) => Err(e.clone()),
+            None => Ok(self),
+        }
+    }
+}
+
+impl Display for ResolutionReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for initializer in &self.failed_initializers {
+            writeln!(
+                f,
+                "failed initializer {}: {}",
+                initializer.name, initializer.error
+            )?;
+        }
+        for bean in &self.initialized {
+            writeln!(f, "ok {} ({:?})", bean.bean, bean.elapsed)?;
+        }
+        for bean in &self.failed {
+            let path: Vec<String> = bean.path.iter().map(|id| id.to_string()).collect();
+            let status = if bean.tolerated {
+                "tolerated"
+            } else {
+                "failed"
+            };
+            writeln!(f, "{} {}: {}", status, path.join(" -> "), bean.error)?;
+        }
+        for bean in &self.skipped {
+            writeln!(f, "skipped {}: {}", bean.bean, bean.reason)?;
+        }
+        Ok(())
+    }
+}
+
+impl BeanContainer {
+    /// Create every singleton in registration order, so misconfiguration shows at startup
+    ///
+    /// The one-time initializers (see [`once`](Self::once)) run first, in the order of
+    /// [`start`](Self::start). The first failure aborts, except of optional beans in [`InitMode::WarnAndContinue`] and
+    /// of any bean in [`InitMode::Continue`]: the singletons left are then reported as skipped.
+    /// See [`ResolutionReport::into_result`] to propagate the failure.
+    pub fn initialize_all(&self, mode: InitMode) -> ResolutionReport {
+        let mut report = ResolutionReport::default();
+        let phases = self.startup_phases.read().unwrap().clone();
+        report.failed_initializers = self
+            .initializers
+            .run_all(&phases, mode == InitMode::Continue)
+            .into_iter()
+            .map(|(name, error)| FailedInitializer { name, error })
+            .collect();
+        let mut aborted_by = match report.failed_initializers.first() {
+            Some(failed) if mode != InitMode::Continue => {
+                Some(format!("initializer {}", failed.name))
+            }
+            _ => None,
+        };
+        for info in self.beans() {
+            if info.scope != Scope::Singleton || info.id.is_alias() {
+                continue;
+            }
+            if let Some(cause) = &aborted_by {
+                report.skipped.push(SkippedBean {
+                    bean: info.name,
+                    reason: format!("initialization aborted by {}", cause),
+                });
+                continue;
+            }
+            let started = Instant::now();
+            match self.resolve_id(&info.id) {
+                Ok(_) => report.initialized.push(InitializedBean {
+                    bean: info.id,
+                    elapsed: started.elapsed(),
+                }),
+                Err(error) => {
+                    let tolerated = info.optional && mode == InitMode::WarnAndContinue;
+                    if tolerated {
+                        self.mark_unavailable(&info.id, error.clone());
+                    } else if mode != InitMode::Continue {
+                        aborted_by = Some(info.id.to_string());
+                    }
+                    // Missing beans are not in the dependency graph, link them to the failed bean
+                    let path = match error.bean().filter(|cause| *cause != info.id) {
+                        Some(cause) => self
+                            .dependency_path(info.id, &[cause])
+                            .unwrap_or_else(|| vec![info.id, cause]),
+                        None => vec![info.id],
+                    };
+                    report.failed.push(FailedBean {
+                        bean: info.id,
+                        path,
+                        error,
+                        tolerated,
+                    });
+                }
+            }
+        }
+        for (bean, cfg) in self.gated_out() {
+            report.skipped.push(SkippedBean {
+                bean,
+                reason: format!("requires cfg({})", cfg),
+            });
+        }
+        report
+    }
+
+    /// Create every singleton in registration order, continuing past failures
+    ///
+    /// Same as [`initialize_all`](Self::initialize_all) with [`InitMode::Continue`]. Dependents
+    /// of a failed bean fail with its error.
+    pub fn init_all(&self) -> ResolutionReport {
+        self.initialize_all(InitMode::Continue)
+    }
+
+    /// Order of the startup phases, e.g. `&["infrastructure", "domain", "web"]`
+    ///
+    /// See [`Registration::phase`](crate::bean::Registration::phase)
+    pub fn set_startup_phases(&self, phases: &[&str]) {
+        *self.startup_phases.write().unwrap() = phases.iter().map(|p| p.to_string()).collect();
+    }
+
+    /// Create the beans of each startup phase in order, then the other eager beans, in
+    /// registration order within a phase, and notify observers with the startup summary
+    ///
+    /// One-time initializers (see [`once`](Self::once)) without a phase run first, those of a
+    /// phase before its beans, and those of an undeclared phase before the eager beans.
+    ///
+    /// Observers are notified when a phase completes. A phase with a failure is not completed,
+    /// and neither the later phases nor the other eager beans and initializers start. Failures
+    /// are reported as warnings, the first one is returned. Beans of a phase not declared with [`set_startup_phases`](Self::set_startup_phases)
+    /// are created with the eager beans.
+    pub fn start(&self) -> Result<StartupSummary, BeanError> {
+        let beans: Vec<_> = self
+            .beans()
+            .into_iter()
+            .filter(|info| !info.id.is_alias())
+            .collect();
+        let phases = self.startup_phases.read().unwrap().clone();
+        let mut first_failure = self.initializers.run_phase(None).err();
+        for phase in &phases {
+            if first_failure.is_some() {
+                break;
+            }
+            let started = Instant::now();
+            let mut created = Vec::new();
+            if let Err(e) = self.initializers.run_phase(Some(phase)) {
+                first_failure = Some(e);
+                break;
+            }
+            for info in beans.iter().filter(|i| i.phase.as_ref() == Some(phase)) {
+                match self.resolve_id(&info.id) {
+                    Ok(_) => created.push(info.id),
+                    Err(e) => {
+                        first_failure.get_or_insert(e);
+                    }
+                }
+            }
+            if first_failure.is_some() {
+                break;
+            }
+            self.notify(|o| o.on_phase_completed(phase, &created, started.elapsed()));
+        }
+        if first_failure.is_none()
+            && let Err(e) = self.initializers.run_undeclared(&phases)
+        {
+            first_failure = Some(e);
+        }
+        if first_failure.is_none() {
+            for info in &beans {
+                let in_phase = info.phase.as_ref().is_some_and(|p| phases.contains(p));
+                if (info.eager || info.phase.is_some())
+                    && !in_phase
+                    && let Err(e) = self.resolve_id(&info.id)
+                {
+                    first_failure.get_or_insert(e);
+                }
+            }
+        }
+        let summary = self.startup_summary();
+        self.notify(|o| o.on_started(&summary));
+        match first_failure {
+            Some(e) => Err(e),
+            None => Ok(summary),
+        }
+    }
+
+    /// Bean counts by scope, active profiles, eager beans initialized and warnings
+    pub fn startup_summary(&self) -> StartupSummary {
+        let mut summary = StartupSummary {
+            beans: 0,
+            singletons: 0,
+            prototypes: 0,
+            cached: 0,
+            pooled: 0,
+            scoped: Vec::new(),
+            active_profiles: self.active_profiles(),
+            eager_initialized: Vec::new(),
+            warnings: Vec::new(),
+        };
+        for info in self.beans() {
+            if info.id.is_alias() {
+                continue;
+            }
+            summary.beans += 1;
+            match info.scope {
+                Scope::Singleton => summary.singletons += 1,
+                Scope::Prototype => summary.prototypes += 1,
+                #[cfg(feature = "scopes")]
+                Scope::Cached(_) => summary.cached += 1,
+                #[cfg(feature = "scopes")]
+                Scope::Pooled(_) => summary.pooled += 1,
+                #[cfg(feature = "scopes")]
+                Scope::Scoped(name) => match summary.scoped.iter_mut().find(|(s, _)| *s == name) {
+                    Some((_, count)) => *count += 1,
+                    None => summary.scoped.push((name, 1)),
+                },
+            }
+            if info.eager || info.phase.is_some() {
+                match info.state {
+                    BeanState::Ready => summary.eager_initialized.push(info.name.clone()),
+                    BeanState::Failed => summary
+                        .warnings
+                        .push(format!("{} failed to initialize", info.name)),
+                    _ => {}
+                }
+            }
+            if let Some(note) = &info.deprecated {
+                summary
+                    .warnings
+                    .push(format!("{} is deprecated: {}", info.name, note));
+            }
+        }
+        for initializer in self.initializers() {
+            if let InitializerState::Failed(e) = initializer.state {
+                summary
+                    .warnings
+                    .push(format!("initializer {} failed: {}", initializer.name, e));
+            }
+        }
+        summary
+    }
+}
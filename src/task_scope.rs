@@ -0,0 +1,52 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::bean::BeanContainer;
+use crate::scope::{ScopeMarker, ScopeState};
+
+tokio::task_local! {
+    // Scopes entered by the current task, innermost last
+    static TASK_SCOPES: Vec<Arc<ScopeState>>;
+}
+
+/// Scopes of the container entered by the current task, innermost last
+pub(crate) fn task_scopes(container: u64) -> Vec<Arc<ScopeState>> {
+    TASK_SCOPES
+        .try_with(|scopes| {
+            scopes
+                .iter()
+                .filter(|s| s.container() == container)
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Disposes the scope when the future completes or is dropped
+struct Disposal(Arc<ScopeState>);
+
+impl Drop for Disposal {
+    fn drop(&mut self) {
+        self.0.dispose();
+    }
+}
+
+impl BeanContainer {
+    /// Run `future` in a scope bound to the current tokio task
+    ///
+    /// Beans registered with `Scope::Scoped(name)` and resolved anywhere in the future, on
+    /// whichever worker thread it is polled, share one instance. They are disposed once the
+    /// future completes or is dropped. Tasks spawned from the future don't inherit the scope.
+    pub async fn in_task_scope<F: Future>(&self, name: &'static str, future: F) -> F::Output {
+        let state = self.new_scope_state(name);
+        let mut scopes = TASK_SCOPES.try_with(Clone::clone).unwrap_or_default();
+        scopes.push(state.clone());
+        let _disposal = Disposal(state);
+        TASK_SCOPES.scope(scopes, future).await
+    }
+
+    /// Run `future` in a task scope keyed by a typed marker, see [`in_task_scope`](Self::in_task_scope)
+    pub async fn in_task_scope_of<M: ScopeMarker, F: Future>(&self, future: F) -> F::Output {
+        self.in_task_scope(M::NAME, future).await
+    }
+}
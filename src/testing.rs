@@ -0,0 +1,450 @@
+use std::any::{Any, TypeId, type_name};
+use std::collections::HashMap;
+use std::ops::Deref;
+#[cfg(feature = "config")]
+use std::sync::Condvar;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+#[cfg(feature = "config")]
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::bean::{BeanContainer, BeanFactory, BeanId, Dependencies, Identifier};
+#[cfg(feature = "config")]
+use crate::clock::{Clock, TimeSource};
+use crate::error::BeanError;
+
+/// Failures injected with [`BeanContainer::fail_next`], with the number of resolutions left,
+/// shared by the identifiers of the bean
+#[derive(Default)]
+pub(crate) struct InjectedFailures {
+    failures: Mutex<HashMap<Identifier, (Arc<AtomicUsize>, BeanError)>>,
+}
+
+impl InjectedFailures {
+    /// Consume one injected failure of the bean, if any is left
+    pub(crate) fn take(&self, id: &Identifier) -> Option<BeanError> {
+        let mut failures = self.failures.lock().unwrap();
+        let (left, error) = failures.get(id)?;
+        let error = error.clone();
+        if left.fetch_sub(1, Ordering::Relaxed) == 1 {
+            let left = left.clone();
+            failures.retain(|_, (l, _)| !Arc::ptr_eq(l, &left));
+        }
+        Some(error)
+    }
+}
+
+type Instance = Arc<dyn Any + Send + Sync>;
+
+/// Instances set with [`BeanContainer::override_bean`], latest last, with their guard's token
+#[derive(Default)]
+pub(crate) struct Overrides {
+    next_token: AtomicU64,
+    overrides: Mutex<HashMap<Identifier, Vec<(u64, Instance)>>>,
+}
+
+impl Overrides {
+    /// Instance overriding the bean, if any
+    pub(crate) fn get(&self, id: &Identifier) -> Option<Instance> {
+        let overrides = self.overrides.lock().unwrap();
+        overrides.get(id)?.last().map(|(_, inst)| inst.clone())
+    }
+}
+
+/// Restores the bean overridden with [`BeanContainer::override_bean`] when dropped
+///
+/// Guards of the same bean can be nested, each one removes its own instance only.
+#[must_use = "the override is removed when the guard is dropped"]
+pub struct OverrideGuard<'a> {
+    container: &'a BeanContainer,
+    ids: Vec<Identifier>,
+    token: u64,
+}
+
+impl Drop for OverrideGuard<'_> {
+    fn drop(&mut self) {
+        {
+            let mut overrides = self.container.overrides.overrides.lock().unwrap();
+            for id in &self.ids {
+                if let Some(stack) = overrides.get_mut(id) {
+                    stack.retain(|(token, _)| *token != self.token);
+                    if stack.is_empty() {
+                        overrides.remove(id);
+                    }
+                }
+            }
+        }
+        for id in &self.ids {
+            self.container.invalidate_dependents(id.clone());
+        }
+    }
+}
+
+/// Stubs set with [`TestContainer::stub`], by type
+#[derive(Default)]
+pub(crate) struct Stubs {
+    stubs: RwLock<HashMap<TypeId, Arc<Stub>>>,
+}
+
+impl Stubs {
+    /// Stub of the type, if any
+    pub(crate) fn get(&self, type_id: TypeId) -> Option<Arc<Stub>> {
+        self.stubs.read().unwrap().get(&type_id).cloned()
+    }
+}
+
+/// Fallback of a type, created once on the first lookup finding no bean of the type
+pub(crate) struct Stub {
+    id: Identifier,
+    factory: Arc<dyn BeanFactory>,
+    instance: Mutex<Option<Instance>>,
+}
+
+impl Stub {
+    pub(crate) fn resolve(&self, deps: &mut Dependencies) -> Result<Instance, BeanError> {
+        if let Some(instance) = &*self.instance.lock().unwrap() {
+            return Ok(instance.clone());
+        }
+        // Created without the lock, so stubs can depend on stubs
+        let created = self.factory.create(deps)?;
+        Ok(self.instance.lock().unwrap().get_or_insert(created).clone())
+    }
+}
+
+/// Container for unit tests which falls back to stubs for the beans not registered, so one
+/// service can be tested without wiring its whole transitive graph
+///
+/// Derefs to the wrapped container. Stubs are not beans: a lookup by type finding no bean of
+/// the type, registered by type or by name, falls back to the stub, created once. A real
+/// definition of the type therefore takes precedence, whatever the registration order.
+///
+/// ```text
+/// let container = TestContainer::new();
+/// container.stub::<Database, _>(|_| Ok(Database::in_memory()))?.stub_default::<Metrics>()?;
+/// container.register::<OrderService, _>(Scope::Singleton, |deps| Ok(OrderService::new(deps.get()?)))?;
+/// ```
+pub struct TestContainer {
+    container: BeanContainer,
+}
+
+impl TestContainer {
+    pub fn new() -> Self {
+        Self::with_container(BeanContainer::new())
+    }
+
+    /// Wrap a container, e.g. one with the application's registrations
+    pub fn with_container(container: BeanContainer) -> Self {
+        TestContainer { container }
+    }
+
+    /// Build `T` with `factory` unless a real definition of `T` is registered
+    ///
+    /// Fails with [`BeanError::AlreadyRegistered`] if `T` is already stubbed.
+    pub fn stub<T, F>(&self, factory: F) -> Result<&Self, BeanError>
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(&mut Dependencies) -> Result<T, BeanError> + Send + Sync + 'static,
+    {
+        let id = Identifier::type_spec::<T>();
+        let factory: Arc<dyn BeanFactory> =
+            Arc::new(move |deps: &mut Dependencies| Ok(Arc::new(factory(deps)?) as Instance));
+        let mut stubs = self.container.stubs.stubs.write().unwrap();
+        if stubs.contains_key(&TypeId::of::<T>()) {
+            return Err(BeanError::AlreadyRegistered(BeanId::new(&id)));
+        }
+        stubs.insert(
+            TypeId::of::<T>(),
+            Arc::new(Stub {
+                id,
+                factory,
+                instance: Mutex::new(None),
+            }),
+        );
+        Ok(self)
+    }
+
+    /// Use `T::default()` unless a real definition of `T` is registered
+    pub fn stub_default<T: Default + Any + Send + Sync + 'static>(
+        &self,
+    ) -> Result<&Self, BeanError> {
+        self.stub::<T, _>(|_| Ok(T::default()))
+    }
+
+    /// Stubs in effect, i.e. of the types without a real definition
+    pub fn stubs(&self) -> Vec<BeanId> {
+        let stubs: Vec<Identifier> = {
+            let stubs = self.container.stubs.stubs.read().unwrap();
+            stubs.values().map(|stub| stub.id.clone()).collect()
+        };
+        let mut stubs: Vec<BeanId> = stubs
+            .into_iter()
+            .filter(|id| !self.container.is_registered(id))
+            .map(|id| BeanId::new(&id))
+            .collect();
+        stubs.sort_by_key(|id| id.to_string());
+        stubs
+    }
+
+    /// The wrapped container
+    pub fn into_inner(self) -> BeanContainer {
+        self.container
+    }
+}
+
+impl Default for TestContainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for TestContainer {
+    type Target = BeanContainer;
+
+    fn deref(&self) -> &BeanContainer {
+        &self.container
+    }
+}
+
+/// Time source advanced manually, making time-dependent beans deterministic in tests
+///
+/// Clones share their time. Sleeping blocks until the clock is advanced past the deadline.
+///
+/// ```text
+/// let clock = TestClock::new();
+/// container.register_clock(clock.clock())?;
+/// clock.advance(Duration::from_secs(60));
+/// ```
+#[cfg(feature = "config")]
+#[derive(Clone)]
+pub struct TestClock {
+    started: (Instant, SystemTime),
+    elapsed: Arc<(Mutex<Duration>, Condvar)>,
+}
+
+#[cfg(feature = "config")]
+impl TestClock {
+    /// Clock starting at the current time, then frozen until advanced
+    pub fn new() -> Self {
+        TestClock {
+            started: (Instant::now(), SystemTime::now()),
+            elapsed: Arc::new((Mutex::new(Duration::ZERO), Condvar::new())),
+        }
+    }
+
+    /// [`Clock`] reading this time source
+    pub fn clock(&self) -> Clock {
+        Clock::new(self.clone())
+    }
+
+    /// Move the time forward, waking the sleepers whose deadline passed
+    pub fn advance(&self, duration: Duration) {
+        let (elapsed, cond) = &*self.elapsed;
+        *elapsed.lock().unwrap() += duration;
+        cond.notify_all();
+    }
+
+    /// Time advanced since the clock was created
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.0.lock().unwrap()
+    }
+}
+
+#[cfg(feature = "config")]
+impl Default for TestClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "config")]
+impl TimeSource for TestClock {
+    fn now(&self) -> Instant {
+        self.started.0 + self.elapsed()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        self.started.1 + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        let (elapsed, cond) = &*self.elapsed;
+        let mut current = elapsed.lock().unwrap();
+        let deadline = *current + duration;
+        while *current < deadline {
+            current = cond.wait(current).unwrap();
+        }
+    }
+}
+
+impl BeanContainer {
+    /// Make the next `n` resolutions of the bean fail with `error`, for resilience tests
+    ///
+    /// Resolutions of cached singletons and resolutions as a dependency count too, so
+    /// fallbacks, retries and health checks can be exercised deterministically
+    pub fn fail_next<T: Any + Send + Sync + 'static>(
+        &self,
+        n: usize,
+        error: BeanError,
+    ) -> Result<(), BeanError> {
+        self.inject_failures::<T>(None, n, error)
+    }
+
+    /// Make the next `n` resolutions of the named bean fail with `error`, see [`fail_next`](Self::fail_next)
+    pub fn fail_next_named<T: Any + Send + Sync + 'static>(
+        &self,
+        name: &str,
+        n: usize,
+        error: BeanError,
+    ) -> Result<(), BeanError> {
+        self.inject_failures::<T>(Some(name), n, error)
+    }
+
+    /// Resolve `T` to `instance` until the returned guard is dropped, e.g. to inject a mock in a
+    /// unit test without rebuilding the container
+    ///
+    /// Works whatever the scope, and hides the cached singleton without dropping it, so the
+    /// original instance is handed out again once restored. The cached dependents of `T` are
+    /// dropped when overriding and when restoring, so they are created again with the instance
+    /// in effect.
+    pub fn override_bean<T: Any + Send + Sync + 'static>(
+        &self,
+        instance: T,
+    ) -> Result<OverrideGuard<'_>, BeanError> {
+        self.override_instance(None, instance)
+    }
+
+    /// Resolve the named bean to `instance` until the returned guard is dropped, see
+    /// [`override_bean`](Self::override_bean)
+    pub fn override_bean_named<T: Any + Send + Sync + 'static>(
+        &self,
+        name: &str,
+        instance: T,
+    ) -> Result<OverrideGuard<'_>, BeanError> {
+        self.override_instance(Some(name), instance)
+    }
+
+    /// Drop the failures injected and not consumed yet
+    pub fn clear_injected_failures(&self) {
+        self.injected_failures.failures.lock().unwrap().clear();
+    }
+
+    /// Assert that `A` depends on a bean of type `B`, directly or transitively, e.g. to enforce
+    /// architecture rules in unit tests
+    ///
+    /// Follows the declared dependencies, the captured ones and those of providers and proxies
+    /// to their target. `A` is resolved first, so that its dependencies are captured
+    /// **NOTE**: panics if the assertion fails or `A` can't be resolved
+    #[track_caller]
+    pub fn assert_depends<A, B>(&self)
+    where
+        A: Any + Send + Sync + 'static,
+        B: Any + Send + Sync + 'static,
+    {
+        if self.type_dependency_path::<A, B>().is_none() {
+            panic!(
+                "{} does not depend on {}",
+                type_name::<A>(),
+                type_name::<B>()
+            );
+        }
+    }
+
+    /// Assert that `A` doesn't depend on any bean of type `B`, directly or transitively,
+    /// see [`assert_depends`](Self::assert_depends)
+    /// **NOTE**: panics with the offending path if the assertion fails, or if `A` can't be resolved
+    #[track_caller]
+    pub fn assert_not_depends<A, B>(&self)
+    where
+        A: Any + Send + Sync + 'static,
+        B: Any + Send + Sync + 'static,
+    {
+        if let Some(path) = self.type_dependency_path::<A, B>() {
+            panic!(
+                "{} depends on {}: {}",
+                type_name::<A>(),
+                type_name::<B>(),
+                path
+            );
+        }
+    }
+
+    /// Shortest path from `A` to a bean of type `B`, see [`assert_depends`](Self::assert_depends)
+    #[track_caller]
+    fn type_dependency_path<A, B>(&self) -> Option<String>
+    where
+        A: Any + Send + Sync + 'static,
+        B: Any + Send + Sync + 'static,
+    {
+        if let Err(e) = self.try_get::<A>(None) {
+            panic!("Cannot resolve {}: {}", type_name::<A>(), e);
+        }
+        let start = BeanId::new(&self.find_id::<A>(None)?);
+        let targets: Vec<BeanId> = self
+            .beans()
+            .into_iter()
+            .filter(|info| info.type_name == type_name::<B>())
+            .map(|info| info.id)
+            .collect();
+        let path = self.dependency_path(start, &targets)?;
+        let path: Vec<String> = path.iter().map(|id| id.to_string()).collect();
+        Some(path.join(" -> "))
+    }
+
+    fn override_instance<T: Any + Send + Sync + 'static>(
+        &self,
+        name: Option<&str>,
+        instance: T,
+    ) -> Result<OverrideGuard<'_>, BeanError> {
+        let ids = self.registered_ids::<T>(name)?;
+        let token = self.overrides.next_token.fetch_add(1, Ordering::Relaxed);
+        let instance: Instance = Arc::new(instance);
+        {
+            let mut overrides = self.overrides.overrides.lock().unwrap();
+            for id in &ids {
+                overrides
+                    .entry(id.clone())
+                    .or_default()
+                    .push((token, instance.clone()));
+            }
+        }
+        for id in &ids {
+            self.invalidate_dependents(id.clone());
+        }
+        Ok(OverrideGuard {
+            container: self,
+            ids,
+            token,
+        })
+    }
+
+    fn inject_failures<T: Any + Send + Sync + 'static>(
+        &self,
+        name: Option<&str>,
+        n: usize,
+        error: BeanError,
+    ) -> Result<(), BeanError> {
+        let ids = self.registered_ids::<T>(name)?;
+        let mut failures = self.injected_failures.failures.lock().unwrap();
+        let left = Arc::new(AtomicUsize::new(n));
+        for id in ids {
+            if n == 0 {
+                failures.remove(&id);
+            } else {
+                failures.insert(id, (left.clone(), error.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Identifiers of the bean, so it is affected however it is resolved, e.g. a named bean
+    /// and its by-type alias
+    fn registered_ids<T: Any + Send + Sync + 'static>(
+        &self,
+        name: Option<&str>,
+    ) -> Result<Vec<Identifier>, BeanError> {
+        self.find_id::<T>(name)
+            .filter(|_| self.contains::<T>(name))
+            .map(|id| self.definition_ids(&id))
+            .ok_or_else(|| BeanError::NotFound(BeanId::new(&Identifier::type_spec::<T>())))
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::bean::{BeanContainer, BeanId};
+use crate::error::BeanError;
+
+/// Outcome of a traced resolution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TraceOutcome {
+    /// Existing instance handed out
+    Reused = 0,
+    /// Factory ran
+    Created = 1,
+    Failed = 2,
+}
+
+/// One resolution recorded by the trace, see [`BeanContainer::enable_trace`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub bean: BeanId,
+    /// Bucket `b` holds durations below `2^b` microseconds, and from `2^(b-1)` for `b > 0`
+    pub duration_bucket: u8,
+    pub outcome: TraceOutcome,
+}
+
+impl TraceEntry {
+    /// Upper bound of the duration bucket
+    pub fn max_duration(&self) -> Duration {
+        Duration::from_micros(1u64 << self.duration_bucket.min(63))
+    }
+}
+
+// Set on written slots, so a zero slot is empty
+const WRITTEN: u64 = 1 << 63;
+
+/// Lock-free ring buffer of the last resolutions, each packed into one `u64`:
+/// bean index in the low 32 bits, then the duration bucket and the outcome, one byte each
+pub(crate) struct ResolutionTrace {
+    slots: Box<[AtomicU64]>,
+    next: AtomicU64,
+}
+
+impl ResolutionTrace {
+    fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1).next_power_of_two();
+        ResolutionTrace {
+            slots: (0..capacity).map(|_| AtomicU64::new(0)).collect(),
+            next: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn record(&self, bean: BeanId, elapsed: Duration, outcome: TraceOutcome) {
+        let micros = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        let bucket = (u64::BITS - micros.leading_zeros()) as u64;
+        let entry = WRITTEN | (outcome as u64) << 40 | bucket << 32 | bean.index() as u64;
+        let slot = self.next.fetch_add(1, Ordering::Relaxed) as usize & (self.slots.len() - 1);
+        self.slots[slot].store(entry, Ordering::Relaxed);
+    }
+
+    /// Recorded entries, oldest first
+    ///
+    /// Entries written while reading may be missing or out of order
+    fn entries(&self) -> Vec<TraceEntry> {
+        let next = self.next.load(Ordering::Relaxed) as usize;
+        let capacity = self.slots.len();
+        let start = next.saturating_sub(capacity);
+        (start..next)
+            .filter_map(|n| {
+                let entry = self.slots[n & (capacity - 1)].load(Ordering::Relaxed);
+                if entry & WRITTEN == 0 {
+                    return None;
+                }
+                let outcome = match (entry >> 40) as u8 {
+                    0 => TraceOutcome::Reused,
+                    1 => TraceOutcome::Created,
+                    _ => TraceOutcome::Failed,
+                };
+                Some(TraceEntry {
+                    bean: BeanId::from_index(entry as u32),
+                    duration_bucket: (entry >> 32) as u8,
+                    outcome,
+                })
+            })
+            .collect()
+    }
+}
+
+impl BeanContainer {
+    /// Record the last `capacity` resolutions (rounded up to a power of two), including
+    /// resolutions of dependencies, in a fixed-size lock-free ring buffer
+    ///
+    /// Cheap enough to stay on in production: a resolution costs two clock reads, an id lookup
+    /// and one atomic write.
+    /// Only the first call has an effect.
+    pub fn enable_trace(&self, capacity: usize) {
+        self.trace.get_or_init(|| ResolutionTrace::new(capacity));
+    }
+
+    /// Resolutions recorded by the trace, oldest first, empty unless
+    /// [`enable_trace`](Self::enable_trace) was called
+    pub fn trace(&self) -> Vec<TraceEntry> {
+        self.trace
+            .get()
+            .map(ResolutionTrace::entries)
+            .unwrap_or_default()
+    }
+
+    /// Write the recorded resolutions to `path` in a compact binary format, returning the
+    /// number of entries written
+    ///
+    /// All integers are little-endian:
+    /// - header: magic `RSBT`, format version `1` as `u8`
+    /// - names: count as `u32`, then per bean its index as `u32`, the length of its name as `u16`
+    ///   and the UTF-8 name
+    /// - entries, oldest first: count as `u32`, then per entry the bean index as `u32`,
+    ///   the duration bucket and the outcome as `u8`
+    pub fn dump_trace(&self, path: impl AsRef<Path>) -> Result<usize, BeanError> {
+        let entries = self.trace();
+        let beans: BTreeSet<BeanId> = entries.iter().map(|e| e.bean).collect();
+
+        let mut out = Vec::with_capacity(16 + entries.len() * 6);
+        out.extend_from_slice(b"RSBT");
+        out.push(1);
+        out.extend_from_slice(&(beans.len() as u32).to_le_bytes());
+        for bean in beans {
+            let name = bean.to_string();
+            let name = &name.as_bytes()[..name.len().min(u16::MAX as usize)];
+            out.extend_from_slice(&bean.index().to_le_bytes());
+            out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            out.extend_from_slice(name);
+        }
+        out.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+        for entry in &entries {
+            out.extend_from_slice(&entry.bean.index().to_le_bytes());
+            out.push(entry.duration_bucket);
+            out.push(entry.outcome as u8);
+        }
+
+        std::fs::write(path.as_ref(), out).map_err(|e| {
+            BeanError::Io(format!(
+                "Failed to write {}: {}",
+                path.as_ref().display(),
+                e
+            ))
+        })?;
+        Ok(entries.len())
+    }
+}
@@ -0,0 +1,97 @@
+use std::any::Any;
+
+use crate::bean::{BeanContainer, BeanDef, BeanId, Dependencies, Scope};
+use crate::error::BeanError;
+
+/// Registration changes staged by [`BeanContainer::transaction`]
+///
+/// Nothing is visible to the container until the transaction commits
+pub struct Transaction<'a> {
+    container: &'a BeanContainer,
+    staged: Vec<BeanDef>,
+}
+
+impl Transaction<'_> {
+    /// Stage the registration of a bean by type
+    #[track_caller]
+    pub fn register<T, F>(&mut self, scope: Scope, factory: F) -> Result<(), BeanError>
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(&mut Dependencies) -> Result<T, BeanError> + Send + Sync + 'static,
+    {
+        self.stage(BeanDef::new::<T, F>(scope, factory))
+    }
+
+    /// Stage the registration of a named bean
+    #[track_caller]
+    pub fn register_named<T, F>(
+        &mut self,
+        name: &str,
+        scope: Scope,
+        factory: F,
+    ) -> Result<(), BeanError>
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(&mut Dependencies) -> Result<T, BeanError> + Send + Sync + 'static,
+    {
+        self.stage(BeanDef::new::<T, F>(scope, factory).named(name))
+    }
+
+    /// Stage the replacement of a bean registered by type
+    #[track_caller]
+    pub fn replace<T, F>(&mut self, scope: Scope, factory: F) -> Result<(), BeanError>
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(&mut Dependencies) -> Result<T, BeanError> + Send + Sync + 'static,
+    {
+        self.stage(BeanDef::new::<T, F>(scope, factory).replacing())
+    }
+
+    /// Stage the replacement of a named bean
+    #[track_caller]
+    pub fn replace_named<T, F>(
+        &mut self,
+        name: &str,
+        scope: Scope,
+        factory: F,
+    ) -> Result<(), BeanError>
+    where
+        T: Any + Send + Sync + 'static,
+        F: Fn(&mut Dependencies) -> Result<T, BeanError> + Send + Sync + 'static,
+    {
+        self.stage(BeanDef::new::<T, F>(scope, factory).named(name).replacing())
+    }
+
+    /// Stage a definition
+    ///
+    /// Conflicts with the container or other staged definitions fail early,
+    /// and are checked again when committing
+    pub fn stage(&mut self, def: BeanDef) -> Result<(), BeanError> {
+        let def = self.container.normalize(def);
+        if self.container.conflicts_with(&def) || self.staged.iter().any(|d| def.conflicts_with(d))
+        {
+            return Err(BeanError::AlreadyRegistered(BeanId::new(&def.key())));
+        }
+        self.staged.push(def);
+        Ok(())
+    }
+}
+
+impl BeanContainer {
+    /// Stage registrations and replacements, then apply them atomically
+    ///
+    /// If `f` or the commit fails, nothing is applied. Other threads never observe
+    /// a half-applied rewiring.
+    pub fn transaction<F>(&self, f: F) -> Result<(), BeanError>
+    where
+        F: FnOnce(&mut Transaction) -> Result<(), BeanError>,
+    {
+        let mut tx = Transaction {
+            container: self,
+            staged: Vec::new(),
+        };
+        f(&mut tx)?;
+        self.register_all(tx.staged)?;
+        Ok(())
+    }
+}
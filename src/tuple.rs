@@ -0,0 +1,49 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use crate::bean::Dependencies;
+use crate::error::BeanError;
+
+/// Tuple of bean types resolved together by [`Dependencies::get_many`], implemented for
+/// tuples of up to 12 types
+pub trait BeanTuple {
+    /// The tuple of instances, e.g. `(Arc<A>, Arc<B>)` for `(A, B)`
+    type Instances;
+
+    fn resolve(deps: &mut Dependencies) -> Result<Self::Instances, BeanError>;
+}
+
+macro_rules! bean_tuple {
+    ($($ty:ident),+) => {
+        impl<$($ty: Any + Send + Sync + 'static),+> BeanTuple for ($($ty,)+) {
+            type Instances = ($(Arc<$ty>,)+);
+
+            fn resolve(deps: &mut Dependencies) -> Result<Self::Instances, BeanError> {
+                Ok(($(deps.get::<$ty>()?,)+))
+            }
+        }
+    };
+}
+
+bean_tuple!(A);
+bean_tuple!(A, B);
+bean_tuple!(A, B, C);
+bean_tuple!(A, B, C, D);
+bean_tuple!(A, B, C, D, E);
+bean_tuple!(A, B, C, D, E, F);
+bean_tuple!(A, B, C, D, E, F, G);
+bean_tuple!(A, B, C, D, E, F, G, H);
+bean_tuple!(A, B, C, D, E, F, G, H, I);
+bean_tuple!(A, B, C, D, E, F, G, H, I, J);
+bean_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+bean_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+impl Dependencies<'_> {
+    /// Get beans of several types by type in one call, in order, e.g.
+    /// `let (db, users) = deps.get_many::<(Database, UserService)>()?;`
+    ///
+    /// Fails with the error of the first bean which can't be resolved
+    pub fn get_many<T: BeanTuple>(&mut self) -> Result<T::Instances, BeanError> {
+        T::resolve(self)
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use crate::bean::{BeanContainer, BeanId};
+use crate::error::BeanError;
+use crate::seal::strongly_connected;
+
+/// Dependency of a bean which is not registered, found by [`BeanContainer::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingDependency {
+    pub bean: BeanId,
+    pub dependency: BeanId,
+}
+
+/// Outcome of [`BeanContainer::validate`]
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    /// Broken edges, in registration order of the dependent bean
+    pub missing: Vec<MissingDependency>,
+    /// Beans depending on each other, each group in registration order
+    pub cycles: Vec<Vec<BeanId>>,
+}
+
+impl ValidationReport {
+    /// Whether no broken edge nor cycle was found
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.cycles.is_empty()
+    }
+
+    /// The report, or `NotFound` with the first missing dependency, or `CircularDependency`
+    /// with the first cycle
+    pub fn into_result(self) -> Result<Self, BeanError> {
+        if let Some(missing) = self.missing.first() {
+            return Err(BeanError::NotFound(missing.dependency));
+        }
+        if let Some(cycle) = self.cycles.first() {
+            return Err(BeanError::CircularDependency(cycle_path(cycle)));
+        }
+        Ok(self)
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for missing in &self.missing {
+            writeln!(
+                f,
+                "{} depends on {}, which is not registered",
+                missing.bean, missing.dependency
+            )?;
+        }
+        for cycle in &self.cycles {
+            writeln!(f, "Circular dependency: {}", cycle_path(cycle))?;
+        }
+        Ok(())
+    }
+}
+
+/// Beans of the cycle back to the first one, e.g. `Bean(a) -> Bean(b) -> Bean(a)`
+fn cycle_path(cycle: &[BeanId]) -> String {
+    let beans: Vec<String> = cycle
+        .iter()
+        .chain(&cycle[..1])
+        .map(|id| id.to_string())
+        .collect();
+    beans.join(" -> ")
+}
+
+impl BeanContainer {
+    /// Check the wiring without creating any bean, reporting every missing dependency and
+    /// every cycle at once
+    ///
+    /// Dependencies are the ones declared with
+    /// [`Registration::depends_on`](crate::bean::Registration::depends_on), plus the ones
+    /// observed while creating beans. Dependencies registered in a parent container are found.
+    pub fn validate(&self) -> ValidationReport {
+        let nodes: Vec<BeanId> = self.beans().into_iter().map(|info| info.id).collect();
+        let mut successors: HashMap<BeanId, Vec<BeanId>> = HashMap::new();
+        let mut missing = Vec::new();
+        for (bean, dependency, registered) in self.wiring_edges() {
+            if registered {
+                successors.entry(bean).or_default().push(dependency);
+            } else {
+                missing.push(MissingDependency { bean, dependency });
+            }
+        }
+
+        let cycles = strongly_connected(&nodes, &successors)
+            .into_iter()
+            .filter(|group| {
+                group.len() > 1
+                    || successors
+                        .get(&group[0])
+                        .is_some_and(|next| next.contains(&group[0]))
+            })
+            .map(|group| {
+                nodes
+                    .iter()
+                    .filter(|id| group.contains(id))
+                    .copied()
+                    .collect()
+            })
+            .collect();
+        ValidationReport { missing, cycles }
+    }
+}
@@ -0,0 +1,132 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::bean::{BeanContainer, Identifier};
+use crate::error::BeanError;
+
+/// Version of a watched bean, bumped whenever its definition is replaced
+#[derive(Default)]
+pub(crate) struct Watch {
+    version: Mutex<u64>,
+    cond: Condvar,
+}
+
+impl Watch {
+    fn bump(&self) {
+        *self.version.lock().unwrap() += 1;
+        self.cond.notify_all();
+    }
+}
+
+/// Watches keyed by the identifiers they observe
+#[derive(Default)]
+pub(crate) struct Watches {
+    watches: Mutex<HashMap<Identifier, Arc<Watch>>>,
+}
+
+impl Watches {
+    fn watch(&self, ids: &[Identifier]) -> Arc<Watch> {
+        let mut watches = self.watches.lock().unwrap();
+        let watch = ids
+            .iter()
+            .find_map(|id| watches.get(id).cloned())
+            .unwrap_or_default();
+        for id in ids {
+            watches.insert(id.clone(), watch.clone());
+        }
+        watch
+    }
+
+    /// Signal subscribers of the given beans
+    pub(crate) fn bump(&self, ids: &[Identifier]) {
+        let watches = self.watches.lock().unwrap();
+        for id in ids {
+            if let Some(watch) = watches.get(id) {
+                watch.bump();
+            }
+        }
+    }
+}
+
+/// Receiver of the current instance of a bean, then of every replacement,
+/// see [`BeanContainer::subscribe`]
+pub struct Subscription<'a, T> {
+    container: &'a BeanContainer,
+    name: Option<String>,
+    watch: Arc<Watch>,
+    // Version last received, none before the first call
+    seen: Option<u64>,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T: Any + Send + Sync + 'static> Subscription<'_, T> {
+    /// Current instance on first call, then block until the bean is replaced
+    pub fn recv(&mut self) -> Result<Arc<T>, BeanError> {
+        let mut version = self.watch.version.lock().unwrap();
+        if let Some(seen) = self.seen {
+            while *version == seen {
+                version = self.watch.cond.wait(version).unwrap();
+            }
+        }
+        self.seen = Some(*version);
+        drop(version);
+        self.current()
+    }
+
+    /// Like [`recv`](Self::recv), giving up after `timeout`
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Option<Result<Arc<T>, BeanError>> {
+        let deadline = Instant::now() + timeout;
+        let mut version = self.watch.version.lock().unwrap();
+        if let Some(seen) = self.seen {
+            while *version == seen {
+                let left = deadline.checked_duration_since(Instant::now())?;
+                version = self.watch.cond.wait_timeout(version, left).unwrap().0;
+            }
+        }
+        self.seen = Some(*version);
+        drop(version);
+        Some(self.current())
+    }
+
+    /// Whether the bean was replaced since last received
+    pub fn has_changed(&self) -> bool {
+        let version = *self.watch.version.lock().unwrap();
+        self.seen.is_none_or(|seen| seen != version)
+    }
+
+    /// Current instance, without marking it as received
+    pub fn current(&self) -> Result<Arc<T>, BeanError> {
+        self.container.try_get::<T>(self.name.as_deref())
+    }
+}
+
+impl BeanContainer {
+    /// Subscribe to the bean registered by type and its replacements
+    ///
+    /// Long-lived workers use it to pick up a new instance after [`replace`](Self::replace)
+    pub fn subscribe<T: Any + Send + Sync + 'static>(&self) -> Subscription<'_, T> {
+        let ids = [Identifier::type_spec::<T>(), Identifier::unnamed::<T>()];
+        self.subscription(None, &ids)
+    }
+
+    /// Subscribe to a named bean and its replacements
+    pub fn subscribe_named<T: Any + Send + Sync + 'static>(
+        &self,
+        name: &str,
+    ) -> Subscription<'_, T> {
+        self.subscription(Some(name), &[self.named_id(name)])
+    }
+
+    fn subscription<T>(&self, name: Option<&str>, ids: &[Identifier]) -> Subscription<'_, T> {
+        Subscription {
+            container: self,
+            name: name.map(str::to_string),
+            watch: self.watches().watch(ids),
+            seen: None,
+            _type: PhantomData,
+        }
+    }
+}